@@ -1,12 +1,28 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fs::Metadata;
+#[cfg(unix)]
 use std::os::unix::prelude::PermissionsExt;
+
 use anyhow::Result;
 extern crate clap;
 
+use rush_exec::builtin_arguments::{
+    ArchiveArguments, ChangePermissionsArguments, ChecksumArguments, ClearHistoryArguments,
+    CopyFileArguments, DateTimeArguments, DeleteDirectoryArguments, DeleteFileArguments,
+    DownloadArguments, EchoArguments, EditPathArguments, EnvironmentVariableArguments,
+    ExtractArguments, FileInfoArguments, FindFileArguments, HeadArguments, HistoryArguments,
+    ListDirectoryArguments, MakeDirectoryArguments, MakeFileArguments, MoveFileArguments,
+    ReadFileArguments, ReadInputArguments, SearchTextArguments, SymlinkArguments, TailArguments,
+    TrashArguments, WatchArguments,
+};
 use rush_exec::builtins;
-use rush_exec::commands::{Builtin, Executable, Runnable};
+use rush_exec::commands::{Builtin, Evaluator, Executable, Runnable};
+use rush_exec::errors::ExecutableError;
 use rush_state::console::Console;
 use rush_state::path::Path;
 use rush_state::shell::Shell;
+use rush_state::signature::CommandSignature;
 
 use crate::errors::DispatchError;
 use crate::parser;
@@ -15,8 +31,14 @@ use crate::parser;
 // Allows for command resolution and execution through aliases
 pub struct Dispatcher {
     commands: Vec<Builtin>,
+    // The number of command lines being evaluated inside one another, e.g. by 'history run' or 'source'
+    depth: Cell<usize>,
 }
 
+// The most command lines that can be evaluated inside one another
+// * A command that runs itself (e.g. through its own history entry) would otherwise recurse until the stack overflows
+const MAX_EVAL_DEPTH: usize = 32;
+
 impl Default for Dispatcher {
     // Initializes the Dispatcher with the default shell commands and aliases
     #[rustfmt::skip]
@@ -24,21 +46,66 @@ impl Default for Dispatcher {
         let mut dispatcher = Self::new();
 
         dispatcher.add_builtin("test", vec!["t"], builtins::test);
+        dispatcher.add_builtin("echo", vec!["print", "say"], builtins::echo).arguments::<EchoArguments>();
         dispatcher.add_builtin("exit", vec!["quit", "q"], builtins::exit);
         dispatcher.add_builtin("working-directory", vec!["pwd", "wd"], builtins::working_directory);
         dispatcher.add_builtin("change-directory", vec!["cd"], builtins::change_directory);
-        dispatcher.add_builtin("list-directory", vec!["directory", "list", "ls", "dir"], builtins::list_directory);
+        dispatcher.add_builtin("list-directory", vec!["directory", "list", "ls", "dir"], builtins::list_directory).arguments::<ListDirectoryArguments>();
         dispatcher.add_builtin("previous-directory", vec!["back", "b", "prev", "pd"], builtins::go_back);
         dispatcher.add_builtin("next-directory", vec!["forward", "f", "next", "nd"], builtins::go_forward);
+        dispatcher.add_builtin("push-directory", vec!["pushd"], builtins::push_directory);
+        dispatcher.add_builtin("pop-directory", vec!["popd"], builtins::pop_directory);
+        dispatcher.add_builtin("directory-stack", vec!["dirs", "ds"], builtins::directory_stack);
+        dispatcher.add_builtin("directory-history", vec!["dh"], builtins::directory_history);
         dispatcher.add_builtin("clear-terminal", vec!["clear", "cls"], builtins::clear_terminal);
-        dispatcher.add_builtin("make-file", vec!["create", "touch", "new", "mf"], builtins::make_file);
-        dispatcher.add_builtin("make-directory", vec!["mkdir", "md"], builtins::make_directory);
-        dispatcher.add_builtin("delete-file", vec!["delete", "remove", "rm", "del", "df"], builtins::delete_file);
-        dispatcher.add_builtin("read-file", vec!["read", "cat", "rf"], builtins::read_file);
+        dispatcher.add_builtin("make-file", vec!["create", "touch", "new", "mf"], builtins::make_file).arguments::<MakeFileArguments>();
+        dispatcher.add_builtin("make-directory", vec!["mkdir", "md"], builtins::make_directory).arguments::<MakeDirectoryArguments>();
+        dispatcher.add_builtin("delete-directory", vec!["rmdir", "rd"], builtins::delete_directory).arguments::<DeleteDirectoryArguments>();
+        dispatcher.add_builtin("symlink", vec!["link", "ln"], builtins::symlink).arguments::<SymlinkArguments>();
+        dispatcher.add_builtin("file-info", vec!["stat", "info", "fi"], builtins::file_info).arguments::<FileInfoArguments>();
+        dispatcher.add_builtin("checksum", vec!["sum", "cs"], builtins::checksum).arguments::<ChecksumArguments>();
+        dispatcher.add_builtin("archive", vec!["compress", "ar"], builtins::archive).arguments::<ArchiveArguments>();
+        dispatcher.add_builtin("extract", vec!["decompress", "unarchive", "ex"], builtins::extract).arguments::<ExtractArguments>();
+        dispatcher.add_builtin("download", vec!["fetch", "dl"], builtins::download).arguments::<DownloadArguments>();
+        dispatcher.add_builtin("delete-file", vec!["delete", "remove", "rm", "del", "df"], builtins::delete_file).arguments::<DeleteFileArguments>();
+        dispatcher.add_builtin("trash", vec!["recycle", "tr"], builtins::trash).arguments::<TrashArguments>();
+        dispatcher.add_builtin("copy-file", vec!["copy", "cp", "cf"], builtins::copy_file).arguments::<CopyFileArguments>();
+        dispatcher.add_builtin("move-file", vec!["move", "rename", "mv"], builtins::move_file).arguments::<MoveFileArguments>();
+        dispatcher.add_builtin("find-file", vec!["find", "ff"], builtins::find_file).arguments::<FindFileArguments>();
+        dispatcher.add_builtin("search-text", vec!["search", "grep", "st"], builtins::search_text).arguments::<SearchTextArguments>();
+        dispatcher.add_builtin("change-permissions", vec!["permissions", "chmod"], builtins::change_permissions).arguments::<ChangePermissionsArguments>();
+        dispatcher.add_builtin("read-file", vec!["read", "cat", "rf"], builtins::read_file).arguments::<ReadFileArguments>();
+        dispatcher.add_builtin("read-head", vec!["head"], builtins::read_head).arguments::<HeadArguments>();
+        dispatcher.add_builtin("read-tail", vec!["tail"], builtins::read_tail).arguments::<TailArguments>();
         dispatcher.add_builtin("run-executable", vec!["run", "exec", "re"], builtins::run_executable);
+        dispatcher.add_builtin("run-with-priority", vec!["nice", "rp"], builtins::run_with_priority);
+        dispatcher.add_builtin("run-in-background", vec!["background", "rb"], builtins::run_in_background);
+        dispatcher.add_builtin("list-jobs", vec!["jobs", "lj"], builtins::list_jobs);
+        dispatcher.add_builtin("detach-job", vec!["disown", "detach", "dj"], builtins::detach_job);
+        dispatcher.add_builtin("show-job", vec!["follow-job", "sj"], builtins::show_job);
+        dispatcher.add_builtin("date-time", vec!["date", "time", "dt"], builtins::date_time).arguments::<DateTimeArguments>();
+        dispatcher.add_builtin("open", vec!["launch", "start"], builtins::open);
+        dispatcher.add_builtin("keybind", vec!["bind", "kb"], builtins::keybind);
+        dispatcher.add_builtin("theme", vec!["colors", "th"], builtins::theme);
+        dispatcher.add_builtin("calculate", vec!["calc", "math"], builtins::calculate);
+        dispatcher.add_builtin("sleep", vec!["pause"], builtins::sleep);
+        dispatcher.add_builtin_with_evaluator("watch", vec!["repeat", "w"], builtins::watch).arguments::<WatchArguments>();
         dispatcher.add_builtin("configure", vec!["config", "conf"], builtins::configure);
-        dispatcher.add_builtin("environment-variable", vec!["environment", "env", "ev"], builtins::environment_variable);
-        dispatcher.add_builtin("edit-path", vec!["path", "ep"], builtins::edit_path);
+        dispatcher.add_builtin("reload-config", vec!["reload", "rc"], builtins::reload_config);
+        dispatcher.add_builtin("environment-variable", vec!["environment", "env", "ev"], builtins::environment_variable).arguments::<EnvironmentVariableArguments>();
+        dispatcher.add_builtin("set-variable", vec!["set", "sv"], builtins::set_variable);
+        dispatcher.add_builtin("export-variable", vec!["export", "xv"], builtins::export_variable);
+        dispatcher.add_builtin("read-input", vec!["input", "ri"], builtins::read_input).arguments::<ReadInputArguments>();
+        dispatcher.add_builtin("unset-variable", vec!["unset", "uv"], builtins::unset_variable);
+        dispatcher.add_builtin("edit-path", vec!["path", "ep"], builtins::edit_path).arguments::<EditPathArguments>();
+        dispatcher.add_builtin("set-alias", vec!["alias"], builtins::set_alias);
+        dispatcher.add_builtin("remove-alias", vec!["unalias"], builtins::remove_alias);
+        dispatcher.add_builtin_with_evaluator("history", vec!["hist", "h"], builtins::history).arguments::<HistoryArguments>();
+        dispatcher.add_builtin("clear-history", vec!["ch"], builtins::clear_history).arguments::<ClearHistoryArguments>();
+        dispatcher.add_builtin_with_evaluator("source", vec!["."], builtins::source);
+        dispatcher.add_builtin("rehash", vec!["hash", "rh"], builtins::rehash);
+        dispatcher.add_builtin_with_evaluator("where", vec!["which", "wh"], builtins::where_command);
+        dispatcher.add_builtin("show-last-error", vec!["last-error", "error", "le"], builtins::show_last_error);
 
         dispatcher
     }
@@ -48,6 +115,7 @@ impl Dispatcher {
     pub fn new() -> Self {
         Self {
             commands: Vec::new(),
+            depth: Cell::new(0),
         }
     }
 
@@ -57,9 +125,24 @@ impl Dispatcher {
         true_name: &str,
         aliases: Vec<&str>,
         function: F,
-    ) {
+    ) -> &mut Builtin {
+        self.commands
+            .push(Builtin::new(true_name, aliases, function));
+        self.commands.last_mut().unwrap()
+    }
+
+    // Adds a builtin that can run other commands through the Dispatcher
+    fn add_builtin_with_evaluator<
+        F: Fn(&dyn Evaluator, &mut Shell, &mut Console, Vec<&str>) -> Result<()> + 'static,
+    >(
+        &mut self,
+        true_name: &str,
+        aliases: Vec<&str>,
+        function: F,
+    ) -> &mut Builtin {
         self.commands
-            .push(Builtin::new(true_name, aliases, function))
+            .push(Builtin::with_evaluator(true_name, aliases, function));
+        self.commands.last_mut().unwrap()
     }
 
     // Finds a builtin command by name or alias
@@ -78,12 +161,53 @@ impl Dispatcher {
         None
     }
 
+    // Gets the true name and aliases of every builtin, e.g. for completing command names
+    pub fn command_names(&self) -> Vec<String> {
+        self.commands
+            .iter()
+            .flat_map(|command| std::iter::once(&command.true_name).chain(command.aliases.iter()))
+            .cloned()
+            .collect()
+    }
+
+    // Gets the argument signature of every builtin that has one, keyed by its true name and each of its aliases
+    pub fn command_signatures(&self) -> HashMap<String, CommandSignature> {
+        let mut signatures = HashMap::new();
+        for command in &self.commands {
+            let Some(signature) = &command.signature else {
+                continue;
+            };
+
+            for name in std::iter::once(&command.true_name).chain(command.aliases.iter()) {
+                signatures.insert(name.clone(), signature.clone());
+            }
+        }
+
+        signatures
+    }
+
     // Evaluates and executes a command from a string
-    pub fn eval(&self, shell: &mut Shell, console: &mut Console, line: &String) -> Result<()> {
-        let commands = parser::parse(line);
+    pub fn eval(&self, shell: &mut Shell, console: &mut Console, line: &str) -> Result<()> {
+        let depth = self.depth.get();
+        if depth >= MAX_EVAL_DEPTH {
+            return Err(DispatchError::NestedTooDeeply(MAX_EVAL_DEPTH).into());
+        }
+
+        self.depth.set(depth + 1);
+        let result = self.eval_line(shell, console, line);
+        self.depth.set(depth);
+        result
+    }
+
+    fn eval_line(&self, shell: &mut Shell, console: &mut Console, line: &str) -> Result<()> {
+        let lookup = |name: &str| shell.env().get_var(name);
+        let line = parser::expand_arithmetic(line, lookup)?;
+        let commands = parser::parse(&line, lookup);
         let mut results: Vec<Result<()>> = Vec::new();
 
-        for (command_name, command_args) in commands {
+        for command in commands {
+            let (command_name, command_args) =
+                parser::expand_aliases(command, &shell.config().aliases);
             // ? Is there a way to avoid this type conversion?
             let command_name = command_name.as_str();
             let command_args = command_args.iter().map(|a| a.as_str()).collect();
@@ -102,6 +226,11 @@ impl Dispatcher {
         Ok(())
     }
 
+    // Evaluates every line of a script file, the same way as the source builtin
+    pub fn eval_file(&self, shell: &mut Shell, console: &mut Console, path: &str) -> Result<()> {
+        builtins::source_file(self, shell, console, path)
+    }
+
     // Resolves and dispatches a command to the appropriate function or external binary
     // If the command does not exist, returns None
     fn dispatch(
@@ -113,27 +242,416 @@ impl Dispatcher {
     ) -> Result<()> {
         // If the command resides in the Dispatcher (generally means it is a builtin) run it
         if let Some(command) = self.resolve(command_name) {
-            command.run(shell, console, command_args)
+            command.run(self, shell, console, command_args)
         } else {
-            // If the command is not in the Dispatcher, try to run it as an executable from the PATH
-            let path = Path::from_path_var(command_name, shell.env().PATH());
-            if let Ok(path) = path {
-                // Check if the file is executable (has the executable bit set)
-                if let Ok(metadata) = fs_err::metadata(path.path()) {
-                    let permission_code = metadata.permissions().mode();
-                    // 0o111 is the octal representation of 73, which is the executable bit
-                    if permission_code & 0o111 == 0 {
-                        Err(DispatchError::CommandNotExecutable(permission_code).into())
-                    } else {
-                        Executable::new(path).run(shell, console, command_args)
-                    }
-                } else {
-                    // If the file cannot be read, return an error
-                    Err(DispatchError::FailedToReadExecutableMetadata(path.to_string()).into())
-                }
-            } else {
-                Err(DispatchError::UnknownCommand(command_name.to_string()).into())
+            // If the command is not in the Dispatcher, try to run it as an executable
+            let result = self.run_executable(shell, console, command_name, command_args.clone());
+
+            // * An executable found through the cache may have been removed since, so the PATH is searched again once
+            // * (it was never started, so running it again is safe)
+            let missing = matches!(
+                result.as_ref().err().and_then(|e| e.downcast_ref()),
+                Some(ExecutableError::PathNoLongerExists(_))
+            );
+            if missing && !command_name.contains('/') {
+                shell.env_mut().invalidate_executables();
+                return self.run_executable(shell, console, command_name, command_args);
             }
+
+            result
         }
     }
+
+    // Finds an executable by path or in the PATH and runs it
+    fn run_executable(
+        &self,
+        shell: &mut Shell,
+        console: &mut Console,
+        command_name: &str,
+        command_args: Vec<&str>,
+    ) -> Result<()> {
+        // * Command names containing a slash are treated as paths (e.g. './script.py'), and everything else is looked up in the PATH
+        let path = if command_name.contains('/') {
+            Path::from_str(command_name, shell.env().HOME())
+        } else {
+            shell.env_mut().resolve_executable(command_name)
+        };
+
+        let Ok(path) = path else {
+            return Err(DispatchError::UnknownCommand(command_name.to_string()).into());
+        };
+
+        // Check if the file is executable (has the executable bit set)
+        let Ok(metadata) = fs_err::metadata(path.path()) else {
+            // If the file cannot be read, return an error
+            return Err(DispatchError::FailedToReadExecutableMetadata(path.to_string()).into());
+        };
+
+        let permission_code = permission_code(&metadata);
+        if is_executable(permission_code) {
+            return Executable::new(path).run(shell, console, command_args);
+        }
+
+        // Scripts without the executable bit can still be run if they have an associated interpreter
+        match Executable::from_script(shell, path)? {
+            Some(executable) => executable.run(shell, console, command_args),
+            None => Err(DispatchError::CommandNotExecutable(permission_code).into()),
+        }
+    }
+}
+
+impl Evaluator for Dispatcher {
+    fn eval(&self, shell: &mut Shell, console: &mut Console, line: &str) -> Result<()> {
+        Dispatcher::eval(self, shell, console, line)
+    }
+
+    fn run_command(
+        &self,
+        shell: &mut Shell,
+        console: &mut Console,
+        name: &str,
+        arguments: Vec<&str>,
+    ) -> Result<()> {
+        let command = (
+            name.to_string(),
+            arguments.into_iter().map(String::from).collect(),
+        );
+        let (name, arguments) = parser::expand_aliases(command, &shell.config().aliases);
+        let arguments = arguments.iter().map(|a| a.as_str()).collect();
+        self.dispatch(shell, console, &name, arguments)
+    }
+
+    fn builtin_name(&self, command_name: &str) -> Option<String> {
+        self.resolve(command_name)
+            .map(|builtin| builtin.true_name.clone())
+    }
+}
+
+// Gets the permission bits of a file
+// * Windows has no permission bits, so a placeholder value is used instead
+#[cfg(unix)]
+fn permission_code(metadata: &Metadata) -> u32 {
+    metadata.permissions().mode()
+}
+
+#[cfg(windows)]
+fn permission_code(_metadata: &Metadata) -> u32 {
+    0
+}
+
+// Checks whether a file's permission bits allow it to be executed
+#[cfg(unix)]
+fn is_executable(permission_code: u32) -> bool {
+    // 0o111 is the octal representation of 73, which is the executable bit
+    permission_code & 0o111 != 0
+}
+
+// * On Windows, whether a file is executable is determined by its extension (PATHEXT) rather than its permissions,
+// * and the PATH lookup has already taken care of that
+#[cfg(windows)]
+fn is_executable(_permission_code: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Creates a shell and a console that is drawn in memory, so that commands can be run without a terminal
+    fn headless_shell() -> (Shell, Console<'static>) {
+        // * The shell expects to be started from another shell, which is not always the case when testing
+        if std::env::var_os("USER").is_none() {
+            std::env::set_var("USER", "tester");
+        }
+
+        let shell = Shell::new().unwrap();
+        let console = Console::headless(80, 24).unwrap();
+        (shell, console)
+    }
+
+    #[test]
+    fn print_builtin_output_to_console() {
+        //given
+        let (mut shell, mut console) = headless_shell();
+        let dispatcher = Dispatcher::default();
+
+        //when
+        let result = dispatcher.eval(&mut shell, &mut console, "echo hello && echo world");
+
+        //then
+        assert!(result.is_ok());
+        assert_eq!(console.output_text(), vec!["hello", "world"]);
+    }
+
+    #[test]
+    fn return_error_for_unknown_command() {
+        //given
+        let (mut shell, mut console) = headless_shell();
+        let dispatcher = Dispatcher::default();
+
+        //when
+        let result = dispatcher.eval(&mut shell, &mut console, "not-a-real-command");
+
+        //then
+        let error = result.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<DispatchError>(),
+            Some(DispatchError::UnknownCommand(_))
+        ));
+    }
+
+    #[test]
+    fn keep_variables_local_until_exported() {
+        //given
+        let (mut shell, mut console) = headless_shell();
+        let dispatcher = Dispatcher::default();
+        let name = "RUSH_TEST_LOCAL_VARIABLE";
+
+        //when
+        let set = dispatcher.eval(&mut shell, &mut console, &format!("set {}=local", name));
+        let inherited_before_export = std::env::var(name).is_ok();
+        let export = dispatcher.eval(&mut shell, &mut console, &format!("export {}", name));
+        let inherited_after_export = std::env::var(name);
+        _ = dispatcher.eval(&mut shell, &mut console, &format!("echo ${}", name));
+
+        //then
+        assert!(set.is_ok() && export.is_ok());
+        assert!(!inherited_before_export);
+        assert_eq!(inherited_after_export.unwrap(), "local");
+        assert_eq!(console.output_text(), vec!["local"]);
+    }
+
+    #[test]
+    fn read_file_longer_than_a_page_without_pager() {
+        //given
+        let (mut shell, mut console) = headless_shell();
+        let dispatcher = Dispatcher::default();
+        let path = std::env::temp_dir().join(format!("rush-read-file-{}.txt", std::process::id()));
+        let contents: String = (1..=200)
+            .map(|number| format!("line {}\n", number))
+            .collect();
+        std::fs::write(&path, contents).unwrap();
+
+        //when
+        let line = format!("read-file -P {}", path.display());
+        let result = dispatcher.eval(&mut shell, &mut console, &line);
+        _ = std::fs::remove_file(&path);
+
+        //then
+        assert!(result.is_ok());
+        let output = console.output_text();
+        assert_eq!(output.len(), 200);
+        assert_eq!(output.last().unwrap(), "line 200");
+    }
+
+    #[test]
+    fn read_head_and_tail_of_file_that_is_not_utf8() {
+        //given
+        let (mut shell, mut console) = headless_shell();
+        let dispatcher = Dispatcher::default();
+        let path = std::env::temp_dir().join(format!("rush-read-bytes-{}.txt", std::process::id()));
+        std::fs::write(&path, b"\xff\xfea\nbc\n").unwrap();
+
+        //when
+        let head = dispatcher.eval(
+            &mut shell,
+            &mut console,
+            &format!("head {}", path.display()),
+        );
+        let tail = dispatcher.eval(
+            &mut shell,
+            &mut console,
+            &format!("tail {}", path.display()),
+        );
+        _ = std::fs::remove_file(&path);
+
+        //then
+        assert!(head.is_ok());
+        assert!(tail.is_ok());
+        let output = console.output_text();
+        assert_eq!(
+            output,
+            ["\u{fffd}\u{fffd}a", "bc", "\u{fffd}\u{fffd}a", "bc"]
+        );
+    }
+
+    #[test]
+    fn print_escapes_without_digits_as_written() {
+        //given
+        let (mut shell, mut console) = headless_shell();
+        let dispatcher = Dispatcher::default();
+
+        //when
+        let result = dispatcher.eval(
+            &mut shell,
+            &mut console,
+            r#"echo -e "[\x] [\0] [\x41\0101]""#,
+        );
+
+        //then
+        assert!(result.is_ok());
+        assert_eq!(console.output_text(), [r"[\x] [\0] [AA]"]);
+    }
+
+    #[test]
+    fn stop_command_that_runs_itself() {
+        //given
+        let (mut shell, mut console) = headless_shell();
+        let dispatcher = Dispatcher::default();
+        shell.history_add(String::from("history run 0"));
+
+        //when
+        let result = dispatcher.eval(&mut shell, &mut console, "history run 0");
+
+        //then
+        let error = result.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<DispatchError>(),
+            Some(DispatchError::NestedTooDeeply(_))
+        ));
+    }
+
+    #[test]
+    fn refuse_to_source_file_that_sources_itself() {
+        //given
+        let (mut shell, mut console) = headless_shell();
+        let dispatcher = Dispatcher::default();
+        let path = std::env::temp_dir().join(format!("rush-source-{}.rush", std::process::id()));
+        let contents = format!("source {}\necho after", path.display());
+        std::fs::write(&path, contents).unwrap();
+
+        //when
+        let result = dispatcher.eval_file(&mut shell, &mut console, &path.to_string_lossy());
+        _ = std::fs::remove_file(&path);
+
+        //then
+        assert!(result.is_err());
+        let output = console.output_text();
+        assert!(output[0].contains("File is already being sourced"));
+        assert_eq!(output[1], "after");
+    }
+
+    #[test]
+    fn refuse_to_copy_file_onto_itself() {
+        //given
+        let (mut shell, mut console) = headless_shell();
+        let dispatcher = Dispatcher::default();
+        let directory = std::env::temp_dir().join(format!("rush-copy-self-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("a.txt");
+        std::fs::write(&path, "contents").unwrap();
+
+        //when
+        let line = format!("copy-file -f {} {}", path.display(), directory.display());
+        let result = dispatcher.eval(&mut shell, &mut console, &line);
+        let contents = std::fs::read_to_string(&path).unwrap();
+        _ = std::fs::remove_dir_all(&directory);
+
+        //then
+        assert!(result.is_err());
+        assert_eq!(contents, "contents");
+    }
+
+    #[test]
+    fn show_kind_of_last_error() {
+        //given
+        let (mut shell, mut console) = headless_shell();
+        let dispatcher = Dispatcher::default();
+        let line = "rush-command-that-does-not-exist";
+        let error = dispatcher.eval(&mut shell, &mut console, line).unwrap_err();
+        shell.set_last_error(line.to_string(), crate::errors::error_kind(&error), error);
+
+        //when
+        dispatcher
+            .eval(&mut shell, &mut console, "show-last-error")
+            .unwrap();
+
+        //then
+        let output = console.output_text();
+        assert!(output
+            .iter()
+            .any(|line| line.starts_with("Kind:") && line.ends_with("UnknownCommand")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_symlinks_as_links_when_copying_recursively() {
+        //given
+        let (mut shell, mut console) = headless_shell();
+        let dispatcher = Dispatcher::default();
+        let root = std::env::temp_dir().join(format!("rush-copy-links-{}", std::process::id()));
+        let source = root.join("source");
+        std::fs::create_dir_all(&source).unwrap();
+        std::os::unix::fs::symlink("..", source.join("parent")).unwrap();
+
+        //when
+        let copy = root.join("copy");
+        let line = format!("copy-file -r {} {}", source.display(), copy.display());
+        let result = dispatcher.eval(&mut shell, &mut console, &line);
+        let target = std::fs::read_link(copy.join("parent"));
+        _ = std::fs::remove_dir_all(&root);
+
+        //then
+        assert!(result.is_ok());
+        assert_eq!(target.unwrap(), std::path::Path::new(".."));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keep_job_when_its_output_cannot_be_moved() {
+        //given
+        let (mut shell, mut console) = headless_shell();
+        let dispatcher = Dispatcher::default();
+        dispatcher
+            .eval(&mut shell, &mut console, "run-in-background sleep 5")
+            .unwrap();
+        let id = shell.jobs().jobs()[0].id;
+
+        //when
+        let line = format!("detach-job {} /nonexistent/rush-job.log", id);
+        let result = dispatcher.eval(&mut shell, &mut console, &line);
+        let remaining = shell.jobs().jobs().len();
+        let log_path = shell.jobs().jobs()[0].log_path.clone();
+        shell.jobs_mut().hangup_all();
+        _ = std::fs::remove_file(log_path);
+
+        //then
+        assert!(result.is_err());
+        assert_eq!(remaining, 1);
+    }
+
+    #[test]
+    fn watch_command_without_parsing_its_arguments_again() {
+        //given
+        let (mut shell, mut console) = headless_shell();
+        let dispatcher = Dispatcher::default();
+        console.headless_backend().unwrap().push_interrupt();
+
+        //when
+        let line = "watch -n 10ms echo 'a&&b' '$HOME' 'it'\\''s \"quoted\"'";
+        let result = dispatcher.eval(&mut shell, &mut console, line);
+
+        //then
+        assert!(result.is_ok());
+        let output = console.output_text();
+        assert_eq!(output.last().unwrap(), "a&&b $HOME it's \"quoted\"");
+    }
+
+    #[test]
+    fn read_line_from_injected_keys() {
+        //given
+        let (mut shell, mut console) = headless_shell();
+        console
+            .headless_backend()
+            .unwrap()
+            .push_text("echo typed\n");
+
+        //when
+        let line = console.read_line(&mut shell).unwrap();
+        let screen = console.headless_backend().unwrap().rows();
+
+        //then
+        assert_eq!(line, "echo typed");
+        assert!(screen.iter().any(|row| row.contains("echo typed")));
+    }
 }
@@ -2,10 +2,39 @@ use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum DispatchError {
-    #[error("Command name could not be found as a builtin or an executable in PATH")]
+    #[error("Command name could not be found as a builtin or an executable in PATH: {0}")]
     UnknownCommand(String),
     #[error("Command does not have the executable permissions set. Current permissions are: {0}")]
     CommandNotExecutable(u32),
     #[error("Failed to read metadata for executable: {0}")]
     FailedToReadExecutableMetadata(String),
+    #[error("Arithmetic expansion is missing its closing '))': {0}")]
+    UnterminatedArithmetic(String),
+    #[error("Commands are nested more than {0} levels deep, so one of them may be running itself")]
+    NestedTooDeeply(usize),
+}
+
+impl DispatchError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::UnknownCommand(..) => "UnknownCommand",
+            Self::CommandNotExecutable(..) => "CommandNotExecutable",
+            Self::FailedToReadExecutableMetadata(..) => "FailedToReadExecutableMetadata",
+            Self::UnterminatedArithmetic(..) => "UnterminatedArithmetic",
+            Self::NestedTooDeeply(..) => "NestedTooDeeply",
+        }
+    }
+}
+
+// Finds the kind of an error for show-last-error, checking the errors that only the dispatcher knows about first
+pub fn error_kind(error: &anyhow::Error) -> &'static str {
+    error
+        .chain()
+        .find_map(|cause| {
+            cause
+                .downcast_ref::<DispatchError>()
+                .map(DispatchError::kind)
+        })
+        .or_else(|| rush_exec::errors::error_kind(error))
+        .unwrap_or("Other")
 }
@@ -1,33 +1,188 @@
-use crate::tokenizer::tokenize;
-use std::collections::VecDeque;
-
-pub fn parse(input: &String) -> Vec<(String, VecDeque<String>)> {
-    let tokens_binding = tokenize(input);
-    let mut tokens = tokens_binding.iter().peekable();
-
-    let mut aggregated_tokens: Vec<VecDeque<String>> = vec![];
-    let mut curr_command: VecDeque<String> = VecDeque::new();
-    let mut commands: Vec<(String, VecDeque<String>)> = Vec::new();
-
-    while let Some(token) = tokens.next() {
-        if token == "&&" {
-            aggregated_tokens.push(curr_command.clone());
-            curr_command.clear()
-        } else {
-            curr_command.push_back(token.clone());
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use anyhow::Result;
+use rush_exec::arithmetic;
+
+use crate::errors::DispatchError;
+use crate::symbols::AND_IF;
+use crate::tokenizer::{tokenize, tokenize_expanding};
+
+// Splits a line into commands at each '&&', expanding the variables in each command
+// * The line is split before variables are expanded, so that a value containing '&&' cannot start another command
+pub fn parse(
+    input: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Vec<(String, VecDeque<String>)> {
+    split_commands(input)
+        .into_iter()
+        .filter_map(|command| {
+            let mut tokens: VecDeque<String> = tokenize_expanding(command, &lookup).into();
+            let name = tokens.pop_front()?;
+            Some((name, tokens))
+        })
+        .collect()
+}
+
+// Splits a line at every '&&' that is not quoted or escaped
+fn split_commands(line: &str) -> Vec<&str> {
+    let mut commands = Vec::new();
+    let mut in_single_quotes = false;
+    let mut in_double_quotes = false;
+    let mut start = 0;
+    let mut characters = line.char_indices();
+
+    while let Some((index, c)) = characters.next() {
+        match c {
+            '\'' if !in_double_quotes => in_single_quotes = !in_single_quotes,
+            '"' if !in_single_quotes => in_double_quotes = !in_double_quotes,
+            '\\' if !in_single_quotes => _ = characters.next(),
+            _ if !in_single_quotes && !in_double_quotes && line[index..].starts_with(AND_IF) => {
+                commands.push(&line[start..index]);
+                characters.next();
+                start = index + AND_IF.len();
+            }
+            _ => (),
         }
+    }
+
+    commands.push(&line[start..]);
+    commands
+}
+
+// Expands the name of a command if it is a user-defined alias, prepending the alias's arguments to the command's own
+// * Expansion is repeated so aliases can refer to other aliases, but each alias is only expanded once to prevent infinite loops
+pub fn expand_aliases(
+    command: (String, VecDeque<String>),
+    aliases: &HashMap<String, String>,
+) -> (String, VecDeque<String>) {
+    let (mut name, mut args) = command;
+    let mut expanded = HashSet::new();
 
-        if tokens.peek().is_none() {
-            aggregated_tokens.push(curr_command.clone());
-            curr_command.clear()
+    while let Some(replacement) = aliases.get(&name) {
+        if !expanded.insert(name.clone()) {
+            break;
         }
+
+        let mut tokens: VecDeque<String> = tokenize(replacement).into();
+        let Some(new_name) = tokens.pop_front() else {
+            break;
+        };
+
+        tokens.extend(args);
+        name = new_name;
+        args = tokens;
     }
 
-    for mut vec_token in aggregated_tokens {
-        commands.push((vec_token.pop_front().unwrap(), vec_token))
+    (name, args)
+}
+
+// Replaces every $NAME and ${NAME} in a text with the value of the variable, or nothing if there is no such variable
+// * This is only used inside arithmetic expressions, since the tokenizer expands the variables in command lines itself
+// * Nothing inside single quotes is expanded, and a backslash before the $ keeps it as it is
+pub fn expand_variables(line: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut expanded = String::with_capacity(line.len());
+    let mut in_single_quotes = false;
+    let mut in_double_quotes = false;
+    let mut rest = line;
+
+    while let Some(c) = rest.chars().next() {
+        match c {
+            '\'' if !in_double_quotes => in_single_quotes = !in_single_quotes,
+            '"' if !in_single_quotes => in_double_quotes = !in_double_quotes,
+            // * The escaped character is copied along with the backslash, so that the tokenizer can handle it as usual
+            '\\' if !in_single_quotes => {
+                let escaped_length = rest[1..].chars().next().map_or(0, char::len_utf8);
+                expanded.push_str(&rest[..1 + escaped_length]);
+                rest = &rest[1 + escaped_length..];
+                continue;
+            }
+            '$' if !in_single_quotes => {
+                if let Some((name, length)) = variable_reference(&rest[1..]) {
+                    expanded.push_str(&lookup(name).unwrap_or_default());
+                    rest = &rest[1 + length..];
+                    continue;
+                }
+            }
+            _ => (),
+        }
+
+        expanded.push(c);
+        rest = &rest[c.len_utf8()..];
     }
 
-    commands
+    expanded
+}
+
+// Gets the name of the variable that a $ refers to, along with the length of the reference after the $
+// * Anything else after a $ (such as the start of an arithmetic expression) is not a reference
+pub(crate) fn variable_reference(text: &str) -> Option<(&str, usize)> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    if let Some(braced) = text.strip_prefix('{') {
+        let length = braced.find('}')?;
+        let name = &braced[..length];
+        return (!name.is_empty() && name.chars().all(is_name_char)).then_some((name, length + 2));
+    }
+
+    if !text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        return None;
+    }
+
+    let length = text.find(|c| !is_name_char(c)).unwrap_or(text.len());
+    Some((&text[..length], length))
+}
+
+// Replaces every $((...)) in a line with the result of the arithmetic expression inside it
+// * This happens before tokenization, so expressions can contain spaces without being quoted
+// * Variables in an expression are expanded here rather than by the tokenizer, so their values are only ever read as numbers
+// * Like other shells, nothing inside single quotes is expanded, but an apostrophe inside double quotes is not a quote
+pub fn expand_arithmetic(line: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String> {
+    let mut expanded = String::with_capacity(line.len());
+    let mut in_single_quotes = false;
+    let mut in_double_quotes = false;
+    let mut rest = line;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '\'' && !in_double_quotes {
+            in_single_quotes = !in_single_quotes;
+        } else if c == '"' && !in_single_quotes {
+            in_double_quotes = !in_double_quotes;
+        } else if c == '\\' && !in_single_quotes {
+            // * The escaped character is copied along with the backslash, so that an escaped quote is not counted
+            let escaped_length = rest[1..].chars().next().map_or(0, char::len_utf8);
+            expanded.push_str(&rest[..1 + escaped_length]);
+            rest = &rest[1 + escaped_length..];
+            continue;
+        } else if !in_single_quotes && rest.starts_with("$((") {
+            let expression_length = find_closing_parentheses(&rest[3..])
+                .ok_or_else(|| DispatchError::UnterminatedArithmetic(rest.to_string()))?;
+            let expression = &rest[3..3 + expression_length];
+
+            let value = arithmetic::evaluate(&expand_variables(expression, &lookup))?;
+            expanded.push_str(&arithmetic::format_number(value));
+            rest = &rest[3 + expression_length + 2..];
+            continue;
+        }
+
+        expanded.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    Ok(expanded)
+}
+
+// Finds the length of an arithmetic expression, up to (but not including) the '))' that closes it
+fn find_closing_parentheses(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (index, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return text[index..].starts_with("))").then_some(index),
+            ')' => depth -= 1,
+            _ => (),
+        }
+    }
+
+    None
 }
 
 #[cfg(test)]
@@ -40,20 +195,20 @@ mod tests {
         let input = String::from("ls -a && ls");
 
         //when
-        let commands = parse(&input);
+        let commands = parse(&input, |_| None);
 
         //then
-        let first_command = &commands.get(0).unwrap().0;
-        let first_command_args = commands.get(0).unwrap().1.get(0).unwrap();
+        let first_command = &commands.first().unwrap().0;
+        let first_command_args = commands.first().unwrap().1.front().unwrap();
 
         let second_command = &commands.get(1).unwrap().0;
-        let second_command_args = commands.get(1).unwrap().1.get(0).is_none();
+        let second_command_args = commands.get(1).unwrap().1.front().is_none();
 
         assert_eq!(first_command, &String::from("ls"));
         assert_eq!(first_command_args, &String::from("-a"));
 
         assert_eq!(second_command, &String::from("ls"));
-        assert_eq!(second_command_args, true);
+        assert!(second_command_args);
     }
 
     #[test]
@@ -62,13 +217,124 @@ mod tests {
         let input = String::from("ls -a");
 
         //when
-        let commands = parse(&input);
+        let commands = parse(&input, |_| None);
 
         //then
-        let command = &commands.get(0).unwrap().0;
-        let args = commands.get(0).unwrap().1.get(0).unwrap();
+        let command = &commands.first().unwrap().0;
+        let args = commands.first().unwrap().1.front().unwrap();
 
         assert_eq!(command, &String::from("ls"));
         assert_eq!(args, &String::from("-a"));
     }
+
+    #[test]
+    fn expand_alias_and_keep_original_arguments() {
+        //given
+        let aliases = HashMap::from([
+            (String::from("ll"), String::from("ls -a")),
+            (String::from("ls"), String::from("list-directory")),
+        ]);
+        let command = parse("ll sample", |_| None).remove(0);
+
+        //when
+        let (name, args) = expand_aliases(command, &aliases);
+
+        //then
+        assert_eq!(name, String::from("list-directory"));
+        assert_eq!(
+            args,
+            VecDeque::from([String::from("-a"), String::from("sample")])
+        );
+    }
+
+    #[test]
+    fn stop_expanding_self_referential_alias() {
+        //given
+        let aliases = HashMap::from([(String::from("ls"), String::from("ls --color"))]);
+        let command = parse("ls", |_| None).remove(0);
+
+        //when
+        let (name, args) = expand_aliases(command, &aliases);
+
+        //then
+        assert_eq!(name, String::from("ls"));
+        assert_eq!(args, VecDeque::from([String::from("--color")]));
+    }
+
+    #[test]
+    fn expand_variables_outside_of_single_quotes() {
+        //given
+        let input = "echo $GREETING, \"${NAME}!\" '$NAME' \\$NAME $MISSING $((1 + 1))";
+        let lookup = |name: &str| match name {
+            "GREETING" => Some(String::from("hello")),
+            "NAME" => Some(String::from("world")),
+            _ => None,
+        };
+
+        //when
+        let expanded = expand_variables(input, lookup);
+
+        //then
+        assert_eq!(
+            expanded,
+            "echo hello, \"world!\" '$NAME' \\$NAME  $((1 + 1))"
+        );
+    }
+
+    #[test]
+    fn keep_variable_values_as_plain_arguments() {
+        //given
+        let input = "echo $X \"$Y\" '$Y' && echo done";
+        let lookup = |name: &str| match name {
+            "X" => Some(String::from("a && echo INJECTED $((6*7))")),
+            "Y" => Some(String::from("two  words")),
+            _ => None,
+        };
+
+        //when
+        let commands = parse(input, lookup);
+
+        //then
+        assert_eq!(commands.len(), 2);
+        assert_eq!(
+            commands[0].1,
+            VecDeque::from(
+                [
+                    "a",
+                    "&&",
+                    "echo",
+                    "INJECTED",
+                    "$((6*7))",
+                    "two  words",
+                    "$Y"
+                ]
+                .map(String::from)
+            )
+        );
+        assert_eq!(commands[1].0, "echo");
+    }
+
+    #[test]
+    fn expand_arithmetic_outside_of_single_quotes() {
+        //given
+        let input = "echo $((2^10 / (3 + 1))) '$((1 + 1))' \"it's $((1 + 1))\"";
+
+        //when
+        let expanded = expand_arithmetic(input, |_| None).unwrap();
+
+        //then
+        assert_eq!(expanded, "echo 256 '$((1 + 1))' \"it's 2\"");
+    }
+
+    #[test]
+    fn fail_to_expand_unterminated_arithmetic() {
+        //given
+        let input = "echo $((1 + 2)";
+
+        //when
+        let expanded = expand_arithmetic(input, |_| None);
+
+        //then
+        assert!(expanded.is_err());
+    }
 }
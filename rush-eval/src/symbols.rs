@@ -23,6 +23,7 @@ pub const CLOBBER: &str = ">|";
 
 pub struct Symbols<'a> {
     pub operators: Vec<&'a str>,
+    #[allow(dead_code)]
     pub separators: Vec<char>,
 }
 
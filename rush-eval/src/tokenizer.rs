@@ -1,9 +1,24 @@
+use crate::parser::variable_reference;
 use crate::symbols::{
     Symbols, AMP, BACKSLASH, DOLLAR, DOUBLE_QUOTE, GREAT, LESS, PIPE, SEMI, SINGLE_QUOTE,
     WHITESPACE,
 };
 
+// Looks up the value of a variable by name
+pub type VariableLookup<'a> = &'a dyn Fn(&str) -> Option<String>;
+
 pub fn tokenize(input: &str) -> Vec<String> {
+    tokenize_with(input, None)
+}
+
+// Splits a line into tokens, replacing every $NAME and ${NAME} outside of single quotes with the value of the variable
+// * Values are never parsed themselves, so quotes, operators, and '$((' inside them are kept as plain text
+// * A value outside of double quotes is split into several arguments at whitespace, like in POSIX shells
+pub fn tokenize_expanding(input: &str, lookup: VariableLookup) -> Vec<String> {
+    tokenize_with(input, Some(lookup))
+}
+
+fn tokenize_with(input: &str, lookup: Option<VariableLookup>) -> Vec<String> {
     let symbols = Symbols::new();
 
     let mut curr_token = String::new();
@@ -48,7 +63,9 @@ pub fn tokenize(input: &str) -> Vec<String> {
                         }
                     }
                     PIPE | LESS | GREAT => {
-                        if in_single_quotes || in_double_quotes {
+                        // * A '-' followed by '>' is the arrow used by symlink, not a redirection
+                        if in_single_quotes || in_double_quotes || (v == GREAT && curr_token == "-")
+                        {
                             curr_token.push(v);
                             continue;
                         }
@@ -90,7 +107,12 @@ pub fn tokenize(input: &str) -> Vec<String> {
                         }
                     }
                     BACKSLASH => {
+                        // * Outside of quotes, a backslash keeps the next character as it is, e.g. '\$HOME' or '\ '
                         if !in_single_quotes && !in_double_quotes {
+                            if let Some(escaped) = characters.next() {
+                                curr_token.push(escaped);
+                            }
+
                             continue;
                         }
 
@@ -120,6 +142,38 @@ pub fn tokenize(input: &str) -> Vec<String> {
                             }
                         }
                     }
+                    DOLLAR if !in_single_quotes && lookup.is_some() => {
+                        let rest: String = characters.clone().collect();
+                        let Some((name, length)) = variable_reference(&rest) else {
+                            curr_token.push(v);
+                            continue;
+                        };
+
+                        // The lookup was checked by the match guard
+                        let value = lookup.unwrap()(name).unwrap_or_default();
+                        // * The reference is made of ASCII characters, so its length in bytes is also its length in characters
+                        characters.nth(length - 1);
+                        if in_double_quotes {
+                            curr_token.push_str(&value);
+                            continue;
+                        }
+
+                        if value.starts_with(char::is_whitespace) {
+                            delimit_token(&mut tokens, &mut curr_token);
+                        }
+
+                        let mut words = value.split_whitespace().peekable();
+                        while let Some(word) = words.next() {
+                            curr_token.push_str(word);
+                            if words.peek().is_some() {
+                                delimit_token(&mut tokens, &mut curr_token);
+                            }
+                        }
+
+                        if value.ends_with(char::is_whitespace) {
+                            delimit_token(&mut tokens, &mut curr_token);
+                        }
+                    }
                     _ => curr_token.push(v),
                 }
             }
@@ -172,6 +226,25 @@ mod tests {
         assert_eq!(tokens, expected);
     }
 
+    #[test]
+    fn keep_arrow_as_one_token() {
+        //given
+        let input = String::from("symlink link -> target");
+
+        //when
+        let tokens = tokenize(&input);
+
+        //then
+        let expected = vec![
+            String::from("symlink"),
+            String::from("link"),
+            String::from("->"),
+            String::from("target"),
+        ];
+
+        assert_eq!(tokens, expected);
+    }
+
     #[test]
     fn return_correct_token_with_and_operator() {
         //given
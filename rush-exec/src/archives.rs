@@ -0,0 +1,409 @@
+use std::cell::Cell;
+use std::io::{self, Read, Seek, Write};
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+
+use anyhow::Result;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use rush_state::console::Console;
+
+use crate::builtin_arguments::ArchiveFormat;
+use crate::errors::{BuiltinError, FilesystemError};
+use crate::file_operations::progress_line;
+
+// Represents a file or directory that is being added to an archive
+struct ArchiveEntry {
+    // Where the entry is on the filesystem
+    path: PathBuf,
+    // The path that the entry is stored under inside the archive
+    name: PathBuf,
+    is_directory: bool,
+}
+
+// Wraps a reader, keeping a running count of the bytes read through it
+// * The count is shared so that it can still be checked after the reader has been handed off to a decoder
+struct CountingReader<R: Read> {
+    inner: R,
+    count: Rc<Cell<u64>>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let count = self.inner.read(buffer)?;
+        self.count.set(self.count.get() + count as u64);
+        Ok(count)
+    }
+}
+
+// Guesses the format of an archive from its file name
+pub fn detect_format(archive: &Path) -> Option<ArchiveFormat> {
+    let name = archive.file_name()?.to_str()?.to_lowercase();
+    if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(ArchiveFormat::TarGz)
+    } else if name.ends_with(".tar") {
+        Some(ArchiveFormat::Tar)
+    } else if name.ends_with(".zip") {
+        Some(ArchiveFormat::Zip)
+    } else {
+        None
+    }
+}
+
+// Creates an archive containing the given files and directories, along with everything inside the directories
+// * Each path is stored under its own name, so archiving 'src/foo' produces entries starting with 'foo/'
+pub fn create_archive(
+    console: &mut Console,
+    archive: &Path,
+    paths: &[PathBuf],
+    format: ArchiveFormat,
+) -> Result<()> {
+    let entries = collect_entries(paths)?;
+    let failed_to_create = || FilesystemError::FailedToCreateArchive(archive.to_path_buf());
+    let file = fs_err::File::create(archive).map_err(|_| failed_to_create())?;
+
+    let result = match format {
+        ArchiveFormat::Tar => write_tar(console, archive, file, &entries).map(|_| ()),
+        ArchiveFormat::TarGz => write_tar(
+            console,
+            archive,
+            GzEncoder::new(file, Compression::default()),
+            &entries,
+        )
+        .and_then(|encoder| Ok(encoder.finish().map(|_| ())?)),
+        ArchiveFormat::Zip => write_zip(console, archive, file, &entries),
+    };
+
+    console.overwrite_line("");
+    // A partially written archive is useless, so it is removed if anything goes wrong (including cancellation)
+    if let Err(e) = result {
+        _ = fs_err::remove_file(archive);
+        return match e.downcast_ref::<BuiltinError>() {
+            Some(BuiltinError::Interrupted) => Err(e),
+            _ => Err(failed_to_create().into()),
+        };
+    }
+
+    Ok(())
+}
+
+// Extracts every entry of an archive into the destination directory, creating it if needed
+// * Entries that would end up outside of the destination (e.g. '../file') are skipped
+pub fn extract_archive(
+    console: &mut Console,
+    archive: &Path,
+    destination: &Path,
+    format: ArchiveFormat,
+    force: bool,
+) -> Result<()> {
+    let failed_to_extract = || FilesystemError::FailedToExtractArchive(archive.to_path_buf());
+    let file = fs_err::File::open(archive)
+        .map_err(|_| FilesystemError::PathNotFound(archive.to_path_buf()))?;
+    fs_err::create_dir_all(destination)
+        .map_err(|_| FilesystemError::FailedToCreateDirectory(destination.to_path_buf()))?;
+
+    let result = match format {
+        ArchiveFormat::Tar => read_tar(console, archive, file, destination, false, force),
+        ArchiveFormat::TarGz => read_tar(console, archive, file, destination, true, force),
+        ArchiveFormat::Zip => read_zip(console, archive, file, destination, force),
+    };
+
+    console.overwrite_line("");
+    result.map_err(|e| match e.downcast_ref::<BuiltinError>() {
+        Some(BuiltinError::Interrupted) => e,
+        _ => failed_to_extract().into(),
+    })
+}
+
+// Finds every file and directory that should be added to an archive, in the order they are added
+// * Symbolic links are not followed, so that links to directories cannot cause an endless loop
+fn collect_entries(paths: &[PathBuf]) -> Result<Vec<ArchiveEntry>> {
+    let mut entries = Vec::new();
+    for path in paths {
+        let canonical_path =
+            fs_err::canonicalize(path).map_err(|_| FilesystemError::PathNotFound(path.clone()))?;
+        // * The root directory has no name of its own, so its contents are stored at the top level instead
+        let name = canonical_path
+            .file_name()
+            .map(PathBuf::from)
+            .unwrap_or_default();
+
+        let mut pending = vec![(path.clone(), name)];
+        while let Some((path, name)) = pending.pop() {
+            let metadata = fs_err::symlink_metadata(&path)
+                .map_err(|_| FilesystemError::PathNotFound(path.clone()))?;
+
+            if metadata.is_dir() {
+                let mut children: Vec<PathBuf> = fs_err::read_dir(&path)
+                    .map_err(|_| FilesystemError::FailedToReadDirectory(path.clone()))?
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .collect();
+                children.sort();
+
+                // Push in reverse so that entries are added in alphabetical order
+                for child in children.into_iter().rev() {
+                    let child_name = name.join(child.file_name().unwrap_or_default());
+                    pending.push((child, child_name));
+                }
+            }
+
+            if !name.as_os_str().is_empty() {
+                entries.push(ArchiveEntry {
+                    path,
+                    name,
+                    is_directory: metadata.is_dir(),
+                });
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+// Writes the entries to a tar archive, returning the writer so that any compression can be finished
+fn write_tar<W: Write>(
+    console: &mut Console,
+    archive: &Path,
+    writer: W,
+    entries: &[ArchiveEntry],
+) -> Result<W> {
+    let mut builder = tar::Builder::new(writer);
+    builder.follow_symlinks(false);
+
+    let mut last_percent = None;
+    for (index, entry) in entries.iter().enumerate() {
+        match entry.is_directory {
+            true => builder.append_dir(&entry.name, &entry.path)?,
+            false => builder.append_path_with_name(&entry.path, &entry.name)?,
+        }
+
+        show_progress(
+            console,
+            archive,
+            index + 1,
+            entries.len(),
+            &mut last_percent,
+        )?;
+    }
+
+    Ok(builder.into_inner()?)
+}
+
+// Writes the entries to a zip archive, compressing each file individually
+// * Zip archives have no way to store symbolic links portably, so linked files are stored as copies
+fn write_zip<W: Write + Seek>(
+    console: &mut Console,
+    archive: &Path,
+    writer: W,
+    entries: &[ArchiveEntry],
+) -> Result<()> {
+    let mut zip = ZipWriter::new(writer);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut last_percent = None;
+    for (index, entry) in entries.iter().enumerate() {
+        let name = zip_entry_name(&entry.name);
+        if entry.is_directory {
+            zip.add_directory(name, options)?;
+        } else {
+            zip.start_file(name, file_options(options, &entry.path))?;
+            io::copy(&mut fs_err::File::open(&entry.path)?, &mut zip)?;
+        }
+
+        show_progress(
+            console,
+            archive,
+            index + 1,
+            entries.len(),
+            &mut last_percent,
+        )?;
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+// Extracts a tar archive, showing progress based on how much of the archive file has been read
+fn read_tar(
+    console: &mut Console,
+    archive: &Path,
+    file: fs_err::File,
+    destination: &Path,
+    compressed: bool,
+    force: bool,
+) -> Result<()> {
+    let size = file.metadata()?.len();
+    let count = Rc::new(Cell::new(0));
+    let reader = CountingReader {
+        inner: file,
+        count: count.clone(),
+    };
+
+    let reader: Box<dyn Read> = match compressed {
+        true => Box::new(GzDecoder::new(reader)),
+        false => Box::new(reader),
+    };
+
+    let mut tar = tar::Archive::new(reader);
+    let mut last_percent = None;
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let target = destination.join(entry.path()?);
+        if target.is_file() && !confirm_overwrite(console, &target, force)? {
+            continue;
+        }
+
+        entry.unpack_in(destination)?;
+        show_progress(
+            console,
+            archive,
+            count.get() as usize,
+            size as usize,
+            &mut last_percent,
+        )?;
+    }
+
+    Ok(())
+}
+
+// Extracts a zip archive, showing progress based on how many of its entries have been extracted
+fn read_zip(
+    console: &mut Console,
+    archive: &Path,
+    file: fs_err::File,
+    destination: &Path,
+    force: bool,
+) -> Result<()> {
+    let mut zip = ZipArchive::new(file)?;
+    let mut last_percent = None;
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index)?;
+        // * The enclosed name is None for entries that would escape the destination directory
+        let Some(name) = entry.enclosed_name().map(Path::to_path_buf) else {
+            continue;
+        };
+
+        let target = destination.join(name);
+        if entry.is_dir() {
+            fs_err::create_dir_all(&target)?;
+        } else if !target.is_file() || confirm_overwrite(console, &target, force)? {
+            if let Some(parent) = target.parent() {
+                fs_err::create_dir_all(parent)?;
+            }
+
+            io::copy(&mut entry, &mut fs_err::File::create(&target)?)?;
+            #[cfg(unix)]
+            if let Some(mode) = entry.unix_mode() {
+                use std::os::unix::fs::PermissionsExt;
+                fs_err::set_permissions(&target, std::fs::Permissions::from_mode(mode))?;
+            }
+        }
+
+        drop(entry);
+        show_progress(console, archive, index + 1, zip.len(), &mut last_percent)?;
+    }
+
+    Ok(())
+}
+
+// Carries the permissions of a file over to its zip entry, so that executables stay executable when extracted
+#[cfg(unix)]
+fn file_options(options: FileOptions, path: &Path) -> FileOptions {
+    use std::os::unix::fs::PermissionsExt;
+    match fs_err::metadata(path) {
+        Ok(metadata) => options.unix_permissions(metadata.permissions().mode()),
+        Err(_) => options,
+    }
+}
+
+#[cfg(not(unix))]
+fn file_options(options: FileOptions, _path: &Path) -> FileOptions {
+    options
+}
+
+// Asks before an extracted file replaces an existing one, unless forced
+// * The progress indicator is cleared first so that the question is not appended to it
+fn confirm_overwrite(console: &mut Console, target: &Path, force: bool) -> Result<bool> {
+    if force {
+        return Ok(true);
+    }
+
+    console.overwrite_line("");
+    console.confirm(&format!("Overwrite '{}'?", target.display()))
+}
+
+// Updates the progress indicator for an archive, and checks whether the user wants to cancel
+// * The indicator is only redrawn when the percentage actually changes
+fn show_progress(
+    console: &mut Console,
+    archive: &Path,
+    done: usize,
+    total: usize,
+    last_percent: &mut Option<usize>,
+) -> Result<()> {
+    if console.poll_interrupt(Duration::ZERO)? {
+        return Err(BuiltinError::Interrupted.into());
+    }
+
+    let percent = (done * 100).checked_div(total).unwrap_or(100);
+    if *last_percent != Some(percent) {
+        *last_percent = Some(percent);
+        console.overwrite_line(&progress_line(
+            &archive.display().to_string(),
+            percent as u64,
+        ));
+    }
+
+    Ok(())
+}
+
+// Converts a path into the form used for zip entry names, which always use forward slashes
+fn zip_entry_name(name: &Path) -> String {
+    name.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skip_zip_entries_that_escape_the_destination() {
+        //given
+        let directory = std::env::temp_dir().join(format!("rush-zip-slip-{}", std::process::id()));
+        let destination = directory.join("extracted");
+        fs_err::create_dir_all(&destination).unwrap();
+        let archive = directory.join("archive.zip");
+
+        let mut zip = ZipWriter::new(fs_err::File::create(&archive).unwrap());
+        for name in ["../escaped.txt", "/absolute.txt", "inside.txt"] {
+            zip.start_file(name, FileOptions::default()).unwrap();
+            zip.write_all(b"contents").unwrap();
+        }
+        zip.finish().unwrap();
+
+        let mut console = Console::headless(80, 24).unwrap();
+
+        //when
+        let file = fs_err::File::open(&archive).unwrap();
+        let result = read_zip(&mut console, &archive, file, &destination, false);
+        let escaped = directory.join("escaped.txt").exists();
+        let inside = fs_err::read_to_string(destination.join("inside.txt")).ok();
+        _ = fs_err::remove_dir_all(&directory);
+
+        //then
+        assert!(result.is_ok());
+        assert!(!escaped);
+        assert_eq!(inside.as_deref(), Some("contents"));
+    }
+}
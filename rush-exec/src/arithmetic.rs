@@ -0,0 +1,288 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use anyhow::Result;
+
+use crate::errors::ArithmeticError;
+
+// Evaluates an arithmetic expression such as '2^10 / 3.5' or 'sqrt(2) * pi'
+// Supports +, -, *, /, % and ^ (right-associative power) with the usual precedence, parentheses,
+// the constants 'pi' and 'e', and a handful of math functions
+// * This is shared by the calculate builtin and $((...)) expansion, so that both behave identically
+pub fn evaluate(expression: &str) -> Result<f64> {
+    let mut parser = ExpressionParser {
+        characters: expression.chars().peekable(),
+    };
+
+    let value = parser.parse_expression()?;
+    parser.skip_whitespace();
+    match parser.characters.next() {
+        Some(c) => Err(ArithmeticError::UnexpectedCharacter(c).into()),
+        None => Ok(value),
+    }
+}
+
+// Formats a result so that whole numbers are shown without a decimal point
+pub fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{}", value)
+    }
+}
+
+// A recursive descent parser that evaluates the expression as it is parsed
+struct ExpressionParser<'a> {
+    characters: Peekable<Chars<'a>>,
+}
+
+impl ExpressionParser<'_> {
+    // expression = term (('+' | '-') term)*
+    fn parse_expression(&mut self) -> Result<f64> {
+        let mut value = self.parse_term()?;
+
+        while let Some(operator) = self.next_operator(&['+', '-']) {
+            let rhs = self.parse_term()?;
+            match operator {
+                '+' => value += rhs,
+                _ => value -= rhs,
+            }
+        }
+
+        Ok(value)
+    }
+
+    // term = power (('*' | '/' | '%') power)*
+    fn parse_term(&mut self) -> Result<f64> {
+        let mut value = self.parse_power()?;
+
+        while let Some(operator) = self.next_operator(&['*', '/', '%']) {
+            let rhs = self.parse_power()?;
+            if operator != '*' && rhs == 0.0 {
+                return Err(ArithmeticError::DivisionByZero.into());
+            }
+
+            match operator {
+                '*' => value *= rhs,
+                '/' => value /= rhs,
+                _ => value %= rhs,
+            }
+        }
+
+        Ok(value)
+    }
+
+    // power = unary ('^' power)?
+    fn parse_power(&mut self) -> Result<f64> {
+        let base = self.parse_unary()?;
+
+        if self.next_operator(&['^']).is_some() {
+            let exponent = self.parse_power()?;
+            Ok(base.powf(exponent))
+        } else {
+            Ok(base)
+        }
+    }
+
+    // unary = ('-' | '+') unary | primary
+    fn parse_unary(&mut self) -> Result<f64> {
+        match self.next_operator(&['-', '+']) {
+            Some('-') => Ok(-self.parse_unary()?),
+            Some(_) => self.parse_unary(),
+            None => self.parse_primary(),
+        }
+    }
+
+    // primary = number | '(' expression ')' | constant | function '(' arguments ')'
+    fn parse_primary(&mut self) -> Result<f64> {
+        self.skip_whitespace();
+
+        match self.characters.peek().copied() {
+            Some('(') => {
+                self.characters.next();
+                let value = self.parse_expression()?;
+                self.expect(')')?;
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => self.parse_number(),
+            Some(c) if c.is_ascii_alphabetic() => self.parse_identifier(),
+            Some(c) => Err(ArithmeticError::UnexpectedCharacter(c).into()),
+            None => Err(ArithmeticError::UnexpectedEnd.into()),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<f64> {
+        let mut number = String::new();
+        while let Some(&c) = self.characters.peek() {
+            if !c.is_ascii_digit() && c != '.' {
+                break;
+            }
+
+            number.push(c);
+            self.characters.next();
+        }
+
+        number
+            .parse::<f64>()
+            .map_err(|_| ArithmeticError::InvalidNumber(number).into())
+    }
+
+    fn parse_identifier(&mut self) -> Result<f64> {
+        let mut name = String::new();
+        while let Some(&c) = self.characters.peek() {
+            if !c.is_ascii_alphanumeric() {
+                break;
+            }
+
+            name.push(c);
+            self.characters.next();
+        }
+
+        match name.as_str() {
+            "pi" => return Ok(std::f64::consts::PI),
+            "e" => return Ok(std::f64::consts::E),
+            _ => (),
+        }
+
+        self.expect('(')?;
+        let mut arguments = vec![self.parse_expression()?];
+        while self.next_operator(&[',']).is_some() {
+            arguments.push(self.parse_expression()?);
+        }
+        self.expect(')')?;
+
+        call_function(&name, &arguments)
+    }
+
+    // Consumes the next non-whitespace character if it is one of the given operators
+    fn next_operator(&mut self, operators: &[char]) -> Option<char> {
+        self.skip_whitespace();
+        let c = *self.characters.peek()?;
+        if operators.contains(&c) {
+            self.characters.next();
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    // Consumes the next non-whitespace character, failing if it is not the expected one
+    fn expect(&mut self, expected: char) -> Result<()> {
+        self.skip_whitespace();
+        match self.characters.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(ArithmeticError::UnexpectedCharacter(c).into()),
+            None => Err(ArithmeticError::UnexpectedEnd.into()),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.characters.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+}
+
+// Calls one of the supported math functions
+fn call_function(name: &str, arguments: &[f64]) -> Result<f64> {
+    let expected = match name {
+        "min" | "max" | "pow" => 2,
+        "sqrt" | "abs" | "sin" | "cos" | "tan" | "ln" | "log" | "exp" | "floor" | "ceil"
+        | "round" => 1,
+        _ => return Err(ArithmeticError::UnknownFunction(name.to_string()).into()),
+    };
+
+    if arguments.len() != expected {
+        return Err(ArithmeticError::WrongArgumentCount(name.to_string(), expected).into());
+    }
+
+    let x = arguments[0];
+    Ok(match name {
+        "min" => x.min(arguments[1]),
+        "max" => x.max(arguments[1]),
+        "pow" => x.powf(arguments[1]),
+        "sqrt" => x.sqrt(),
+        "abs" => x.abs(),
+        "sin" => x.sin(),
+        "cos" => x.cos(),
+        "tan" => x.tan(),
+        "ln" => x.ln(),
+        "log" => x.log10(),
+        "exp" => x.exp(),
+        "floor" => x.floor(),
+        "ceil" => x.ceil(),
+        _ => x.round(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_of(expression: &str) -> ArithmeticError {
+        evaluate(expression)
+            .unwrap_err()
+            .downcast::<ArithmeticError>()
+            .unwrap()
+    }
+
+    #[test]
+    fn multiply_before_adding() {
+        //given
+        let expression = "2 + 3 * 4 - 10 / 5";
+
+        //when
+        let value = evaluate(expression).unwrap();
+
+        //then
+        assert_eq!(value, 12.0);
+    }
+
+    #[test]
+    fn raise_powers_from_right_to_left() {
+        //given
+        let expression = "2 ^ 3 ^ 2";
+
+        //when
+        let value = evaluate(expression).unwrap();
+
+        //then
+        assert_eq!(value, 512.0);
+    }
+
+    #[test]
+    fn negate_operands_with_unary_minus() {
+        //given
+        let expression = "-3 * -(2 + 1)";
+
+        //when
+        let value = evaluate(expression).unwrap();
+
+        //then
+        assert_eq!(value, 9.0);
+    }
+
+    #[test]
+    fn refuse_to_divide_by_zero() {
+        //given
+        let expressions = ["1 / 0", "5 % (2 - 2)"];
+
+        //when
+        let errors = expressions.map(error_of);
+
+        //then
+        for error in errors {
+            assert!(matches!(error, ArithmeticError::DivisionByZero));
+        }
+    }
+
+    #[test]
+    fn refuse_to_call_unknown_function() {
+        //given
+        let expression = "frobnicate(2)";
+
+        //when
+        let error = error_of(expression);
+
+        //then
+        assert!(matches!(error, ArithmeticError::UnknownFunction(name) if name == "frobnicate"));
+    }
+}
@@ -1,9 +1,432 @@
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser, Debug)]
 #[command(no_binary_name = true)]
 pub struct ListDirectoryArguments {
     #[clap(short, long, default_value_t = false)]
     pub all: bool,
+    // Show the permissions, owner, size, and modification time of each entry
+    #[clap(short, long, default_value_t = false)]
+    pub long: bool,
+    // Show an icon before each entry (requires a Nerd Font)
+    #[clap(long, default_value_t = false)]
+    pub icons: bool,
+    // The order to list entries in
+    #[clap(short, long, value_enum, default_value_t = SortOrder::Name)]
+    pub sort: SortOrder,
+    // List entries in the opposite order
+    #[clap(short, long, default_value_t = false)]
+    pub reverse: bool,
+    // List directories before any other entries
+    #[clap(short, long, default_value_t = false)]
+    pub group_directories_first: bool,
+    // Recursively list subdirectories as a tree
+    #[clap(short, long, default_value_t = false, conflicts_with = "long")]
+    pub tree: bool,
+    // How many directories deep to list in tree mode
+    #[clap(short, long, requires = "tree")]
+    pub depth: Option<usize>,
     pub path: Option<String>,
 }
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortOrder {
+    Name,
+    // * Largest entries are listed first
+    Size,
+    // * Most recently modified entries are listed first
+    #[value(alias = "mtime")]
+    Time,
+    #[value(alias = "ext")]
+    Extension,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct EchoArguments {
+    // Do not print a trailing newline
+    #[clap(short = 'n', default_value_t = false)]
+    pub no_newline: bool,
+    // Interpret backslash escape sequences
+    #[clap(short = 'e', default_value_t = false)]
+    pub escapes: bool,
+    #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
+    pub text: Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct HistoryArguments {
+    #[command(subcommand)]
+    pub action: Option<HistoryAction>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HistoryAction {
+    // List every command in the history along with its index
+    List,
+    // List the commands in the history which contain a substring or match a regex
+    Search {
+        pattern: String,
+        #[clap(short, long, default_value_t = false)]
+        regex: bool,
+    },
+    // Run the command at the given index in the history
+    Run {
+        index: usize,
+    },
+    // Remove every command from the history
+    Clear,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct EditPathArguments {
+    #[command(subcommand)]
+    pub action: Option<EditPathAction>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum EditPathAction {
+    // List every directory in the PATH along with its index
+    List,
+    // Add a directory to the end of the PATH, where it is searched last
+    Append {
+        path: String,
+    },
+    // Add a directory to the start of the PATH, where it is searched first
+    Prepend {
+        path: String,
+    },
+    // Add a directory at the given index in the PATH
+    Insert {
+        index: usize,
+        path: String,
+    },
+    // Remove a directory from the PATH by its index or its path
+    Remove {
+        target: String,
+    },
+    // Remove every directory that appears earlier in the PATH
+    #[clap(alias = "dedup")]
+    Deduplicate,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct ClearHistoryArguments {
+    // Only remove this many of the most recent commands
+    #[clap(short, long)]
+    pub last: Option<usize>,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct CopyFileArguments {
+    pub source: String,
+    pub destination: String,
+    // Copy directories along with their contents
+    #[clap(short, long, default_value_t = false)]
+    pub recursive: bool,
+    // Overwrite existing files without asking
+    #[clap(short, long, default_value_t = false)]
+    pub force: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct MoveFileArguments {
+    pub source: String,
+    pub destination: String,
+    // Overwrite existing files without asking
+    #[clap(short, long, default_value_t = false)]
+    pub force: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct DeleteFileArguments {
+    pub path: String,
+    // Delete directories along with their contents
+    #[clap(short, long, default_value_t = false)]
+    pub recursive: bool,
+    // Delete without asking for confirmation
+    #[clap(short, long, default_value_t = false)]
+    pub force: bool,
+    // Delete permanently, even if use-trash is enabled
+    #[clap(short, long, default_value_t = false)]
+    pub permanent: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct TrashArguments {
+    // The files to move to the trash, or the trashed items to restore with --restore
+    #[clap(required_unless_present = "list")]
+    pub paths: Vec<String>,
+    // Show everything in the trash, along with when it was deleted
+    #[clap(short, long, default_value_t = false, conflicts_with_all = ["paths", "restore"])]
+    pub list: bool,
+    // Move the most recently trashed item with each name or original path back to where it came from
+    #[clap(short, long, default_value_t = false)]
+    pub restore: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct ReadFileArguments {
+    pub path: String,
+    // Show the line number before each line
+    #[clap(short = 'n', long, default_value_t = false)]
+    pub line_numbers: bool,
+    // Only read the given range of bytes, in the form 'START-END' where either end can be omitted
+    // * The end of the range is exclusive
+    #[clap(short, long)]
+    pub bytes: Option<String>,
+    // Print the whole file at once instead of pausing after each page
+    #[clap(short = 'P', long, default_value_t = false)]
+    pub no_pager: bool,
+    // Show the file without syntax highlighting
+    #[clap(long, default_value_t = false)]
+    pub plain: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct HeadArguments {
+    pub path: String,
+    // The number of lines to print from the start of the file
+    #[clap(short = 'n', long, default_value_t = 10)]
+    pub lines: usize,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct TailArguments {
+    pub path: String,
+    // The number of lines to print from the end of the file
+    #[clap(short = 'n', long, default_value_t = 10)]
+    pub lines: usize,
+    // Keep printing lines as they are appended to the file, until interrupted with Ctrl+C
+    #[clap(short, long, default_value_t = false)]
+    pub follow: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct FindFileArguments {
+    // A file name pattern, where '*' matches any characters and '?' matches a single character
+    pub pattern: String,
+    // The directory to search in (defaults to the working directory)
+    pub directory: Option<String>,
+    // Only show entries of this type
+    #[clap(short = 't', long = "type", value_enum)]
+    pub entry_type: Option<EntryType>,
+    // How many directories deep to search
+    #[clap(short = 'd', long)]
+    pub max_depth: Option<usize>,
+    // Match names regardless of case
+    #[clap(short, long, default_value_t = false)]
+    pub ignore_case: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryType {
+    #[value(alias = "f")]
+    File,
+    #[value(alias = "d")]
+    Directory,
+    #[value(alias = "l")]
+    Symlink,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct SearchTextArguments {
+    // The regular expression to search for
+    pub pattern: String,
+    // The files (or directories, with --recursive) to search in
+    #[clap(required = true)]
+    pub paths: Vec<String>,
+    // Search every file in the given directories and their subdirectories
+    #[clap(short, long, default_value_t = false)]
+    pub recursive: bool,
+    // Match regardless of case
+    #[clap(short, long, default_value_t = false)]
+    pub ignore_case: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct ChangePermissionsArguments {
+    // The new mode, in octal (e.g. 755) or symbolic (e.g. u+x,go-w) notation
+    pub mode: String,
+    #[clap(required = true)]
+    pub paths: Vec<String>,
+    // Change the permissions of directories and all of their contents
+    #[clap(short, long, default_value_t = false)]
+    pub recursive: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct EnvironmentVariableArguments {
+    // The variable to show, or every variable if omitted
+    pub name: Option<String>,
+    // Print 'KEY=value' lines that can be sourced by other shells
+    #[clap(short, long, default_value_t = false)]
+    pub export_format: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct FileInfoArguments {
+    #[clap(required = true)]
+    pub paths: Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct ChecksumArguments {
+    #[clap(required = true)]
+    pub paths: Vec<String>,
+    // The hash function used to compute the checksums
+    #[clap(short, long, value_enum, default_value_t = ChecksumAlgorithm::Sha256)]
+    pub algorithm: ChecksumAlgorithm,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    #[value(alias = "sha-256")]
+    Sha256,
+    #[value(alias = "md-5")]
+    Md5,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct ArchiveArguments {
+    // The archive to create
+    pub archive: String,
+    // The files and directories to put in the archive
+    #[clap(required = true)]
+    pub paths: Vec<String>,
+    // The kind of archive to create, if it cannot be guessed from the archive name
+    #[clap(short = 'F', long, value_enum)]
+    pub format: Option<ArchiveFormat>,
+    // Overwrite an existing archive without asking
+    #[clap(short, long, default_value_t = false)]
+    pub force: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct ExtractArguments {
+    pub archive: String,
+    // The directory to extract into, which is created if needed (defaults to the working directory)
+    pub destination: Option<String>,
+    // The kind of archive being extracted, if it cannot be guessed from the archive name
+    #[clap(short = 'F', long, value_enum)]
+    pub format: Option<ArchiveFormat>,
+    // Overwrite existing files without asking
+    #[clap(short, long, default_value_t = false)]
+    pub force: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct DownloadArguments {
+    pub url: String,
+    // Where to save the file, or the directory to save it in (defaults to the last part of the URL)
+    pub destination: Option<String>,
+    // Overwrite an existing file without asking
+    #[clap(short, long, default_value_t = false)]
+    pub force: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Tar,
+    #[value(name = "tar.gz", alias = "tgz")]
+    TarGz,
+    Zip,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct SymlinkArguments {
+    // Either '<link> <target>', '<link> -> <target>', or the links to read with --read
+    #[clap(required = true, allow_hyphen_values = true)]
+    pub paths: Vec<String>,
+    // Print the target of each link instead of creating one
+    #[clap(short, long, default_value_t = false)]
+    pub read: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct DeleteDirectoryArguments {
+    #[clap(required = true)]
+    pub paths: Vec<String>,
+    // Delete directories along with their contents, rather than only empty directories
+    #[clap(short, long, default_value_t = false)]
+    pub recursive: bool,
+    // Delete without asking for confirmation
+    #[clap(short, long, default_value_t = false)]
+    pub force: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct MakeDirectoryArguments {
+    #[clap(required = true)]
+    pub paths: Vec<String>,
+    // Create any missing parent directories, and ignore directories that already exist
+    #[clap(short, long, default_value_t = false)]
+    pub parents: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct MakeFileArguments {
+    #[clap(required = true)]
+    pub paths: Vec<String>,
+    // Create any missing parent directories
+    #[clap(short, long, default_value_t = false)]
+    pub parents: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct ReadInputArguments {
+    // The variable to store the input in
+    pub variable: String,
+    // The text to show before the input
+    #[clap(short, long, default_value = "")]
+    pub prompt: String,
+    // Do not show the input as it is typed, e.g. for passwords
+    #[clap(short = 's', long, default_value_t = false)]
+    pub hidden: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct WatchArguments {
+    // How long to wait between runs, such as '2', '500ms', or '1m'
+    #[clap(short = 'n', long, default_value = "2")]
+    pub interval: String,
+    // The command to run, along with its arguments
+    #[clap(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+    pub command: Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+pub struct DateTimeArguments {
+    // A strftime-style format string, e.g. '%Y-%m-%d %H:%M:%S'
+    #[clap(default_value = "%a %b %e %H:%M:%S %Z %Y")]
+    pub format: String,
+    // Show the time in UTC instead of the local time zone
+    #[clap(short, long, default_value_t = false)]
+    pub utc: bool,
+}
@@ -10,22 +10,47 @@ An 'External' will only have access to its arguments and environment variables,
 
 use clap::Parser;
 use fs_err::{self};
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::cmp::Reverse;
+use std::collections::{HashSet, VecDeque};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path as StdPath, PathBuf};
+use std::time::Duration;
 
 use anyhow::Result;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Local, Utc};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Span, Spans};
+use regex::{Regex, RegexBuilder};
 
-use crate::builtin_arguments::ListDirectoryArguments;
-use rush_state::console::Console;
+use crate::archives;
+use crate::arithmetic;
+use crate::builtin_arguments::{
+    ArchiveArguments, ArchiveFormat, ChangePermissionsArguments, ChecksumArguments,
+    ClearHistoryArguments, CopyFileArguments, DateTimeArguments, DeleteDirectoryArguments,
+    DeleteFileArguments, DownloadArguments, EchoArguments, EditPathAction, EditPathArguments,
+    EnvironmentVariableArguments, ExtractArguments, FileInfoArguments, FindFileArguments,
+    HeadArguments, HistoryAction, HistoryArguments, ListDirectoryArguments, MakeDirectoryArguments,
+    MakeFileArguments, MoveFileArguments, ReadFileArguments, ReadInputArguments,
+    SearchTextArguments, SortOrder, SymlinkArguments, TailArguments, TrashArguments,
+    WatchArguments,
+};
+use rush_state::config::{setting_keys, SettingSource};
+use rush_state::console::{format_columns, ColumnAlignment, Console, PagerAction};
+use rush_state::keymap::{ConsoleAction, KeyBinding, COMMAND_PREFIX};
 use rush_state::path::Path;
 use rush_state::shell::Shell;
-use rush_state::showln;
+use rush_state::theme::Theme;
+use rush_state::{show, showln};
 
-use crate::commands::{Executable, Runnable};
-use crate::errors::BuiltinError;
+use crate::commands::{spawn_detached, spawn_job, Evaluator, Executable, Runnable};
+use crate::downloads;
 use crate::errors::BuiltinError::{
     FailedReadingDir, FailedReadingFileName, FailedReadingFileType, FailedReadingPath,
 };
+use crate::errors::{BuiltinError, FilesystemError};
+use crate::file_operations::{self, CopyOptions, FindOptions};
+use crate::highlighting::Highlighter;
 
 pub fn test(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
     check_args(&args, 0, "test", console)?;
@@ -33,8 +58,30 @@ pub fn test(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Resul
     Ok(())
 }
 
-pub fn exit(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+pub fn echo(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<EchoArguments>(&args, console)?;
+    let mut text = arguments.text.join(" ");
+    let mut newline = !arguments.no_newline;
+
+    if arguments.escapes {
+        let (interpreted, stopped) = interpret_escapes(&text);
+        text = interpreted;
+        // * The \c escape suppresses all further output, including the trailing newline
+        newline &= !stopped;
+    }
+
+    match newline {
+        true => showln!(console, "{}", text),
+        false => show!(console, "{}", text),
+    }
+
+    Ok(())
+}
+
+pub fn exit(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
     check_args(&args, 0, "exit", console)?;
+    // Background jobs are hung up on exit, unless they have been detached from the shell
+    shell.jobs_mut().hangup_all();
     console.exit(0);
     Ok(())
 }
@@ -58,66 +105,251 @@ pub fn change_directory(shell: &mut Shell, console: &mut Console, args: Vec<&str
 }
 
 pub fn list_directory(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
-    let arguments = ListDirectoryArguments::parse_from(&args);
-    let show_hidden = arguments.all;
-    let path_to_read = match arguments.path {
+    let arguments = parse_arguments::<ListDirectoryArguments>(&args, console)?;
+    let path_to_read = match &arguments.path {
         Some(path) => PathBuf::from(path),
         None => shell.env().CWD().path().to_path_buf(),
     };
 
-    let read_dir_result = match fs_err::read_dir(&path_to_read) {
+    let entries = read_directory_entries(&path_to_read, &arguments)?;
+    let theme = console.theme().clone();
+
+    if arguments.tree {
+        let root = Spans::from(Span::styled(
+            path_to_read.display().to_string(),
+            Style::default()
+                .fg(theme.directory)
+                .add_modifier(Modifier::BOLD),
+        ));
+
+        let mut lines = vec![root];
+        tree_lines(&entries, &arguments, &theme, "", 1, &mut lines);
+        console.println_styled_lines(lines);
+        return Ok(());
+    }
+
+    let names = entries
+        .iter()
+        .map(|entry| styled_entry_name(&entry.name, &entry.path, &theme, arguments.icons));
+
+    if arguments.long {
+        let rows: Vec<Vec<String>> = entries
+            .iter()
+            .map(|entry| long_listing_columns(&entry.path))
+            .collect();
+        let alignments = [
+            ColumnAlignment::Left,
+            ColumnAlignment::Left,
+            ColumnAlignment::Right,
+        ];
+
+        let lines = format_columns(&rows, &alignments)
+            .into_iter()
+            .zip(names)
+            .map(|(columns, mut name)| {
+                name.0.insert(0, Span::raw(columns + " "));
+                name
+            });
+        console.println_styled_lines(lines.collect::<Vec<_>>());
+    } else {
+        console.println_styled_lines(names.collect::<Vec<_>>());
+    }
+
+    Ok(())
+}
+
+// Represents a single entry shown by 'list-directory'
+struct DirectoryEntry {
+    // The name shown for the entry, which has a trailing slash if it is a directory
+    name: String,
+    path: PathBuf,
+    is_directory: bool,
+    size: u64,
+    modified: Option<std::time::SystemTime>,
+}
+
+// Reads the entries of a directory, filtered and sorted according to the 'list-directory' arguments
+fn read_directory_entries(
+    path_to_read: &std::path::Path,
+    arguments: &ListDirectoryArguments,
+) -> Result<Vec<DirectoryEntry>> {
+    let read_dir_result = match fs_err::read_dir(path_to_read) {
         Ok(v) => v,
-        Err(_) => return Err(FailedReadingPath(path_to_read).into()),
+        Err(_) => return Err(FailedReadingPath(path_to_read.to_path_buf()).into()),
     };
 
-    let mut directories = Vec::new();
-    let mut files = Vec::new();
+    let mut entries = Vec::new();
 
     for dir_entry in read_dir_result {
         let fs_object = match dir_entry {
             Ok(v) => v,
-            Err(_) => return Err(FailedReadingDir(path_to_read).into()),
+            Err(_) => return Err(FailedReadingDir(path_to_read.to_path_buf()).into()),
         };
 
         let fs_object_name = match fs_object.file_name().to_str() {
             Some(v) => String::from(v),
-            None => return Err(FailedReadingFileName(path_to_read).into()),
+            None => return Err(FailedReadingFileName(path_to_read.to_path_buf()).into()),
         };
 
         let fs_object_type = match fs_object.file_type() {
             Ok(v) => v,
-            Err(_) => return Err(FailedReadingFileType(path_to_read).into()),
+            Err(_) => return Err(FailedReadingFileType(path_to_read.to_path_buf()).into()),
         };
 
-        if fs_object_name.starts_with('.') && !show_hidden {
+        if fs_object_name.starts_with('.') && !arguments.all {
             continue;
         }
 
-        if fs_object_type.is_dir() {
-            directories.push(format!("{}/", fs_object_name));
-        } else {
-            files.push(fs_object_name);
+        let metadata = fs_object.metadata().ok();
+        let is_directory = fs_object_type.is_dir();
+        entries.push(DirectoryEntry {
+            name: match is_directory {
+                true => format!("{}/", fs_object_name),
+                false => fs_object_name,
+            },
+            path: fs_object.path(),
+            is_directory,
+            size: metadata.as_ref().map_or(0, |metadata| metadata.len()),
+            modified: metadata.and_then(|metadata| metadata.modified().ok()),
+        });
+    }
+
+    sort_directory_entries(&mut entries, arguments);
+    Ok(entries)
+}
+
+fn sort_directory_entries(entries: &mut [DirectoryEntry], arguments: &ListDirectoryArguments) {
+    let extension = |entry: &DirectoryEntry| {
+        entry
+            .path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+    };
+
+    // * Entries are always sorted by name first, so that ties are broken alphabetically
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    match arguments.sort {
+        SortOrder::Name => (),
+        SortOrder::Size => entries.sort_by_key(|entry| Reverse(entry.size)),
+        SortOrder::Time => entries.sort_by_key(|entry| Reverse(entry.modified)),
+        SortOrder::Extension => entries.sort_by_key(|entry| extension(entry)),
+    }
+
+    if arguments.reverse {
+        entries.reverse();
+    }
+
+    if arguments.group_directories_first {
+        entries.sort_by_key(|entry| !entry.is_directory);
+    }
+}
+
+// Builds the lines of a 'list-directory --tree' listing, recursing into subdirectories
+// * Unreadable subdirectories are still listed, but their contents are skipped
+fn tree_lines(
+    entries: &[DirectoryEntry],
+    arguments: &ListDirectoryArguments,
+    theme: &Theme,
+    prefix: &str,
+    depth: usize,
+    lines: &mut Vec<Spans<'static>>,
+) {
+    for (index, entry) in entries.iter().enumerate() {
+        let is_last = index == entries.len() - 1;
+        let (branch, indent) = match is_last {
+            true => ("└── ", "    "),
+            false => ("├── ", "│   "),
         };
+
+        let mut line = styled_entry_name(&entry.name, &entry.path, theme, arguments.icons);
+        line.0.insert(0, Span::raw(format!("{}{}", prefix, branch)));
+        lines.push(line);
+
+        // Symbolic links to directories are not followed, to avoid infinite loops
+        let is_symlink = entry.path.is_symlink();
+        let within_depth = arguments.depth.is_none_or(|max_depth| depth < max_depth);
+        if entry.is_directory && !is_symlink && within_depth {
+            if let Ok(children) = read_directory_entries(&entry.path, arguments) {
+                let prefix = format!("{}{}", prefix, indent);
+                tree_lines(&children, arguments, theme, &prefix, depth + 1, lines);
+            }
+        }
     }
+}
 
-    directories.sort();
-    files.sort();
+// Gets the columns shown before each entry's name by 'list-directory --long'
+// * Symbolic links are described by their own metadata, rather than the metadata of their target
+fn long_listing_columns(path: &std::path::Path) -> Vec<String> {
+    match fs_err::symlink_metadata(path) {
+        Ok(metadata) => {
+            let modified = metadata
+                .modified()
+                .map(file_operations::format_time)
+                .unwrap_or_else(|_| String::from("-"));
 
-    for directory in directories {
-        showln!(console, "{}", &directory);
+            vec![
+                file_operations::permissions_string(&metadata),
+                file_operations::owner_name(&metadata),
+                metadata.len().to_string(),
+                modified,
+            ]
+        }
+        Err(_) => vec![String::from("?"); 4],
     }
+}
 
-    for file in files {
-        showln!(console, "{}", &file);
+// Colors a directory entry's name by its type, optionally with an icon in front of it
+fn styled_entry_name(
+    name: &str,
+    path: &std::path::Path,
+    theme: &Theme,
+    show_icon: bool,
+) -> Spans<'static> {
+    let metadata = fs_err::symlink_metadata(path).ok();
+    let (color, icon) = match metadata {
+        Some(metadata) if metadata.is_symlink() => (Some(theme.symlink), '\u{f481}'),
+        Some(metadata) if metadata.is_dir() => (Some(theme.directory), '\u{f115}'),
+        Some(metadata) if file_operations::is_executable(path, &metadata) => {
+            (Some(theme.executable), '\u{f489}')
+        }
+        _ => (None, file_icon(path)),
+    };
+
+    let style = match color {
+        Some(color) => Style::default().fg(color).add_modifier(Modifier::BOLD),
+        None => Style::default(),
+    };
+
+    let mut spans = Vec::new();
+    if show_icon {
+        spans.push(Span::styled(format!("{} ", icon), style));
     }
 
-    Ok(())
+    spans.push(Span::styled(name.to_string(), style));
+    Spans::from(spans)
+}
+
+// Gets a Nerd Font icon for a regular file based on its extension
+fn file_icon(path: &std::path::Path) -> char {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("rs") => '\u{e7a8}',
+        Some("py") => '\u{e606}',
+        Some("js" | "ts") => '\u{e74e}',
+        Some("md") => '\u{f48a}',
+        Some("toml" | "json" | "yaml" | "yml" | "rush") => '\u{e615}',
+        Some("sh" | "bash" | "zsh") => '\u{f489}',
+        Some("png" | "jpg" | "jpeg" | "gif" | "svg") => '\u{f1c5}',
+        Some("zip" | "tar" | "gz" | "xz" | "7z") => '\u{f410}',
+        Some("lock") => '\u{f023}',
+        _ => '\u{f15b}',
+    }
 }
 
 // TODO: Find a better name for this
 pub fn go_back(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
     check_args(&args, 0, "go-back", console)?;
-    shell.env_mut().go_back().map_err(|_| {
+    let history_limit = shell.config().history_limit;
+    shell.env_mut().go_back(history_limit).map_err(|_| {
         showln!(console, "Previous directory does not exist or is invalid");
         BuiltinError::FailedToRun.into()
     })
@@ -125,181 +357,2006 @@ pub fn go_back(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Res
 
 pub fn go_forward(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
     check_args(&args, 0, "go-forward", console)?;
-    shell.env_mut().go_forward().map_err(|_| {
+    let history_limit = shell.config().history_limit;
+    shell.env_mut().go_forward(history_limit).map_err(|_| {
         showln!(console, "Next directory does not exist or is invalid");
         BuiltinError::FailedToRun.into()
     })
 }
 
+// Lists the back/forward directory history with indices, or jumps straight to the directory at the given index
+// * Earlier directories have negative indices and later ones have positive indices, with the current working directory at 0
+pub fn directory_history(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let index = match args.as_slice() {
+        [] => None,
+        [index] => match index.parse::<isize>() {
+            Ok(index) => Some(index),
+            Err(_) => {
+                showln!(console, "Invalid directory history index: '{}'", index);
+                return Err(BuiltinError::InvalidArgument(index.to_string()).into());
+            }
+        },
+        _ => {
+            showln!(console, "Usage: directory-history [index]");
+            return Err(BuiltinError::InvalidArgumentCount(args.len()).into());
+        }
+    };
+
+    let Some(index) = index else {
+        show_directory_history(shell, console);
+        return Ok(());
+    };
+
+    let history_limit = shell.config().history_limit;
+    shell
+        .env_mut()
+        .move_in_history(index, history_limit)
+        .map_err(|_| {
+            showln!(
+                console,
+                "No directory at index {} in the directory history",
+                args[0]
+            );
+            BuiltinError::FailedToRun.into()
+        })
+}
+
+// Prints the back/forward directory history from oldest to newest, with the index of each directory
+fn show_directory_history(shell: &Shell, console: &mut Console) {
+    let home = shell.env().HOME();
+    let truncation_factor = shell.config().truncation_factor;
+    let backward = shell.env().backward_directories();
+    let directories = backward
+        .iter()
+        .chain(std::iter::once(shell.env().CWD()))
+        .chain(shell.env().forward_directories());
+
+    let lines: Vec<String> = directories
+        .enumerate()
+        .map(|(position, directory)| {
+            let index = match position as isize - backward.len() as isize {
+                0 => String::from("0"),
+                index => format!("{:+}", index),
+            };
+            format!(
+                "{:>3}  {}",
+                index,
+                directory.collapse(home, truncation_factor)
+            )
+        })
+        .collect();
+
+    console.println_lines(lines);
+}
+
+pub fn push_directory(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    if args.len() > 1 {
+        showln!(console, "Usage: push-directory [path | +index]");
+        return Err(BuiltinError::InvalidArgumentCount(args.len()).into());
+    }
+
+    let history_limit = shell.config().history_limit;
+    let result = match args.first() {
+        // * '+N' rotates the stack instead of pushing, like in other shells
+        Some(arg) if arg.starts_with('+') => match arg[1..].parse::<usize>() {
+            Ok(index) => shell.env_mut().rotate_directories(index, history_limit),
+            Err(_) => {
+                showln!(console, "Invalid directory stack index: '{}'", arg);
+                return Err(BuiltinError::InvalidArgument(arg.to_string()).into());
+            }
+        },
+        directory => shell
+            .env_mut()
+            .push_directory(directory.copied(), history_limit),
+    };
+
+    result.map_err(|e| {
+        showln!(console, "Failed to push directory: {}", e);
+        BuiltinError::FailedToRun
+    })?;
+
+    show_directory_stack(shell, console);
+    Ok(())
+}
+
+pub fn pop_directory(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    check_args(&args, 0, "pop-directory", console)?;
+    let history_limit = shell.config().history_limit;
+    shell.env_mut().pop_directory(history_limit).map_err(|e| {
+        showln!(console, "Failed to pop directory: {}", e);
+        BuiltinError::FailedToRun
+    })?;
+
+    show_directory_stack(shell, console);
+    Ok(())
+}
+
+pub fn directory_stack(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    check_args(&args, 0, "directory-stack", console)?;
+    show_directory_stack(shell, console);
+    Ok(())
+}
+
+// Prints the directory stack with its indices, starting with the current working directory at index 0
+fn show_directory_stack(shell: &Shell, console: &mut Console) {
+    let home = shell.env().HOME();
+    let truncation_factor = shell.config().truncation_factor;
+    let lines: Vec<String> = std::iter::once(shell.env().CWD())
+        .chain(shell.env().directory_stack())
+        .enumerate()
+        .map(|(index, directory)| {
+            format!(
+                "{:>3}  {}",
+                index,
+                directory.collapse(home, truncation_factor)
+            )
+        })
+        .collect();
+
+    console.println_lines(lines);
+}
+
 pub fn clear_terminal(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
     check_args(&args, 0, "clear-terminal", console)?;
     console.clear_output()
 }
 
-// TODO: Add prompt to confirm file overwrite
+// Creates any files that do not exist yet, and updates the modification time of those that do
 pub fn make_file(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
-    if args.len() == 1 {
-        fs_err::File::create(args[0]).map_err(|_| {
-            showln!(console, "Failed to create file: '{}'", args[0]);
-            BuiltinError::FailedToRun
+    let arguments = parse_arguments::<MakeFileArguments>(&args, console)?;
+
+    for path in &arguments.paths {
+        file_operations::touch_file(&PathBuf::from(path), arguments.parents).map_err(|e| {
+            showln!(console, "Failed to create file: {}", e);
+            e
         })?;
-        Ok(())
-    } else {
-        showln!(console, "Usage: make-file <path>");
-        Err(BuiltinError::InvalidArgumentCount(args.len()).into())
     }
+
+    Ok(())
 }
 
-pub fn make_directory(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
-    if args.len() == 1 {
-        fs_err::create_dir(args[0]).map_err(|_| {
-            showln!(console, "Failed to create directory: '{}'", args[0]);
-            BuiltinError::FailedToRun
+pub fn make_directory(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<MakeDirectoryArguments>(&args, console)?;
+
+    for path in &arguments.paths {
+        let path = Path::normalize(path, shell.env().CWD().path(), shell.env().HOME())
+            .inspect_err(|_| {
+                showln!(console, "Failed to resolve path: '{}'", path);
+            })?;
+
+        let created = file_operations::create_directory(&path, arguments.parents).map_err(|e| {
+            showln!(console, "{}", e);
+            e
         })?;
-        Ok(())
-    } else {
-        showln!(console, "Usage: make-directory <path>");
-        Err(BuiltinError::InvalidArgumentCount(args.len()).into())
+
+        for directory in created {
+            showln!(console, "Created directory: '{}'", directory.display());
+        }
     }
+
+    Ok(())
 }
 
-pub fn delete_file(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
-    if args.len() == 1 {
-        fs_err::remove_file(args[0]).map_err(|_| {
-            showln!(console, "Failed to delete file: '{}'", args[0]);
-            BuiltinError::FailedToRun
-        })?;
-        Ok(())
-    } else {
-        showln!(console, "Usage: delete-file <path>");
-        Err(BuiltinError::InvalidArgumentCount(args.len()).into())
+pub fn delete_directory(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<DeleteDirectoryArguments>(&args, console)?;
+
+    for path in &arguments.paths {
+        let path = PathBuf::from(path);
+        file_operations::delete_directory(console, &path, arguments.recursive, arguments.force)
+            .inspect_err(|e| showln!(console, "Failed to delete directory: {}", e))?;
     }
+
+    Ok(())
 }
 
-pub fn read_file(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
-    check_args(&args, 1, "read-file <path>", console)?;
-    let file_name = args[0].to_string();
-    let file = fs_err::File::open(&file_name).map_err(|_| {
-        showln!(console, "Failed to open file: '{}'", file_name);
-        BuiltinError::FailedToRun
-    })?;
+pub fn symlink(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<SymlinkArguments>(&args, console)?;
+
+    if arguments.read {
+        for link in &arguments.paths {
+            let target = file_operations::read_symlink(&PathBuf::from(link))
+                .inspect_err(|e| showln!(console, "Failed to read link: {}", e))?;
+            showln!(console, "{} -> {}", link, target.display());
+        }
 
-    let reader = BufReader::new(file);
-    for line in reader.lines() {
-        let line = line.expect("Failed to read line");
-        showln!(console, "{}", &line);
+        return Ok(());
     }
 
-    Ok(())
+    let (link, target) = match arguments.paths.as_slice() {
+        [link, target] => (link, target),
+        [link, arrow, target] if arrow == "->" => (link, target),
+        _ => {
+            showln!(
+                console,
+                "Usage: symlink <link> [->] <target>, or symlink --read <link>..."
+            );
+            return Err(BuiltinError::InvalidArgumentCount(args.len()).into());
+        }
+    };
+
+    file_operations::create_symlink(&PathBuf::from(link), &PathBuf::from(target))
+        .inspect_err(|e| showln!(console, "{}", e))
 }
 
-pub fn run_executable(shell: &mut Shell, console: &mut Console, mut args: Vec<&str>) -> Result<()> {
-    let executable_name = args[0].to_string();
-    let executable_path = Path::from_str(&executable_name, shell.env().HOME()).map_err(|_| {
-        showln!(
-            console,
-            "Failed to resolve executable path: '{}'",
-            executable_name
-        );
-        BuiltinError::FailedToRun
-    })?;
+// * The output matches sha256sum and md5sum, so it can be checked with those tools later
+pub fn checksum(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<ChecksumArguments>(&args, console)?;
 
-    // * Executable name is removed before running the executable because the std::process::Command
-    // * process builder automatically adds the executable name as the first argument
-    args.remove(0);
-    Executable::new(executable_path).run(shell, console, args)
+    for path in arguments.paths.iter() {
+        let checksum =
+            file_operations::checksum_file(console, &PathBuf::from(path), arguments.algorithm)
+                .inspect_err(|e| showln!(console, "{}", e))?;
+        showln!(console, "{}  {}", checksum, path);
+    }
+
+    Ok(())
 }
 
-pub fn configure(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
-    check_args(&args, 2, "configure <key> <value>", console)?;
-    let key = args[0];
-    let value = args[1];
-
-    match key {
-        "truncation" => {
-            if value == "false" {
-                shell.config_mut().truncation_factor = None;
-                return Ok(());
-            }
+pub fn file_info(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<FileInfoArguments>(&args, console)?;
 
-            shell.config_mut().truncation_factor = Some(value.parse::<usize>().map_err(|_| {
-                showln!(console, "Invalid truncation length: '{}'", value);
-                BuiltinError::InvalidValue(value.to_string())
-            })?)
+    for (index, path) in arguments.paths.iter().enumerate() {
+        let path = PathBuf::from(path);
+        // * Symbolic links are described by their own metadata, with their target shown separately
+        let metadata = fs_err::symlink_metadata(&path).map_err(|_| {
+            showln!(console, "Path does not exist: '{}'", path.display());
+            BuiltinError::FailedReadingPath(path.clone())
+        })?;
+
+        if index > 0 {
+            showln!(console);
         }
-        "history-limit" => {
-            if value == "false" {
-                shell.config_mut().history_limit = None;
-                return Ok(());
-            }
 
-            shell.config_mut().history_limit = Some(value.parse::<usize>().map_err(|_| {
-                showln!(console, "Invalid history limit: '{}'", value);
-                BuiltinError::InvalidValue(value.to_string())
-            })?)
+        let time = |time: std::io::Result<std::time::SystemTime>| {
+            time.map(file_operations::format_time)
+                .unwrap_or_else(|_| String::from("-"))
+        };
+
+        let size = match metadata.len() {
+            bytes if bytes < 1024 => format!("{} bytes", bytes),
+            bytes => format!("{} ({} bytes)", file_operations::format_size(bytes), bytes),
+        };
+
+        let mut rows = vec![
+            vec![String::from("Path:"), path.display().to_string()],
+            vec![
+                String::from("Type:"),
+                file_operations::file_type_name(&metadata).to_string(),
+            ],
+            vec![String::from("Size:"), size],
+            vec![
+                String::from("Permissions:"),
+                file_operations::permissions_string(&metadata),
+            ],
+            vec![
+                String::from("Owner:"),
+                file_operations::owner_name(&metadata),
+            ],
+            vec![String::from("Modified:"), time(metadata.modified())],
+            vec![String::from("Accessed:"), time(metadata.accessed())],
+            vec![String::from("Created:"), time(metadata.created())],
+        ];
+
+        if metadata.is_symlink() {
+            let target = file_operations::read_symlink(&path)
+                .map(|target| target.display().to_string())
+                .unwrap_or_else(|_| String::from("?"));
+            rows.push(vec![String::from("Target:"), target]);
         }
-        "show-errors" => {
-            shell.config_mut().show_errors = value.parse::<bool>().map_err(|_| {
-                showln!(console, "Invalid value for show-errors: '{}'", value);
-                BuiltinError::InvalidValue(value.to_string())
-            })?
+
+        console.println_columns(&rows, &[]);
+    }
+
+    Ok(())
+}
+
+pub fn delete_file(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<DeleteFileArguments>(&args, console)?;
+    let path = PathBuf::from(&arguments.path);
+
+    if shell.config().use_trash && !arguments.permanent {
+        return file_operations::move_to_trash(&path, arguments.recursive)
+            .inspect_err(|e| showln!(console, "Failed to delete: {}", e));
+    }
+
+    file_operations::delete_path(console, &path, arguments.recursive, arguments.force).map_err(
+        |e| {
+            showln!(console, "Failed to delete: {}", e);
+            e
+        },
+    )
+}
+
+pub fn trash(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<TrashArguments>(&args, console)?;
+
+    if arguments.list {
+        let items = file_operations::list_trash().inspect_err(|e| showln!(console, "{}", e))?;
+        if items.is_empty() {
+            showln!(console, "The trash is empty");
         }
-        _ => {
-            showln!(console, "Invalid configuration key: '{}'", key);
-            return Err(BuiltinError::InvalidArgument(key.to_string()).into());
+
+        let rows: Vec<Vec<String>> = items
+            .into_iter()
+            .map(|(path, time)| {
+                vec![
+                    file_operations::format_time(time),
+                    path.display().to_string(),
+                ]
+            })
+            .collect();
+        console.println_columns(&rows, &[]);
+        return Ok(());
+    }
+
+    for path in &arguments.paths {
+        if arguments.restore {
+            // * Items can be restored by name alone, but the full path is needed to tell apart items with the same name
+            let original_path =
+                Path::normalize(path, shell.env().CWD().path(), shell.env().HOME())?;
+            let restored_path = file_operations::restore_from_trash(path, &original_path)
+                .inspect_err(|e| showln!(console, "Failed to restore: {}", e))?;
+            showln!(console, "Restored '{}'", restored_path.display());
+        } else {
+            // * The trash is always used here, so directories are trashed without needing any extra flags
+            file_operations::move_to_trash(&PathBuf::from(path), true)
+                .inspect_err(|e| showln!(console, "Failed to delete: {}", e))?;
         }
     }
 
     Ok(())
 }
 
-pub fn environment_variable(
-    shell: &mut Shell,
-    console: &mut Console,
-    args: Vec<&str>,
-) -> Result<()> {
-    check_args(&args, 1, "environment-variable <var>", console)?;
-    match args[0].to_uppercase().as_str() {
-        "PATH" => {
-            for (i, path) in shell.env().PATH().iter().enumerate() {
-                showln!(console, "[{i}]: {path}");
-            }
-        }
-        "USER" => showln!(console, "{}", shell.env().USER()),
-        "HOME" => showln!(console, "{}", shell.env().HOME().display()),
-        "CWD" | "WORKING-DIRECTORY" => showln!(console, "{}", shell.env().CWD()),
-        _ => {
-            showln!(console, "Invalid environment variable: '{}'", args[0]);
-            return Err(BuiltinError::InvalidArgument(args[0].to_string()).into());
+pub fn copy_file(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<CopyFileArguments>(&args, console)?;
+    let source = PathBuf::from(&arguments.source);
+    let mut destination = PathBuf::from(&arguments.destination);
+
+    // If the destination is an existing directory, copy the source into it
+    if destination.is_dir() {
+        if let Some(name) = source.file_name() {
+            destination.push(name);
         }
     }
 
+    let options = CopyOptions {
+        recursive: arguments.recursive,
+        force: arguments.force,
+    };
+
+    file_operations::copy_path(console, &source, &destination, options).map_err(|e| {
+        showln!(console, "Failed to copy: {}", e);
+        e
+    })
+}
+
+pub fn archive(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<ArchiveArguments>(&args, console)?;
+    let archive = PathBuf::from(&arguments.archive);
+    let format = archive_format(&archive, arguments.format, console)?;
+
+    if archive.exists()
+        && !arguments.force
+        && !console.confirm(&format!("Overwrite '{}'?", archive.display()))?
+    {
+        return Ok(());
+    }
+
+    let paths: Vec<PathBuf> = arguments.paths.iter().map(PathBuf::from).collect();
+    archives::create_archive(console, &archive, &paths, format)
+        .inspect_err(|e| showln!(console, "Failed to create archive: {}", e))
+}
+
+pub fn extract(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<ExtractArguments>(&args, console)?;
+    let archive = PathBuf::from(&arguments.archive);
+    let format = archive_format(&archive, arguments.format, console)?;
+    let destination = PathBuf::from(arguments.destination.as_deref().unwrap_or("."));
+
+    archives::extract_archive(console, &archive, &destination, format, arguments.force)
+        .inspect_err(|e| showln!(console, "Failed to extract archive: {}", e))
+}
+
+pub fn download(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<DownloadArguments>(&args, console)?;
+    let file_name = downloads::file_name_from_url(&arguments.url);
+    let mut destination = PathBuf::from(arguments.destination.as_deref().unwrap_or(&file_name));
+
+    // If the destination is an existing directory, download into it
+    if destination.is_dir() {
+        destination.push(file_name);
+    }
+
+    if destination.exists()
+        && !arguments.force
+        && !console.confirm(&format!("Overwrite '{}'?", destination.display()))?
+    {
+        return Ok(());
+    }
+
+    let size = downloads::download_file(console, &arguments.url, &destination)
+        .inspect_err(|e| showln!(console, "{}", e))?;
+    showln!(
+        console,
+        "Saved '{}' ({})",
+        destination.display(),
+        file_operations::format_size(size)
+    );
+
     Ok(())
 }
 
-pub fn edit_path(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
-    check_args(&args, 2, "edit-path <append | prepend> <path>", console)?;
-    let action = args[0];
-    let path = Path::from_str(args[1], shell.env().HOME()).map_err(|_| {
-        showln!(console, "Invalid directory: '{}'", args[1]);
-        BuiltinError::FailedToRun
+// Gets the format given on the command line, or guesses it from the archive name if none was given
+fn archive_format(
+    archive: &StdPath,
+    format: Option<ArchiveFormat>,
+    console: &mut Console,
+) -> Result<ArchiveFormat> {
+    format
+        .or_else(|| archives::detect_format(archive))
+        .ok_or_else(|| {
+            showln!(
+                console,
+                "Unknown archive format for '{}', use --format to specify one",
+                archive.display()
+            );
+            FilesystemError::UnknownArchiveFormat(archive.to_path_buf()).into()
+        })
+}
+
+pub fn move_file(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<MoveFileArguments>(&args, console)?;
+    let source = PathBuf::from(&arguments.source);
+    let mut destination = PathBuf::from(&arguments.destination);
+
+    // If the destination is an existing directory, move the source into it
+    if destination.is_dir() {
+        if let Some(name) = source.file_name() {
+            destination.push(name);
+        }
+    }
+
+    file_operations::move_path(console, &source, &destination, arguments.force).map_err(|e| {
+        showln!(console, "Failed to move: {}", e);
+        e
+    })
+}
+
+pub fn find_file(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<FindFileArguments>(&args, console)?;
+    let root = PathBuf::from(arguments.directory.as_deref().unwrap_or("."));
+
+    let options = FindOptions::new(
+        &arguments.pattern,
+        arguments.ignore_case,
+        arguments.entry_type,
+        arguments.max_depth,
+    )?;
+
+    file_operations::find_paths(console, &root, &options).map_err(|e| {
+        showln!(console, "Failed to search: {}", e);
+        e
     })?;
 
-    match action {
-        "append" => shell.env_mut().PATH_mut().push_front(path),
-        "prepend" => shell.env_mut().PATH_mut().push_back(path),
-        _ => {
-            showln!(console, "Invalid action: '{}'", action);
-            return Err(BuiltinError::InvalidArgument(args[0].to_string()).into());
+    Ok(())
+}
+
+pub fn search_text(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<SearchTextArguments>(&args, console)?;
+    let pattern = RegexBuilder::new(&arguments.pattern)
+        .case_insensitive(arguments.ignore_case)
+        .build()
+        .map_err(|e| {
+            showln!(console, "Invalid regex: {}", e);
+            BuiltinError::InvalidArgument(arguments.pattern.clone())
+        })?;
+
+    let mut files = Vec::new();
+    for path in &arguments.paths {
+        files.extend(
+            file_operations::collect_files(&PathBuf::from(path), arguments.recursive).map_err(
+                |e| {
+                    showln!(console, "Failed to search: {}", e);
+                    e
+                },
+            )?,
+        );
+    }
+
+    // File names are only worth showing if there is more than one file the match could have come from
+    let show_file_names = files.len() > 1 || arguments.recursive;
+    let mut match_count = 0;
+
+    for file in files {
+        // * Files that are not valid UTF-8 (such as binaries) are skipped
+        let Ok(contents) = fs_err::read_to_string(&file) else {
+            continue;
+        };
+
+        let file_name = file.display().to_string();
+        let mut lines = Vec::new();
+        for (index, line) in contents.lines().enumerate() {
+            if let Some(spans) = highlight_matches(&pattern, line) {
+                let mut prefix = Vec::new();
+                if show_file_names {
+                    prefix.push(Span::styled(
+                        format!("{}:", file_name),
+                        Style::default().fg(Color::Magenta),
+                    ));
+                }
+
+                prefix.push(Span::styled(
+                    format!("{}:", index + 1),
+                    Style::default().fg(Color::Green),
+                ));
+                prefix.extend(spans);
+                lines.push(Spans::from(prefix));
+            }
+        }
+
+        match_count += lines.len();
+        console.println_styled_lines(lines);
+
+        if console.poll_interrupt(Duration::ZERO)? {
+            break;
         }
     }
 
+    // * Like grep, finding nothing counts as a failure so that it can be detected by the user
+    if match_count == 0 {
+        return Err(BuiltinError::FailedToRun.into());
+    }
+
     Ok(())
 }
 
+// Splits a line into Spans with every match of the pattern highlighted, or returns None if there are no matches
+fn highlight_matches(pattern: &Regex, line: &str) -> Option<Vec<Span<'static>>> {
+    let highlight = Style::default()
+        .fg(Color::LightRed)
+        .add_modifier(Modifier::BOLD);
+
+    if !pattern.is_match(line) {
+        return None;
+    }
+
+    let mut spans = Vec::new();
+    let mut last_end = 0;
+    // * Empty matches (e.g. from '^' or 'a*') still count as matches, but there is nothing to highlight
+    for found in pattern.find_iter(line).filter(|found| !found.is_empty()) {
+        spans.push(Span::raw(line[last_end..found.start()].to_string()));
+        spans.push(Span::styled(found.as_str().to_string(), highlight));
+        last_end = found.end();
+    }
+
+    spans.push(Span::raw(line[last_end..].to_string()));
+    Some(spans)
+}
+
+pub fn change_permissions(
+    _shell: &mut Shell,
+    console: &mut Console,
+    args: Vec<&str>,
+) -> Result<()> {
+    let arguments = parse_arguments::<ChangePermissionsArguments>(&args, console)?;
+
+    for path in &arguments.paths {
+        let path = PathBuf::from(path);
+        let mut targets = vec![path.clone()];
+        if arguments.recursive && path.is_dir() {
+            targets.extend(file_operations::collect_entries(&path).map_err(|e| {
+                showln!(console, "Failed to change permissions: {}", e);
+                e
+            })?);
+        }
+
+        for target in targets {
+            file_operations::change_permissions(&target, &arguments.mode).map_err(|e| {
+                showln!(console, "Failed to change permissions: {}", e);
+                e
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn read_file(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<ReadFileArguments>(&args, console)?;
+    let (start, end) = match &arguments.bytes {
+        Some(range) => parse_byte_range(range).ok_or_else(|| {
+            showln!(console, "Invalid byte range: '{}'", range);
+            BuiltinError::InvalidValue(range.clone())
+        })?,
+        None => (0, None),
+    };
+
+    let read_error = |console: &mut Console| {
+        showln!(console, "Failed to read file: '{}'", arguments.path);
+        BuiltinError::FailedToRun
+    };
+
+    let mut reader = open_file_reader(&arguments.path, console)?;
+    let file_size = reader
+        .get_ref()
+        .metadata()
+        .map_err(|_| read_error(console))?
+        .len();
+
+    // When reading from the middle of the file, the line numbers have to account for the skipped lines
+    let mut line_number = 1;
+    if arguments.line_numbers {
+        let mut skipped = (&mut reader).take(start);
+        line_number += count_newlines(&mut skipped).map_err(|_| read_error(console))?;
+    }
+
+    reader
+        .seek(SeekFrom::Start(start))
+        .map_err(|_| read_error(console))?;
+    let end = end.unwrap_or(file_size).min(file_size);
+    let total_bytes = end.saturating_sub(start);
+    let mut reader = reader.take(total_bytes);
+
+    let page_height = console.output_height()?.saturating_sub(1).max(1);
+    let mut remaining_lines = page_height;
+    let mut bytes_read = 0;
+    let mut lines = Vec::new();
+    let mut buffer = Vec::new();
+    let mut highlighter = None;
+
+    loop {
+        buffer.clear();
+        match reader.read_until(b'\n', &mut buffer) {
+            Ok(0) => break,
+            Ok(length) => bytes_read += length as u64,
+            Err(_) => return Err(read_error(console).into()),
+        }
+
+        let line = String::from_utf8_lossy(&buffer);
+        // * The file type is detected from the first line that is read, in case the extension is not recognized
+        let highlighter = highlighter.get_or_insert_with(|| match arguments.plain {
+            true => None,
+            false => Highlighter::for_file(std::path::Path::new(&arguments.path), &line),
+        });
+
+        let mut spans = match highlighter {
+            Some(highlighter) => highlighter.highlight(&line),
+            None => Spans::from(line.trim_end_matches(['\n', '\r']).to_string()),
+        };
+
+        if arguments.line_numbers {
+            let number = format!("{:>6}  ", line_number);
+            let style = Style::default().add_modifier(Modifier::DIM);
+            spans.0.insert(0, Span::styled(number, style));
+        }
+
+        lines.push(spans);
+        line_number += 1;
+        // * Lines are only counted while paging, since the whole file is printed at once otherwise
+        if arguments.no_pager {
+            continue;
+        }
+
+        remaining_lines -= 1;
+        let at_end = reader.fill_buf().map_or(true, |buffer| buffer.is_empty());
+        if remaining_lines == 0 && !at_end {
+            console.println_styled_lines(lines.drain(..));
+
+            let percentage = bytes_read * 100 / total_bytes.max(1);
+            let status = format!(
+                "-- More ({}%) -- space: next page, enter: next line, q: quit",
+                percentage
+            );
+
+            remaining_lines = match console.wait_for_pager(&status)? {
+                PagerAction::NextPage => page_height,
+                PagerAction::NextLine => 1,
+                PagerAction::Quit => return Ok(()),
+            };
+        }
+    }
+
+    console.println_styled_lines(lines);
+    Ok(())
+}
+
+// Parses a byte range in the form 'START-END', where either end can be omitted
+fn parse_byte_range(range: &str) -> Option<(u64, Option<u64>)> {
+    let (start, end) = range.split_once('-')?;
+    let start = match start.trim() {
+        "" => 0,
+        start => start.parse().ok()?,
+    };
+    let end = match end.trim() {
+        "" => None,
+        end => Some(end.parse().ok()?),
+    };
+
+    match end {
+        Some(end) if end < start => None,
+        _ => Some((start, end)),
+    }
+}
+
+// Counts the newline characters in the rest of a reader
+fn count_newlines(reader: &mut impl BufRead) -> std::io::Result<u64> {
+    let mut count = 0;
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(count);
+        }
+
+        count += buffer.iter().filter(|&&byte| byte == b'\n').count() as u64;
+        let length = buffer.len();
+        reader.consume(length);
+    }
+}
+
+pub fn read_head(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<HeadArguments>(&args, console)?;
+    let mut reader = open_file_reader(&arguments.path, console)?;
+
+    let mut lines = Vec::with_capacity(arguments.lines);
+    let mut buffer = Vec::new();
+    while lines.len() < arguments.lines {
+        let Some(line) = read_lossy_line(&mut reader, &mut buffer).map_err(|_| {
+            showln!(console, "Failed to read file: '{}'", arguments.path);
+            BuiltinError::FailedToRun
+        })?
+        else {
+            break;
+        };
+
+        lines.push(line);
+    }
+
+    console.println_lines(lines);
+    Ok(())
+}
+
+pub fn read_tail(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<TailArguments>(&args, console)?;
+    let mut reader = open_file_reader(&arguments.path, console)?;
+
+    // Only the last few lines need to be kept in memory, no matter how large the file is
+    let mut lines = VecDeque::with_capacity(arguments.lines);
+    let mut buffer = Vec::new();
+    while let Some(line) = read_lossy_line(&mut reader, &mut buffer).map_err(|_| {
+        showln!(console, "Failed to read file: '{}'", arguments.path);
+        BuiltinError::FailedToRun
+    })? {
+        if lines.len() == arguments.lines {
+            lines.pop_front();
+        }
+
+        if arguments.lines > 0 {
+            lines.push_back(line);
+        }
+    }
+
+    console.println_lines(lines);
+
+    if arguments.follow {
+        follow_file(console, &arguments.path, reader)?;
+    }
+
+    Ok(())
+}
+
+// Prints lines as they are appended to a file, until the user presses Ctrl+C
+// * The file is polled rather than watched, which keeps this portable and lets the console handle input in between
+fn follow_file(
+    console: &mut Console,
+    path: &str,
+    mut reader: BufReader<fs_err::File>,
+) -> Result<()> {
+    const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+    let failed_to_read = |console: &mut Console| {
+        showln!(console, "Failed to read file: '{}'", path);
+        BuiltinError::FailedToRun
+    };
+
+    let mut position = reader
+        .stream_position()
+        .map_err(|_| failed_to_read(console))?;
+    // Holds any trailing bytes that have not been terminated by a newline yet
+    // * Bytes are kept rather than text, so that a character split between two writes is not mangled
+    let mut partial_line = Vec::new();
+
+    while !console.poll_interrupt(POLL_INTERVAL)? {
+        let length = fs_err::metadata(path)
+            .map_err(|_| failed_to_read(console))?
+            .len();
+
+        // If the file was truncated, start again from the beginning
+        if length < position {
+            reader
+                .seek(SeekFrom::Start(0))
+                .map_err(|_| failed_to_read(console))?;
+            partial_line.clear();
+        }
+
+        let read = reader
+            .read_to_end(&mut partial_line)
+            .map_err(|_| failed_to_read(console))?;
+        position = reader
+            .stream_position()
+            .map_err(|_| failed_to_read(console))?;
+
+        if read == 0 {
+            continue;
+        }
+
+        if let Some(end) = partial_line.iter().rposition(|&byte| byte == b'\n') {
+            let complete: Vec<String> = partial_line[..end]
+                .split(|&byte| byte == b'\n')
+                .map(|line| {
+                    String::from_utf8_lossy(line)
+                        .trim_end_matches('\r')
+                        .to_string()
+                })
+                .collect();
+            partial_line.drain(..=end);
+            console.println_lines(complete);
+        }
+    }
+
+    Ok(())
+}
+
+// Reads the next line of a file without its line ending, or None at the end of the file
+// * Bytes that are not valid UTF-8 are replaced rather than treated as an error, like when the whole file is read
+fn read_lossy_line(
+    reader: &mut impl BufRead,
+    buffer: &mut Vec<u8>,
+) -> std::io::Result<Option<String>> {
+    buffer.clear();
+    if reader.read_until(b'\n', buffer)? == 0 {
+        return Ok(None);
+    }
+
+    let line = String::from_utf8_lossy(buffer);
+    Ok(Some(line.trim_end_matches(['\n', '\r']).to_string()))
+}
+
+// Opens a file for buffered reading, printing an error message if it cannot be opened
+fn open_file_reader(path: &str, console: &mut Console) -> Result<BufReader<fs_err::File>> {
+    let file = fs_err::File::open(path).map_err(|_| {
+        showln!(console, "Failed to open file: '{}'", path);
+        BuiltinError::FailedToRun
+    })?;
+
+    Ok(BufReader::new(file))
+}
+
+pub fn run_executable(shell: &mut Shell, console: &mut Console, mut args: Vec<&str>) -> Result<()> {
+    let executable_name = args[0].to_string();
+    let executable_path = Path::from_str(&executable_name, shell.env().HOME()).map_err(|_| {
+        showln!(
+            console,
+            "Failed to resolve executable path: '{}'",
+            executable_name
+        );
+        BuiltinError::FailedToRun
+    })?;
+
+    // * Executable name is removed before running the executable because the std::process::Command
+    // * process builder automatically adds the executable name as the first argument
+    args.remove(0);
+
+    // Scripts with an associated interpreter are run through it, regardless of whether they have the executable bit set
+    match Executable::from_script(shell, executable_path.clone())? {
+        Some(executable) => executable.run(shell, console, args),
+        None => Executable::new(executable_path).run(shell, console, args),
+    }
+}
+
+pub fn run_with_priority(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    if args.len() < 2 {
+        showln!(
+            console,
+            "Usage: run-with-priority <niceness> <executable> [arguments]"
+        );
+        return Err(BuiltinError::InvalidArgumentCount(args.len()).into());
+    }
+
+    let niceness = args[0].parse::<i32>().map_err(|_| {
+        showln!(console, "Invalid niceness: '{}'", args[0]);
+        BuiltinError::InvalidValue(args[0].to_string())
+    })?;
+
+    let executable_path = resolve_executable(shell, console, args[1])?;
+
+    Executable::new(executable_path)
+        .with_niceness(niceness)
+        .run(shell, console, args[2..].to_vec())
+}
+
+pub fn date_time(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<DateTimeArguments>(&args, console)?;
+
+    // * chrono panics when displaying an invalid format, so the format has to be validated first
+    let items: Vec<Item> = StrftimeItems::new(&arguments.format).collect();
+    if items.contains(&Item::Error) {
+        showln!(console, "Invalid date-time format: '{}'", arguments.format);
+        return Err(BuiltinError::InvalidValue(arguments.format).into());
+    }
+
+    let formatted = if arguments.utc {
+        Utc::now().format_with_items(items.iter()).to_string()
+    } else {
+        Local::now().format_with_items(items.iter()).to_string()
+    };
+
+    showln!(console, "{}", formatted);
+    Ok(())
+}
+
+// Opens files or URLs with the default application for them
+pub fn open(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    if args.is_empty() {
+        showln!(console, "Usage: open <path or url>...");
+        return Err(BuiltinError::InvalidArgumentCount(args.len()).into());
+    }
+
+    for target in args {
+        let (program, mut arguments) = platform_opener();
+        arguments.push(target);
+        spawn_detached(program, &arguments).map_err(|e| {
+            showln!(console, "Failed to open '{}': {}", target, e);
+            e
+        })?;
+    }
+
+    Ok(())
+}
+
+// Gets the program (and any leading arguments) used to open files with their default application
+#[cfg(target_os = "macos")]
+fn platform_opener() -> (&'static str, Vec<&'static str>) {
+    ("open", Vec::new())
+}
+
+// * The empty argument is the window title, which 'start' would otherwise take from a quoted target
+#[cfg(windows)]
+fn platform_opener() -> (&'static str, Vec<&'static str>) {
+    ("cmd", vec!["/C", "start", ""])
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn platform_opener() -> (&'static str, Vec<&'static str>) {
+    ("xdg-open", Vec::new())
+}
+
+// Lists the available color schemes, or switches to one of them
+pub fn theme(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    match args.as_slice() {
+        [] => {
+            let current = console.theme().name.clone();
+            let lines: Vec<String> = Theme::builtin_themes()
+                .into_iter()
+                .map(|theme| match theme.name == current {
+                    true => format!("* {}", theme.name),
+                    false => format!("  {}", theme.name),
+                })
+                .collect();
+
+            console.println_lines(lines);
+            Ok(())
+        }
+        // * This is the same as 'configure theme <name>', so colors that were configured separately are kept
+        [name] => {
+            shell
+                .config_mut()
+                .set("theme", name, SettingSource::Runtime)
+                .map_err(|_| {
+                    showln!(console, "Unknown theme: '{}'", name);
+                    BuiltinError::InvalidValue(name.to_string())
+                })?;
+
+            console.set_theme(shell.config().theme(), shell);
+            Ok(())
+        }
+        _ => {
+            showln!(console, "Usage: theme [name]");
+            Err(BuiltinError::InvalidArgumentCount(args.len()).into())
+        }
+    }
+}
+
+pub fn calculate(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    if args.is_empty() {
+        showln!(console, "Usage: calculate <expression>");
+        return Err(BuiltinError::InvalidArgumentCount(args.len()).into());
+    }
+
+    // * The expression is joined back together so that it can be written with or without spaces
+    let expression = args.join(" ");
+    let value = arithmetic::evaluate(&expression).map_err(|e| {
+        showln!(console, "Invalid expression: {}", e);
+        e
+    })?;
+
+    showln!(console, "{}", arithmetic::format_number(value));
+    Ok(())
+}
+
+// Waits for the given amount of time, without blocking the UI
+// * Durations can have a unit suffix (ms, s, m, or h), and multiple durations are added together
+pub fn sleep(_shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    if args.is_empty() {
+        showln!(console, "Usage: sleep <duration>...");
+        return Err(BuiltinError::InvalidArgumentCount(args.len()).into());
+    }
+
+    let mut duration = Duration::ZERO;
+    for arg in &args {
+        duration = parse_duration(arg)
+            .and_then(|arg_duration| duration.checked_add(arg_duration))
+            .filter(|duration| *duration <= MAX_DURATION)
+            .ok_or_else(|| {
+                showln!(console, "Invalid duration: '{}'", arg);
+                BuiltinError::InvalidValue(arg.to_string())
+            })?;
+    }
+
+    if console.poll_interrupt(duration)? {
+        return Err(BuiltinError::Interrupted.into());
+    }
+
+    Ok(())
+}
+
+// Repeatedly runs a command, replacing its previous output each time, until the user presses Ctrl+C
+pub fn watch(
+    evaluator: &dyn Evaluator,
+    shell: &mut Shell,
+    console: &mut Console,
+    args: Vec<&str>,
+) -> Result<()> {
+    let arguments = parse_arguments::<WatchArguments>(&args, console)?;
+    let interval = parse_duration(&arguments.interval)
+        .filter(|interval| !interval.is_zero())
+        .ok_or_else(|| {
+            showln!(console, "Invalid interval: '{}'", arguments.interval);
+            BuiltinError::InvalidValue(arguments.interval.clone())
+        })?;
+
+    // * The command is run from its arguments as they were given, rather than being joined back into a line and parsed again
+    let (name, command_args) = arguments
+        .command
+        .split_first()
+        .ok_or(BuiltinError::InvalidArgumentCount(args.len()))?;
+    let command = arguments.command.join(" ");
+    let start = console.output_position();
+
+    loop {
+        console.truncate_output(start);
+        showln!(
+            console,
+            "Every {:?}: {} ({})",
+            interval,
+            command,
+            Local::now().format("%H:%M:%S")
+        );
+
+        // * A failing run does not stop the watch, since the command may succeed on a later run
+        let command_args = command_args.iter().map(|a| a.as_str()).collect();
+        if let Err(e) = evaluator.run_command(shell, console, name, command_args) {
+            showln!(console, "Error: {}", e);
+        }
+
+        if console.poll_interrupt(interval)? {
+            return Ok(());
+        }
+    }
+}
+
+// The longest duration that can be given to sleep or watch
+// * Anything longer is almost certainly a mistake, and could overflow when added to the current time
+const MAX_DURATION: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+// Parses a duration such as '1.5', '250ms', or '2m' (seconds are assumed if there is no unit), up to MAX_DURATION
+fn parse_duration(text: &str) -> Option<Duration> {
+    let unit_start = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (amount, unit) = text.split_at(unit_start);
+    let amount = amount.parse::<f64>().ok()?;
+
+    let seconds = match unit {
+        "ms" => amount / 1000.0,
+        "" | "s" => amount,
+        "m" => amount * 60.0,
+        "h" => amount * 60.0 * 60.0,
+        _ => return None,
+    };
+
+    Duration::try_from_secs_f64(seconds)
+        .ok()
+        .filter(|duration| *duration <= MAX_DURATION)
+}
+
+pub fn configure(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    match args.as_slice() {
+        ["list"] => {
+            let config = shell.config();
+            let rows: Vec<Vec<String>> = setting_keys()
+                .chain([String::from("interpreter")])
+                .map(|key| {
+                    let value = config.get(&key).unwrap_or_default();
+                    let source = format!("({})", config.source(&key));
+                    vec![key, value, source]
+                })
+                .collect();
+            console.println_columns(&rows, &[]);
+        }
+        ["get", key] => match shell.config().get(key) {
+            Some(value) => showln!(console, "{} ({})", value, shell.config().source(key)),
+            None => {
+                showln!(console, "Invalid configuration key: '{}'", key);
+                return Err(BuiltinError::InvalidArgument(key.to_string()).into());
+            }
+        },
+        ["save"] => {
+            shell
+                .config_mut()
+                .save()
+                .inspect_err(|e| showln!(console, "Failed to save configuration: {}", e))?;
+            showln!(console, "Configuration saved");
+        }
+        [key, value] => shell
+            .config_mut()
+            .set(key, value, SettingSource::Runtime)
+            .inspect_err(|e| showln!(console, "{}", e))?,
+        _ => {
+            showln!(
+                console,
+                "Usage: configure <key> <value>, configure get <key>, configure list, or configure save"
+            );
+            return Err(BuiltinError::InvalidArgumentCount(args.len()).into());
+        }
+    }
+
+    Ok(())
+}
+
+// * Settings that were changed with configure but not saved are discarded
+pub fn reload_config(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    check_args(&args, 0, "reload-config", console)?;
+    shell
+        .reload_config()
+        .inspect_err(|e| showln!(console, "Failed to reload configuration: {}", e))?;
+    for warning in shell.config_mut().take_warnings() {
+        showln!(console, "Configuration warning: {}", warning);
+    }
+
+    showln!(console, "Configuration reloaded");
+    Ok(())
+}
+
+pub fn environment_variable(
+    shell: &mut Shell,
+    console: &mut Console,
+    args: Vec<&str>,
+) -> Result<()> {
+    let arguments = parse_arguments::<EnvironmentVariableArguments>(&args, console)?;
+    let Some(name) = arguments.name else {
+        if arguments.export_format {
+            print_exported_vars(shell, console);
+        } else {
+            // * Variables that only exist inside the shell are marked, since child processes will not see them
+            let rows: Vec<Vec<String>> = shell
+                .env()
+                .all_vars()
+                .into_iter()
+                .map(|(name, value)| match shell.env().is_exported(&name) {
+                    true => vec![name, value],
+                    false => vec![name, value, String::from("(local)")],
+                })
+                .collect();
+            console.println_columns(&rows, &[]);
+        }
+
+        return Ok(());
+    };
+
+    if arguments.export_format {
+        match shell.env().get_var(&name) {
+            Some(value) => showln!(console, "{}", export_line(&name, &value)),
+            None => {
+                showln!(console, "Invalid environment variable: '{}'", name);
+                return Err(BuiltinError::InvalidArgument(name).into());
+            }
+        }
+
+        return Ok(());
+    }
+
+    match name.to_uppercase().as_str() {
+        "PATH" => {
+            for (i, path) in shell.env().PATH().iter().enumerate() {
+                showln!(console, "[{i}]: {path}");
+            }
+        }
+        "USER" => showln!(console, "{}", shell.env().USER()),
+        "HOME" => showln!(console, "{}", shell.env().HOME().display()),
+        "CWD" | "WORKING-DIRECTORY" => showln!(console, "{}", shell.env().CWD()),
+        _ => match shell.env().get_var(&name) {
+            Some(value) => showln!(console, "{}", value),
+            None => {
+                showln!(console, "Invalid environment variable: '{}'", name);
+                return Err(BuiltinError::InvalidArgument(name).into());
+            }
+        },
+    }
+
+    Ok(())
+}
+
+// Prints every variable that child processes inherit as a 'KEY=value' line
+fn print_exported_vars(shell: &Shell, console: &mut Console) {
+    let vars = shell.env().all_vars();
+    let exported = vars
+        .iter()
+        .filter(|(name, _)| shell.env().is_exported(name))
+        .map(|(name, value)| export_line(name, value));
+    console.println_lines(exported);
+}
+
+// Formats a variable as a 'KEY=value' line that POSIX shells can source
+// * Values are single-quoted unless they only contain characters that never need quoting
+fn export_line(name: &str, value: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "_-./:,@%+=".contains(c);
+    if !value.is_empty() && value.chars().all(is_safe) {
+        format!("{}={}", name, value)
+    } else {
+        format!("{}='{}'", name, value.replace('\'', "'\\''"))
+    }
+}
+
+pub fn show_last_error(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    check_args(&args, 0, "show-last-error", console)?;
+    let Some(last_error) = shell.last_error() else {
+        showln!(console, "No errors have occurred yet");
+        return Ok(());
+    };
+
+    let mut rows = vec![
+        vec![String::from("Command:"), last_error.command.clone()],
+        vec![String::from("Kind:"), last_error.kind.to_string()],
+        vec![String::from("Message:"), last_error.error.to_string()],
+    ];
+
+    // Any context added to the error comes first in the chain, followed by the errors that caused it
+    for (index, cause) in last_error.error.chain().skip(1).enumerate() {
+        let label = match index {
+            0 => String::from("Caused by:"),
+            _ => String::new(),
+        };
+        rows.push(vec![label, format!("{}: {}", index, cause)]);
+    }
+
+    console.println_columns(&rows, &[]);
+    Ok(())
+}
+
+pub fn set_variable(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    // * Variables can be set as either 'set-variable NAME value' or 'set-variable NAME=value'
+    let (name, value) = match args.len() {
+        1 if args[0].contains('=') => args[0].split_once('=').unwrap(),
+        2 => (args[0], args[1]),
+        _ => {
+            showln!(console, "Usage: set-variable <name> <value>");
+            return Err(BuiltinError::InvalidArgumentCount(args.len()).into());
+        }
+    };
+
+    let history_limit = shell.config().history_limit;
+    shell
+        .env_mut()
+        .set_var(name, value, history_limit)
+        .map_err(|e| {
+            showln!(console, "Failed to set variable: {}", e);
+            BuiltinError::FailedToRun.into()
+        })
+}
+
+// Marks variables to be inherited by child processes, optionally setting them at the same time
+// * Variables can be exported as 'export-variable NAME', 'export-variable NAME=value', or 'export-variable NAME value'
+pub fn export_variable(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let (name, value) = match args.as_slice() {
+        [] => {
+            print_exported_vars(shell, console);
+            return Ok(());
+        }
+        [definition] => match definition.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (*definition, None),
+        },
+        [name, value] => (*name, Some(*value)),
+        _ => {
+            showln!(console, "Usage: export-variable <name>[=<value>]");
+            return Err(BuiltinError::InvalidArgumentCount(args.len()).into());
+        }
+    };
+
+    let history_limit = shell.config().history_limit;
+    let env = shell.env_mut();
+    // * The variable is exported before it is set, so that its new value is exported along with it
+    env.export(name)
+        .and_then(|_| match value {
+            Some(value) => env.set_var(name, value, history_limit),
+            None => Ok(()),
+        })
+        .map_err(|e| {
+            showln!(console, "Failed to export variable: {}", e);
+            BuiltinError::FailedToRun.into()
+        })
+}
+
+pub fn read_input(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<ReadInputArguments>(&args, console)?;
+
+    let Some(input) = console.read_input(&arguments.prompt, arguments.hidden)? else {
+        return Err(BuiltinError::FailedToRun.into());
+    };
+
+    let history_limit = shell.config().history_limit;
+    shell
+        .env_mut()
+        .set_var(&arguments.variable, &input, history_limit)
+        .map_err(|e| {
+            showln!(console, "Failed to set variable: {}", e);
+            BuiltinError::FailedToRun.into()
+        })
+}
+
+pub fn unset_variable(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    check_args(&args, 1, "unset-variable <name>", console)?;
+    shell.env_mut().unset_var(args[0]).map_err(|e| {
+        showln!(console, "Failed to unset variable: {}", e);
+        BuiltinError::FailedToRun.into()
+    })
+}
+
+pub fn edit_path(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<EditPathArguments>(&args, console)?;
+    let resolve = |shell: &Shell, console: &mut Console, path: &str| {
+        Path::from_str(path, shell.env().HOME()).map_err(|_| {
+            showln!(console, "Invalid directory: '{}'", path);
+            BuiltinError::InvalidValue(path.to_string())
+        })
+    };
+
+    match arguments.action.unwrap_or(EditPathAction::List) {
+        EditPathAction::List => {
+            for (i, path) in shell.env().PATH().iter().enumerate() {
+                showln!(console, "[{i}]: {path}");
+            }
+
+            return Ok(());
+        }
+        EditPathAction::Append { path } => {
+            let path = resolve(shell, console, &path)?;
+            shell.env_mut().PATH_mut().push_back(path);
+        }
+        EditPathAction::Prepend { path } => {
+            let path = resolve(shell, console, &path)?;
+            shell.env_mut().PATH_mut().push_front(path);
+        }
+        EditPathAction::Insert { index, path } => {
+            let path = resolve(shell, console, &path)?;
+            if index > shell.env().PATH().len() {
+                showln!(console, "Invalid PATH index: {}", index);
+                return Err(BuiltinError::InvalidValue(index.to_string()).into());
+            }
+
+            shell.env_mut().PATH_mut().insert(index, path);
+        }
+        EditPathAction::Remove { target } => {
+            let removed = remove_path_entries(shell, &target);
+            if removed.is_empty() {
+                showln!(console, "No matching directory in the PATH: '{}'", target);
+                return Err(BuiltinError::InvalidArgument(target).into());
+            }
+
+            for path in removed {
+                showln!(console, "Removed '{}' from the PATH", path);
+            }
+        }
+        EditPathAction::Deduplicate => {
+            let mut seen = HashSet::new();
+            let path = shell.env_mut().PATH_mut();
+            let original_length = path.len();
+            path.retain(|directory| seen.insert(directory.clone()));
+
+            showln!(
+                console,
+                "Removed {} duplicate(s) from the PATH",
+                original_length - path.len()
+            );
+        }
+    }
+
+    shell.env_mut().export_PATH()
+}
+
+// Removes the PATH entries matching an index or a directory, returning the removed entries
+// * Directories are compared by their resolved path, or literally if they cannot be resolved (e.g. if they were deleted)
+fn remove_path_entries(shell: &mut Shell, target: &str) -> Vec<Path> {
+    if let Ok(index) = target.parse::<usize>() {
+        return shell
+            .env_mut()
+            .PATH_mut()
+            .remove(index)
+            .into_iter()
+            .collect();
+    }
+
+    let resolved = Path::from_str(target, shell.env().HOME()).ok();
+    let matches = |path: &Path| Some(path) == resolved.as_ref() || path.to_string() == target;
+
+    let path = shell.env_mut().PATH_mut();
+    let removed = path.iter().filter(|path| matches(path)).cloned().collect();
+    path.retain(|path| !matches(path));
+    removed
+}
+
+pub fn set_alias(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    // With no arguments, list every alias
+    if args.is_empty() {
+        let mut aliases: Vec<_> = shell.config().aliases.iter().collect();
+        aliases.sort();
+        for (name, command) in aliases {
+            showln!(console, "{} = {}", name, command);
+        }
+
+        return Ok(());
+    }
+
+    // * Aliases can be defined as either 'alias name command...' or 'alias name=command...'
+    let definition = match args[0].contains('=') {
+        true => args.join(" "),
+        false if args.len() > 1 => format!("{}={}", args[0], args[1..].join(" ")),
+        false => {
+            // With only a name, show that alias
+            match shell.config().aliases.get(args[0]) {
+                Some(command) => showln!(console, "{} = {}", args[0], command),
+                None => {
+                    showln!(console, "No such alias: '{}'", args[0]);
+                    return Err(BuiltinError::InvalidArgument(args[0].to_string()).into());
+                }
+            }
+
+            return Ok(());
+        }
+    };
+
+    shell.config_mut().set_alias(&definition).map_err(|e| {
+        showln!(console, "{}", e);
+        BuiltinError::InvalidValue(definition.clone())
+    })?;
+
+    save_aliases(shell, console);
+    Ok(())
+}
+
+pub fn remove_alias(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    check_args(&args, 1, "remove-alias <name>", console)?;
+    if shell.config_mut().aliases.remove(args[0]).is_none() {
+        showln!(console, "No such alias: '{}'", args[0]);
+        return Err(BuiltinError::InvalidArgument(args[0].to_string()).into());
+    }
+
+    save_aliases(shell, console);
+    Ok(())
+}
+
+// Persists the aliases to the configuration file
+// * A failure to persist is only a warning, because the alias has still been changed for this session
+fn save_aliases(shell: &Shell, console: &mut Console) {
+    if let Err(e) = shell.config().save_aliases() {
+        showln!(console, "Warning: alias was not saved: {}", e);
+    }
+}
+
+// Lists, shows, or changes the key bindings for Console actions and commands
+// * Bindings can be given as either 'keybind key action' or 'keybind key=action', and binding to 'none' removes a binding
+// * A key can also run a command line, e.g. 'keybind ctrl+g "run:git status"'
+pub fn keybind(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let definition = match args.as_slice() {
+        [] => {
+            let lines: Vec<String> = shell
+                .config()
+                .keymap
+                .bindings()
+                .into_iter()
+                .map(|(binding, action)| format!("{:<16}{}", binding.to_string(), action))
+                .collect();
+            console.println_lines(lines);
+            return Ok(());
+        }
+        ["--actions"] => {
+            let actions = ConsoleAction::all().iter().map(ToString::to_string);
+            console.println_lines(actions.chain([format!("{}<command>", COMMAND_PREFIX)]));
+            return Ok(());
+        }
+        [definition] if definition.contains('=') => definition.to_string(),
+        [key] => {
+            let binding = parse_key_binding(key, console)?;
+            match shell.config().keymap.action_for(&binding) {
+                Some(action) => showln!(console, "{}", action),
+                None => showln!(console, "'{}' is not bound", binding),
+            }
+
+            return Ok(());
+        }
+        [key, action] => format!("{}={}", key, action),
+        _ => {
+            showln!(
+                console,
+                "Usage: keybind [key [action | {}<command> | none]]",
+                COMMAND_PREFIX
+            );
+            return Err(BuiltinError::InvalidArgumentCount(args.len()).into());
+        }
+    };
+
+    shell.config_mut().keymap.apply(&definition).map_err(|e| {
+        showln!(console, "{}", e);
+        showln!(
+            console,
+            "Run 'keybind --actions' to see the available actions"
+        );
+        BuiltinError::InvalidValue(definition.clone())
+    })?;
+
+    if let Err(e) = shell.config().save_keybindings() {
+        showln!(console, "Warning: key binding was not saved: {}", e);
+    }
+
+    Ok(())
+}
+
+// Parses a key combination such as 'ctrl+k', printing an error message if it is invalid
+fn parse_key_binding(key: &str, console: &mut Console) -> Result<KeyBinding> {
+    key.parse::<KeyBinding>().map_err(|_| {
+        showln!(console, "Invalid key: '{}'", key);
+        BuiltinError::InvalidValue(key.to_string()).into()
+    })
+}
+
+pub fn history(
+    evaluator: &dyn Evaluator,
+    shell: &mut Shell,
+    console: &mut Console,
+    args: Vec<&str>,
+) -> Result<()> {
+    let arguments = parse_arguments::<HistoryArguments>(&args, console)?;
+
+    match arguments.action.unwrap_or(HistoryAction::List) {
+        HistoryAction::List => {
+            for (index, command) in shell.history().iter().enumerate() {
+                showln!(console, "[{}] {}", index, command);
+            }
+        }
+        HistoryAction::Search { pattern, regex } => {
+            let matcher = match regex {
+                true => Regex::new(&pattern).map_err(|e| {
+                    showln!(console, "Invalid regex: {}", e);
+                    BuiltinError::InvalidValue(pattern.clone())
+                })?,
+                false => Regex::new(&regex::escape(&pattern))?,
+            };
+
+            for (index, command) in shell.history().iter().enumerate() {
+                if matcher.is_match(command) {
+                    showln!(console, "[{}] {}", index, command);
+                }
+            }
+        }
+        HistoryAction::Run { index } => {
+            let Some(line) = shell.history().get(index).cloned() else {
+                showln!(console, "No command at history index {}", index);
+                return Err(BuiltinError::InvalidValue(index.to_string()).into());
+            };
+
+            showln!(console, "{}", line);
+            evaluator.eval(shell, console, &line)?;
+        }
+        HistoryAction::Clear => shell.history_clear(),
+    }
+
+    Ok(())
+}
+
+// * Commands are only kept in memory for now, so this does not need to touch the filesystem
+pub fn clear_history(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    let arguments = parse_arguments::<ClearHistoryArguments>(&args, console)?;
+    let removed = match arguments.last {
+        Some(count) => shell.history_remove_last(count),
+        None => {
+            let count = shell.history().len();
+            shell.history_clear();
+            count
+        }
+    };
+
+    showln!(console, "Removed {} command(s) from the history", removed);
+    Ok(())
+}
+
+pub fn source(
+    evaluator: &dyn Evaluator,
+    shell: &mut Shell,
+    console: &mut Console,
+    args: Vec<&str>,
+) -> Result<()> {
+    check_args(&args, 1, "source <path>", console)?;
+    source_file(evaluator, shell, console, args[0])
+}
+
+// Evaluates every line of a file as a command, skipping blank lines and comments
+// * This is shared with the startup script, which is run the same way as a file given to the source command
+pub fn source_file(
+    evaluator: &dyn Evaluator,
+    shell: &mut Shell,
+    console: &mut Console,
+    file_name: &str,
+) -> Result<()> {
+    let open_error = |console: &mut Console| {
+        showln!(console, "Failed to open file: '{}'", file_name);
+        BuiltinError::FailedToRun
+    };
+    let path = fs_err::canonicalize(file_name).map_err(|_| open_error(console))?;
+    let contents = fs_err::read_to_string(&path).map_err(|_| open_error(console))?;
+
+    // * A script that sources itself (directly or through another script) would otherwise recurse until the stack overflows
+    if !shell.start_sourcing(path.clone()) {
+        return Err(BuiltinError::AlreadySourcing(path).into());
+    }
+
+    // Every line is evaluated against the current shell, so changes like the working directory persist afterwards
+    // * A failing line does not stop the rest of the file from being evaluated, but the source command will still fail
+    let mut failed_lines = 0;
+    for (line_number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Err(e) = evaluator.eval(shell, console, line) {
+            showln!(console, "{}:{}: {}", file_name, line_number + 1, e);
+            failed_lines += 1;
+        }
+    }
+
+    shell.finish_sourcing();
+    match failed_lines {
+        0 => Ok(()),
+        _ => Err(BuiltinError::FailedToRun.into()),
+    }
+}
+
+// Lists everything a command name could refer to, in the order they are checked
+pub fn where_command(
+    evaluator: &dyn Evaluator,
+    shell: &mut Shell,
+    console: &mut Console,
+    args: Vec<&str>,
+) -> Result<()> {
+    check_args(&args, 1, "where <command>", console)?;
+    let name = args[0];
+    let mut rows = Vec::new();
+    let mut shadowed_by = None;
+
+    if let Some(alias) = shell.config().aliases.get(name) {
+        rows.push(vec![
+            String::from("alias"),
+            format!("'{}'", alias),
+            String::new(),
+        ]);
+        shadowed_by = Some("alias");
+    }
+
+    if let Some(builtin) = evaluator.builtin_name(name) {
+        let note = match shadowed_by {
+            Some(shadow) => format!("(shadowed by {})", shadow),
+            None => String::new(),
+        };
+        rows.push(vec![String::from("builtin"), builtin, note]);
+        shadowed_by = shadowed_by.or(Some("builtin"));
+    }
+
+    // * The executable that is chosen comes from the executable cache, so this also shows if the cache is out of date
+    let mut chosen = shell.env_mut().resolve_executable(name).ok();
+    for (index, executable) in Path::all_from_path_var(name, shell.env().PATH())
+        .into_iter()
+        .enumerate()
+    {
+        // * The same directory can appear in the PATH more than once, but only its first occurrence is chosen
+        let is_chosen = chosen.as_ref() == Some(&executable);
+        if is_chosen {
+            chosen = None;
+        }
+
+        let note = match (shadowed_by, is_chosen) {
+            (Some(shadow), true) => format!("(shadowed by {})", shadow),
+            (None, true) => String::from("(chosen)"),
+            (_, false) => String::new(),
+        };
+        rows.push(vec![format!("[{}]", index), executable.to_string(), note]);
+    }
+
+    if rows.is_empty() {
+        showln!(
+            console,
+            "No builtin, alias, or executable found for '{}'",
+            name
+        );
+        return Err(BuiltinError::InvalidArgument(name.to_string()).into());
+    }
+
+    console.println_columns(&rows, &[]);
+    Ok(())
+}
+
+pub fn rehash(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    check_args(&args, 0, "rehash", console)?;
+    let count = shell.env_mut().rehash();
+    showln!(console, "Found {} executables in PATH", count);
+    Ok(())
+}
+
+pub fn run_in_background(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    if args.is_empty() {
+        showln!(console, "Usage: run-in-background <executable> [arguments]");
+        return Err(BuiltinError::InvalidArgumentCount(args.len()).into());
+    }
+
+    let executable_path = resolve_executable(shell, console, args[0])?;
+    let (id, log_path) = spawn_job(shell, executable_path, args[0], &args[1..]).map_err(|e| {
+        showln!(console, "Failed to start background job: {}", e);
+        BuiltinError::FailedToRun
+    })?;
+
+    showln!(
+        console,
+        "[{}] Started, logging output to {}",
+        id,
+        log_path.display()
+    );
+    Ok(())
+}
+
+pub fn list_jobs(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    check_args(&args, 0, "list-jobs", console)?;
+    for job in shell.jobs().jobs() {
+        showln!(
+            console,
+            "[{}] {} (pid {}, output: {})",
+            job.id,
+            job.command,
+            job.pid(),
+            job.log_path.display()
+        );
+    }
+
+    Ok(())
+}
+
+pub fn detach_job(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    if args.is_empty() || args.len() > 2 {
+        showln!(
+            console,
+            "Usage: detach-job <job id> [output file | /dev/null]"
+        );
+        return Err(BuiltinError::InvalidArgumentCount(args.len()).into());
+    }
+
+    let id = parse_job_id(args[0], console)?;
+    let Some(log_path) = shell
+        .jobs()
+        .jobs()
+        .iter()
+        .find(|job| job.id == id)
+        .map(|job| job.log_path.clone())
+    else {
+        showln!(console, "No such job: {}", id);
+        return Err(BuiltinError::InvalidArgument(args[0].to_string()).into());
+    };
+
+    match args.get(1) {
+        // * A running process's output cannot be redirected from outside, so the log is emptied and unlinked instead,
+        // * and whatever the job writes after this still takes up space until it exits
+        Some(&"/dev/null") => {
+            _ = fs_err::OpenOptions::new()
+                .write(true)
+                .open(&log_path)
+                .and_then(|file| file.set_len(0));
+            _ = fs_err::remove_file(&log_path);
+            showln!(
+                console,
+                "[{}] Output discarded, though it uses disk space until the job exits",
+                id
+            );
+        }
+        Some(destination) => {
+            let moved = move_job_output(&log_path, destination).map_err(|_| {
+                showln!(console, "Failed to move job output to '{}'", destination);
+                BuiltinError::FailedToRun
+            })?;
+
+            if !moved {
+                showln!(
+                    console,
+                    "[{}] Output so far copied to '{}', but the job keeps writing to '{}'",
+                    id,
+                    destination,
+                    log_path.display()
+                );
+            }
+        }
+        None => (),
+    }
+
+    // * The job is only removed once its output has been dealt with, so that it is still listed (and hung up on exit) if that fails
+    if let Some(job) = shell.jobs_mut().remove(id) {
+        showln!(console, "[{}] Detached: {}", job.id, job.command);
+    }
+
+    Ok(())
+}
+
+// Moves a job's log file to a new path, returning whether the job's later output will end up there too
+// * The job keeps its handle to the log file, so renaming the file redirects whatever it writes from now on
+// * Renaming does not work across filesystems (and the log is in the temporary directory), so in that case
+// * the output so far is copied instead and the log is emptied, since it is opened for appending
+fn move_job_output(log_path: &StdPath, destination: &str) -> std::io::Result<bool> {
+    match fs_err::rename(log_path, destination) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            fs_err::copy(log_path, destination)?;
+            fs_err::OpenOptions::new()
+                .write(true)
+                .open(log_path)?
+                .set_len(0)?;
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub fn show_job(shell: &mut Shell, console: &mut Console, args: Vec<&str>) -> Result<()> {
+    if args.len() > 1 {
+        showln!(console, "Usage: show-job [job id]");
+        return Err(BuiltinError::InvalidArgumentCount(args.len()).into());
+    }
+
+    // * Without a job ID, the most recently started job is shown
+    let job = match args.first() {
+        Some(arg) => {
+            let id = parse_job_id(arg, console)?;
+            shell
+                .jobs()
+                .jobs()
+                .iter()
+                .find(|job| job.id == id)
+                .ok_or_else(|| {
+                    showln!(console, "No such job: {}", id);
+                    BuiltinError::InvalidArgument(arg.to_string())
+                })?
+        }
+        None => shell.jobs().jobs().last().ok_or_else(|| {
+            showln!(console, "There are no background jobs to show");
+            BuiltinError::FailedToRun
+        })?,
+    };
+
+    console.show_job(job.id, &job.command, &job.log_path);
+    Ok(())
+}
+
+// Parses a job ID, which can be written with a leading '%' like in other shells
+fn parse_job_id(arg: &str, console: &mut Console) -> Result<usize> {
+    arg.trim_start_matches('%').parse::<usize>().map_err(|_| {
+        showln!(console, "Invalid job ID: '{}'", arg);
+        BuiltinError::InvalidValue(arg.to_string()).into()
+    })
+}
+
+// Resolves an executable given either as a path or as a name to be looked up in the PATH
+fn resolve_executable(shell: &mut Shell, console: &mut Console, name: &str) -> Result<Path> {
+    let path = if name.contains('/') {
+        Path::from_str(name, shell.env().HOME())
+    } else {
+        shell.env_mut().resolve_executable(name)
+    };
+
+    path.map_err(|_| {
+        showln!(console, "Failed to resolve executable path: '{}'", name);
+        BuiltinError::FailedToRun.into()
+    })
+}
+
+// Interprets the backslash escape sequences supported by 'echo -e'
+// Returns the interpreted string, and whether a \c escape was found (which truncates the output)
+fn interpret_escapes(text: &str) -> (String, bool) {
+    let mut result = String::new();
+    let mut chars = text.chars().peekable();
+
+    // Reads up to the given number of digits in the given radix, returning the character they encode
+    // * If no digits follow, there is no character, and the escape is printed as it is written
+    let read_code =
+        |chars: &mut std::iter::Peekable<std::str::Chars>, radix: u32, max_digits: usize| {
+            let mut code = 0;
+            let mut digits = 0;
+            while digits < max_digits {
+                match chars.peek().and_then(|c| c.to_digit(radix)) {
+                    Some(digit) => {
+                        code = code * radix + digit;
+                        digits += 1;
+                        chars.next();
+                    }
+                    None => break,
+                }
+            }
+
+            (digits > 0).then(|| char::from_u32(code)).flatten()
+        };
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some('v') => result.push('\u{0B}'),
+            Some('a') => result.push('\u{07}'),
+            Some('b') => result.push('\u{08}'),
+            Some('e') => result.push('\u{1B}'),
+            Some('f') => result.push('\u{0C}'),
+            Some('\\') => result.push('\\'),
+            Some('0') => match read_code(&mut chars, 8, 3) {
+                Some(code) => result.push(code),
+                None => result.push_str("\\0"),
+            },
+            Some('x') => match read_code(&mut chars, 16, 2) {
+                Some(code) => result.push(code),
+                None => result.push_str("\\x"),
+            },
+            Some('c') => return (result, true),
+            // Unknown escapes are printed as-is
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+
+    (result, false)
+}
+
+// Parses a builtin's arguments using clap, printing the usage/error message to the console on failure
+// * clap's parse_from() exits the process on error, which is not acceptable for a builtin
+fn parse_arguments<T: Parser>(args: &[&str], console: &mut Console) -> Result<T> {
+    T::try_parse_from(args).map_err(|e| {
+        showln!(console, "{}", e.render().to_string().trim_end());
+        BuiltinError::InvalidArgument(args.join(" ")).into()
+    })
+}
+
 // Convenience function for exiting a builtin on invalid argument count
 fn check_args(
-    args: &Vec<&str>,
+    args: &[&str],
     expected_args: usize,
     usage: &str,
     console: &mut Console,
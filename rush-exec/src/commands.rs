@@ -1,24 +1,55 @@
-use std::io::{BufRead, BufReader};
-use std::process::{Command as Process, Stdio};
-use std::sync::mpsc;
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader};
+#[cfg(unix)]
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::path::PathBuf;
+use std::process::{Child, Command as Process, ExitStatus, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
+use clap::{ArgAction, Command as ClapCommand, CommandFactory};
 
+use rush_state::config::OutputOverflow;
 use rush_state::console::Console;
 use rush_state::path::Path;
-use rush_state::shell::Shell;
+use rush_state::shell::{ResourceUsage, Shell};
 use rush_state::showln;
+use rush_state::signature::{CommandSignature, OptionSignature, PositionalSignature};
+use rush_state::temp_file;
 
 use crate::errors::ExecutableError;
 
-// Represents either a builtin (internal command) or an executable (external command)
-// A Runnable may be executed by calling its .run() method
+// How long to collect child output before redrawing the console (roughly 60 frames per second)
+const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+// The maximum number of lines to display in a single frame
+const MAX_LINES_PER_FRAME: usize = 1024;
+// The number of lines that can be buffered between the reader threads and the console
+const OUTPUT_CHANNEL_CAPACITY: usize = 4096;
+
+// Represents an external command that may be executed by calling its .run() method
+// * Builtins are run through Builtin.run() instead, because they may need access to an Evaluator
 pub trait Runnable {
     fn run(&self, shell: &mut Shell, console: &mut Console, arguments: Vec<&str>) -> Result<()>;
 }
 
+// Represents something that can evaluate a line of input as if the user had typed it at the prompt
+// * This is implemented by the Dispatcher, and allows builtins like 'history run' to run other commands
+pub trait Evaluator {
+    fn eval(&self, shell: &mut Shell, console: &mut Console, line: &str) -> Result<()>;
+    // Runs a command that has already been split into its name and arguments, so nothing in them is parsed again
+    fn run_command(
+        &self,
+        shell: &mut Shell,
+        console: &mut Console,
+        name: &str,
+        arguments: Vec<&str>,
+    ) -> Result<()>;
+    // Gets the true name of the builtin that a command name refers to, if there is one
+    fn builtin_name(&self, command_name: &str) -> Option<String>;
+}
+
 // Wrapper type for Vec<String> that makes it easier to read code related to Builtins
 pub struct Aliases {
     aliases: Vec<String>,
@@ -37,13 +68,23 @@ impl Aliases {
     pub fn contains(&self, alias: &str) -> bool {
         self.aliases.contains(&alias.to_string())
     }
+
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        self.aliases.iter()
+    }
 }
 
+// The signature shared by all builtin functions
+// * Most builtins do not need the Evaluator, so they are wrapped into this form by Builtin::new()
+type BuiltinFunction = dyn Fn(&dyn Evaluator, &mut Shell, &mut Console, Vec<&str>) -> Result<()>;
+
 // Represents a builtin function, its name and its aliases
 pub struct Builtin {
     pub true_name: String,
     pub aliases: Aliases,
-    function: Box<dyn Fn(&mut Shell, &mut Console, Vec<&str>) -> Result<()>>,
+    // The arguments that the builtin accepts, if it parses them with clap
+    pub signature: Option<CommandSignature>,
+    function: Box<BuiltinFunction>,
 }
 
 impl Builtin {
@@ -51,6 +92,19 @@ impl Builtin {
         true_name: &str,
         aliases: Vec<&str>,
         function: F,
+    ) -> Self {
+        Self::with_evaluator(true_name, aliases, move |_, shell, console, arguments| {
+            function(shell, console, arguments)
+        })
+    }
+
+    // Creates a builtin which is given access to an Evaluator, so that it can run other commands
+    pub fn with_evaluator<
+        F: Fn(&dyn Evaluator, &mut Shell, &mut Console, Vec<&str>) -> Result<()> + 'static,
+    >(
+        true_name: &str,
+        aliases: Vec<&str>,
+        function: F,
     ) -> Self {
         let true_name = true_name.to_string();
         let aliases = Aliases::from(aliases);
@@ -59,20 +113,84 @@ impl Builtin {
         Self {
             true_name,
             aliases,
+            signature: None,
             function,
         }
     }
+
+    // Describes the arguments of the builtin using the clap definition that it parses them with
+    pub fn arguments<T: CommandFactory>(&mut self) {
+        self.signature = Some(command_signature(&self.true_name, &T::command()));
+    }
+
+    pub fn run(
+        &self,
+        evaluator: &dyn Evaluator,
+        shell: &mut Shell,
+        console: &mut Console,
+        arguments: Vec<&str>,
+    ) -> Result<()> {
+        (self.function)(evaluator, shell, console, arguments)
+    }
 }
 
-impl Runnable for Builtin {
-    fn run(&self, shell: &mut Shell, console: &mut Console, arguments: Vec<&str>) -> Result<()> {
-        (self.function)(shell, console, arguments)
+// Converts a clap definition into a signature that the console can show hints from
+// * Hidden arguments are left out, and the automatic help flag is not included because the command is never built
+fn command_signature(name: &str, command: &ClapCommand) -> CommandSignature {
+    let mut signature = CommandSignature {
+        name: name.to_string(),
+        ..Default::default()
+    };
+
+    for argument in command.get_arguments().filter(|a| !a.is_hide_set()) {
+        let possible_values = argument
+            .get_possible_values()
+            .iter()
+            .filter(|value| !value.is_hide_set())
+            .map(|value| value.get_name().to_string())
+            .collect();
+        // * Clap names a value after the id of its argument, unless it was given a different name
+        let value_name = argument
+            .get_value_names()
+            .and_then(|names| names.first())
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| argument.get_id().to_string().to_uppercase());
+
+        if argument.is_positional() {
+            let variadic = matches!(argument.get_action(), ArgAction::Append)
+                || argument
+                    .get_num_args()
+                    .is_some_and(|range| range.max_values() > 1);
+            signature.positionals.push(PositionalSignature {
+                name: value_name,
+                required: argument.is_required_set(),
+                variadic,
+                possible_values,
+            });
+        } else {
+            signature.options.push(OptionSignature {
+                short: argument.get_short(),
+                long: argument.get_long().map(str::to_string),
+                value_name: argument.get_action().takes_values().then_some(value_name),
+                possible_values,
+            });
+        }
     }
+
+    signature.subcommands = command
+        .get_subcommands()
+        .map(|subcommand| command_signature(subcommand.get_name(), subcommand))
+        .collect();
+    signature
 }
 
 // Represents an external binary/executable
 pub struct Executable {
     path: Path,
+    // If the executable is an interpreter, the script that it should run
+    script: Option<Path>,
+    // The scheduling priority to launch the executable with, overriding the configured default
+    niceness: Option<i32>,
 }
 
 impl Executable {
@@ -81,138 +199,393 @@ impl Executable {
     // * 2. When the user invokes an external binary that is in the PATH without using the run-executable builtin (implicit invocation)
     // * The Path wrapper type must be validated before construction, so it can be assumed that the path is valid
     pub fn new(path: Path) -> Self {
-        Self { path }
+        Self {
+            path,
+            script: None,
+            niceness: None,
+        }
+    }
+
+    // Sets the scheduling priority that the executable will be launched with
+    pub fn with_niceness(mut self, niceness: i32) -> Self {
+        self.niceness = Some(niceness);
+        self
+    }
+
+    // Starts the executable, telling a missing file apart from other failures
+    fn spawn(&self, command: &mut Process) -> Result<Child> {
+        let process = command.spawn().map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => {
+                ExecutableError::PathNoLongerExists(self.path.path().clone())
+            }
+            _ => ExecutableError::FailedToSpawn(e.to_string()),
+        })?;
+
+        Ok(process)
+    }
+
+    // Attempts to construct an Executable which runs a script through the interpreter associated with its file extension
+    // Returns None if the Configuration does not associate any interpreter with the script's extension
+    pub fn from_script(shell: &mut Shell, script: Path) -> Result<Option<Self>> {
+        let Some(interpreter) = shell.config().interpreter_for(script.path()).cloned() else {
+            return Ok(None);
+        };
+
+        let interpreter_path = shell
+            .env_mut()
+            .resolve_executable(&interpreter)
+            .map_err(|_| ExecutableError::InterpreterNotFound(interpreter))?;
+
+        Ok(Some(Self {
+            path: interpreter_path,
+            script: Some(script),
+            niceness: None,
+        }))
     }
 }
 
 impl Runnable for Executable {
-    // * Executables do not have access to the shell state, but the context argument is required by the Runnable trait
+    // * Executables do not modify the shell state, but the configuration is read to decide how to handle their output
     // TODO: Remove as many .unwrap() calls as possible here
-    fn run(&self, _shell: &mut Shell, console: &mut Console, arguments: Vec<&str>) -> Result<()> {
-        // Create the Process, pass the provided arguments to it, and execute it
-        let Ok(mut process) = Process::new(self.path.path())
-            .args(arguments)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-        else {
-            return Err(ExecutableError::PathNoLongerExists(self.path.path().clone()).into())
+    fn run(&self, shell: &mut Shell, console: &mut Console, arguments: Vec<&str>) -> Result<()> {
+        let start_time = Instant::now();
+
+        // Create the Process and pass the provided arguments to it
+        // * If this is an interpreted script, the script path is passed to the interpreter ahead of the user's arguments
+        let script = self.script.as_ref().map(|s| s.path().as_os_str());
+        let mut command = Process::new(self.path.path());
+        command.args(script).args(arguments);
+
+        // An explicit niceness for this executable takes priority over the configured default
+        if let Some(niceness) = self.niceness.or(shell.config().niceness) {
+            set_niceness(&mut command, niceness);
+        }
+
+        // Programs that draw over the whole terminal (such as editors and pagers) are given the terminal while they run,
+        // and otherwise the output is captured and printed to the console
+        let (status, usage) = match shell.config().is_full_screen_program(self.path.path()) {
+            true => console.run_full_screen(|| {
+                let mut process = self.spawn(&mut command)?;
+                ignoring_interrupts(|| wait_with_usage(&mut process))
+            })??,
+            false => {
+                command.stdout(Stdio::piped()).stderr(Stdio::piped());
+                let mut process = self.spawn(&mut command)?;
+                capture_output(shell, console, &mut process)?;
+                wait_with_usage(&mut process)?
+            }
         };
 
-        // Create channels for communication between threads
-        let (tx_stdout, rx_stdout) = mpsc::channel::<Result<String>>();
-        let (tx_stderr, rx_stderr) = mpsc::channel::<Result<String>>();
-
-        // Spawn a thread to read stdout
-        let stdout_thread = {
-            let stdout = process.stdout.take().unwrap();
-            thread::spawn(move || {
-                let reader = BufReader::new(stdout);
-                for line in reader.lines() {
-                    // If the line is Ok, send it to the main thread
-                    match line {
-                        Ok(line) => {
-                            // If sending the line fails, return an error
-                            if let Err(e) = tx_stdout.send(Ok(line)) {
-                                return Err(ExecutableError::FailedToParseStdout(e.to_string()));
-                            }
-                        }
-                        // If reading the line fails, return an error
-                        Err(e) => {
+        let usage = ResourceUsage {
+            wall_time: start_time.elapsed(),
+            ..usage
+        };
+
+        shell.set_last_resource_usage(usage);
+        if shell.config().show_resource_usage {
+            showln!(console, "{}", usage);
+        }
+
+        match status.success() {
+            true => Ok(()),
+            false => {
+                // * 126 is a special exit code that means that the command was found but could not be executed
+                // * as per https://tldp.org/LDP/abs/html/exitcodes.html
+                // * It can be assumed that the command was found here because the External path must have been validated already
+                // * Otherwise it could be a 127 for "command not found"
+                let code = status.code().unwrap_or(126);
+                shell.set_executable_exit_code(code);
+                Err(ExecutableError::FailedToExecute(code as isize).into())
+            }
+        }
+    }
+}
+
+// Prints the output of a child process to the console as it arrives, until both of its output pipes are closed
+fn capture_output(shell: &Shell, console: &mut Console, process: &mut Child) -> Result<()> {
+    // Create a bounded channel shared by the stdout and stderr threads
+    // * Because the channel is bounded, the reader threads will block once it is full,
+    // * which in turn stops draining the child's pipes and eventually blocks the child itself
+    let (tx_stdout, rx) = mpsc::sync_channel::<Result<String>>(OUTPUT_CHANNEL_CAPACITY);
+    let tx_stderr = tx_stdout.clone();
+
+    // Spawn a thread to read stdout
+    let stdout_thread = {
+        let stdout = process.stdout.take().unwrap();
+        thread::spawn(move || {
+            let reader = BufReader::new(stdout);
+            for line in reader.lines() {
+                // If the line is Ok, send it to the main thread
+                match line {
+                    Ok(line) => {
+                        // If sending the line fails, return an error
+                        if let Err(e) = tx_stdout.send(Ok(line)) {
                             return Err(ExecutableError::FailedToParseStdout(e.to_string()));
                         }
                     }
+                    // If reading the line fails, return an error
+                    Err(e) => {
+                        return Err(ExecutableError::FailedToParseStdout(e.to_string()));
+                    }
                 }
-                Ok(())
-            })
-        };
+            }
+            Ok(())
+        })
+    };
 
-        let stderr_thread = {
-            let stderr = process.stderr.take().unwrap();
-            thread::spawn(move || {
-                let reader = BufReader::new(stderr);
-                for line in reader.lines() {
-                    match line {
-                        Ok(line) => {
-                            if let Err(e) = tx_stderr.send(Ok(line)) {
-                                return Err(ExecutableError::FailedToParseStderr(e.to_string()));
-                            }
-                        }
-                        Err(e) => {
+    let stderr_thread = {
+        let stderr = process.stderr.take().unwrap();
+        thread::spawn(move || {
+            let reader = BufReader::new(stderr);
+            for line in reader.lines() {
+                match line {
+                    Ok(line) => {
+                        if let Err(e) = tx_stderr.send(Ok(line)) {
                             return Err(ExecutableError::FailedToParseStderr(e.to_string()));
                         }
                     }
+                    Err(e) => {
+                        return Err(ExecutableError::FailedToParseStderr(e.to_string()));
+                    }
                 }
-                Ok(())
-            })
-        };
-
-        let read_timeout = Duration::from_millis(100);
-        let sleep_timeout = Duration::from_millis(10);
-
-        let mut stdout_done = false;
-        let mut stderr_done = false;
-        let mut process_done = false;
-
-        while !stdout_done || !stderr_done || !process_done {
-            if let Ok(packet) = rx_stdout.recv_timeout(read_timeout) {
-                // If the packet is Ok, unpack it and print it
-                if let Ok(line) = packet {
-                    showln!(console, "{}", &line);
-                // If the packet is Err, propagate err up the stack
-                } else {
-                    packet?;
-                }
-            } else {
-                stdout_done = true;
             }
-            if let Ok(packet) = rx_stderr.recv_timeout(read_timeout) {
-                if let Ok(line) = packet {
-                    showln!(console, "{}", &line);
-                } else {
-                    packet?;
-                }
-            } else {
-                stderr_done = true;
+            Ok(())
+        })
+    };
+
+    let overflow = shell.config().output_overflow;
+    let mut output_done = false;
+    console.begin_running();
+
+    // Coalesce the child's output into one batch per frame, so that fast-printing children
+    // do not trigger a full redraw for every single line they print
+    while !output_done {
+        let mut batch = VecDeque::new();
+        let mut dropped_lines = 0;
+        let frame_deadline = Instant::now() + FRAME_INTERVAL;
+
+        loop {
+            // Under the blocking policy, stop draining once the frame is full and leave the rest in the channel
+            if overflow == OutputOverflow::Block && batch.len() >= MAX_LINES_PER_FRAME {
+                break;
             }
 
-            if !process_done {
-                match process.try_wait() {
-                    Ok(Some(_)) => {
-                        process_done = true;
-                        // Set these to false so we do at least one more check on both - since the
-                        // program may terminate and not have had anything printed recently.
-                        stdout_done = false;
-                        stderr_done = false;
-                    }
-                    Ok(None) => {
-                        // Child process is still running
-                        // Add a small sleep to prevent high CPU usage in the loop
-                        thread::sleep(sleep_timeout);
-                    }
-                    Err(e) => {
-                        eprintln!("Error while waiting for child process: {}", e);
-                        break;
+            let timeout = frame_deadline.saturating_duration_since(Instant::now());
+            match rx.recv_timeout(timeout) {
+                Ok(packet) => {
+                    batch.push_back(packet?);
+                    // Under the drop-oldest policy, keep draining but only hold onto the newest lines
+                    if batch.len() > MAX_LINES_PER_FRAME {
+                        batch.pop_front();
+                        dropped_lines += 1;
                     }
                 }
+                Err(RecvTimeoutError::Timeout) => break,
+                // * The channel disconnects once both reader threads have finished
+                Err(RecvTimeoutError::Disconnected) => {
+                    output_done = true;
+                    break;
+                }
             }
         }
 
-        // Wait for the threads to finish, if err, push it up the stack
-        stdout_thread.join().unwrap()?;
-        stderr_thread.join().unwrap()?;
+        if dropped_lines > 0 {
+            batch.push_front(format!("[{} lines omitted]", dropped_lines));
+        }
 
-        let status = process.wait().expect("Failed to wait on child process");
+        if !batch.is_empty() {
+            console.println_ansi_lines(batch);
+        }
 
-        match status.success() {
-            true => Ok(()),
-            false => {
-                // * 126 is a special exit code that means that the command was found but could not be executed
-                // * as per https://tldp.org/LDP/abs/html/exitcodes.html
-                // * It can be assumed that the command was found here because the External path must have been validated already
-                // * Otherwise it could be a 127 for "command not found"
-                Err(ExecutableError::FailedToExecute(status.code().unwrap_or(126) as isize).into())
+        // * The indicator is ticked even if nothing was printed, so that a silent command does not look frozen
+        console.tick_running();
+    }
+
+    console.end_running();
+
+    // Wait for the threads to finish, if err, push it up the stack
+    stdout_thread.join().unwrap()?;
+    stderr_thread.join().unwrap()?;
+
+    Ok(())
+}
+
+// Starts an executable as a background job in the shell's job table, returning its job ID and log file
+// * Background jobs write their output to a log file instead of the console, and run in their own session,
+// * so they are not tied to the shell's terminal and can keep running after being detached
+pub fn spawn_job(
+    shell: &mut Shell,
+    path: Path,
+    name: &str,
+    arguments: &[&str],
+) -> Result<(usize, PathBuf)> {
+    let log_name = format!("rush-job-{}-{}", std::process::id(), shell.jobs().next_id());
+    let (log_path, log_file) = temp_file::create(&log_name, "log")?;
+
+    let mut command = Process::new(path.path());
+    command
+        .args(arguments)
+        .stdin(Stdio::null())
+        .stdout(log_file.file().try_clone()?)
+        .stderr(log_file.into_parts().0);
+
+    if let Some(niceness) = shell.config().niceness {
+        set_niceness(&mut command, niceness);
+    }
+
+    start_new_session(&mut command);
+
+    let process = command
+        .spawn()
+        .map_err(|e| ExecutableError::FailedToSpawn(e.to_string()))?;
+
+    let command_line = std::iter::once(name)
+        .chain(arguments.iter().copied())
+        .collect::<Vec<&str>>()
+        .join(" ");
+    let id = shell
+        .jobs_mut()
+        .add(command_line, process, log_path.clone());
+
+    Ok((id, log_path))
+}
+
+// Launches a program that is completely detached from the shell, such as a GUI application
+// * Unlike jobs, detached processes are not tracked and their output is discarded
+// * The process is reaped on a separate thread so that it does not linger as a zombie after exiting
+pub fn spawn_detached(program: &str, arguments: &[&str]) -> Result<()> {
+    let mut command = Process::new(program);
+    command
+        .args(arguments)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    start_new_session(&mut command);
+
+    let mut process = command
+        .spawn()
+        .map_err(|e| ExecutableError::FailedToSpawn(format!("{}: {}", program, e)))?;
+
+    thread::spawn(move || process.wait());
+    Ok(())
+}
+
+// Makes a process start in a new session, detaching it from the shell's controlling terminal
+#[cfg(unix)]
+fn start_new_session(command: &mut Process) {
+    // SAFETY: setsid() is async-signal-safe, so it may be called between fork() and exec()
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(io::Error::last_os_error());
             }
+
+            Ok(())
+        });
+    }
+}
+
+#[cfg(windows)]
+fn start_new_session(_command: &mut Process) {}
+
+// Makes a process run with the given niceness (-20 is the highest priority, 19 is the lowest)
+// * On Linux, the IO priority of a process is derived from its niceness unless set explicitly,
+// * so raising the niceness also lowers the IO priority of heavy jobs like builds
+#[cfg(unix)]
+fn set_niceness(command: &mut Process, niceness: i32) {
+    // SAFETY: setpriority() is async-signal-safe, so it may be called between fork() and exec()
+    unsafe {
+        command.pre_exec(move || {
+            if libc::setpriority(libc::PRIO_PROCESS as _, 0, niceness) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(())
+        });
+    }
+}
+
+// * Windows uses priority classes rather than niceness, so this is not supported yet
+#[cfg(windows)]
+fn set_niceness(_command: &mut Process, _niceness: i32) {}
+
+// Runs a closure while the shell ignores Ctrl+C
+// * Without raw mode, Ctrl+C interrupts every process in the foreground, so the shell would be interrupted along with the program
+// * The handler is only changed after the program has started, since programs inherit ignored signals
+#[cfg(unix)]
+fn ignoring_interrupts<T>(run: impl FnOnce() -> T) -> T {
+    // SAFETY: signal() has no memory safety requirements, and the previous handler is put back afterward
+    let previous = unsafe { libc::signal(libc::SIGINT, libc::SIG_IGN) };
+    let result = run();
+    unsafe { libc::signal(libc::SIGINT, previous) };
+    result
+}
+
+// * Console control events are not handled yet, so Ctrl+C may still reach the shell on Windows
+#[cfg(windows)]
+fn ignoring_interrupts<T>(run: impl FnOnce() -> T) -> T {
+    run()
+}
+
+// Waits for a child process to exit, collecting the resources it used along with its exit status
+// * std::process::Child::wait() discards the rusage reported by the kernel, so wait4() is called directly instead
+// * The wall time is not known here, so it is left as zero for the caller to fill in
+#[cfg(unix)]
+fn wait_with_usage(process: &mut Child) -> Result<(ExitStatus, ResourceUsage)> {
+    let pid = process.id() as libc::pid_t;
+    let mut status = 0;
+    // SAFETY: rusage is a plain C struct, for which all-zeroes is a valid value
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+
+    loop {
+        // SAFETY: The pointers are valid for the duration of the call, and the pid belongs to an unreaped child
+        let result = unsafe { libc::wait4(pid, &mut status, 0, &mut usage) };
+        if result != -1 {
+            break;
+        }
+
+        // Retry if the wait was interrupted by a signal
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(ExecutableError::FailedToWait(error.to_string()).into());
         }
     }
+
+    let to_duration = |time: libc::timeval| {
+        Duration::from_secs(time.tv_sec as u64) + Duration::from_micros(time.tv_usec as u64)
+    };
+
+    // * Linux reports the max RSS in kilobytes, but macOS reports it in bytes
+    let max_rss = if cfg!(target_os = "macos") {
+        usage.ru_maxrss as u64 / 1024
+    } else {
+        usage.ru_maxrss as u64
+    };
+
+    let usage = ResourceUsage {
+        wall_time: Duration::ZERO,
+        user_time: to_duration(usage.ru_utime),
+        system_time: to_duration(usage.ru_stime),
+        max_rss,
+    };
+
+    Ok((ExitStatus::from_raw(status), usage))
+}
+
+// * Windows does not report per-process resource usage through the standard library, so only the exit status is collected
+#[cfg(windows)]
+fn wait_with_usage(process: &mut Child) -> Result<(ExitStatus, ResourceUsage)> {
+    let status = process
+        .wait()
+        .map_err(|e| ExecutableError::FailedToWait(e.to_string()))?;
+
+    let usage = ResourceUsage {
+        wall_time: Duration::ZERO,
+        user_time: Duration::ZERO,
+        system_time: Duration::ZERO,
+        max_rss: 0,
+    };
+
+    Ok((status, usage))
 }
@@ -0,0 +1,126 @@
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use rush_state::console::Console;
+
+use crate::errors::{BuiltinError, FilesystemError};
+use crate::file_operations::{format_size, progress_line};
+
+// How long to wait for the server to connect or send more data before giving up
+const TIMEOUT: Duration = Duration::from_secs(30);
+// The size of each chunk read from the response before it is written to the file
+const CHUNK_SIZE: usize = 64 * 1024;
+// How often the progress indicator is redrawn when the size of the download is not known
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+// Gets the name that a download is saved under when no destination is given, based on the last part of the URL
+// * URLs without a path (e.g. 'https://example.com') are saved as 'index.html', like most browsers do
+pub fn file_name_from_url(url: &str) -> String {
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+
+    url.split_once('/')
+        .and_then(|(_, path)| path.rsplit('/').next())
+        .filter(|name| !name.is_empty())
+        .unwrap_or("index.html")
+        .to_string()
+}
+
+// Downloads the contents of a URL into a file, returning the number of bytes downloaded
+// * The download can be cancelled with Ctrl+C, and the partial file is removed if it does not finish
+pub fn download_file(console: &mut Console, url: &str, destination: &Path) -> Result<u64> {
+    let response = ureq::AgentBuilder::new()
+        .timeout_connect(TIMEOUT)
+        .timeout_read(TIMEOUT)
+        .build()
+        .get(url)
+        .call()
+        .map_err(|e| BuiltinError::FailedToDownload(url.to_string(), failure_reason(e)))?;
+
+    let size = response
+        .header("Content-Length")
+        .and_then(|length| length.parse().ok());
+    let mut reader = response.into_reader();
+    let mut file = fs_err::File::create(destination)
+        .map_err(|_| FilesystemError::FailedToWriteFile(destination.to_path_buf()))?;
+
+    let result = write_response(console, url, &mut reader, &mut file, destination, size);
+    console.overwrite_line("");
+
+    if result.is_err() {
+        drop(file);
+        _ = fs_err::remove_file(destination);
+    }
+
+    result
+}
+
+// Copies the response body into the file, showing a progress bar if the size is known or a running total if not
+fn write_response(
+    console: &mut Console,
+    url: &str,
+    reader: &mut impl Read,
+    file: &mut fs_err::File,
+    destination: &Path,
+    size: Option<u64>,
+) -> Result<u64> {
+    let label = destination
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| url.to_string());
+
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut downloaded = 0;
+    let mut last_percent = None;
+    let mut last_redraw = Instant::now();
+
+    loop {
+        let count = reader
+            .read(&mut buffer)
+            .map_err(|e| BuiltinError::FailedToDownload(url.to_string(), e.to_string()))?;
+        if count == 0 {
+            break;
+        }
+
+        file.write_all(&buffer[..count])
+            .map_err(|_| FilesystemError::FailedToWriteFile(destination.to_path_buf()))?;
+        downloaded += count as u64;
+
+        if console.poll_interrupt(Duration::ZERO)? {
+            return Err(BuiltinError::Interrupted.into());
+        }
+
+        // Only redraw when the percentage actually changes, or periodically if there is no percentage to show
+        match size {
+            Some(size) if size > 0 => {
+                let percent = downloaded * 100 / size;
+                if last_percent != Some(percent) {
+                    last_percent = Some(percent);
+                    console.overwrite_line(&progress_line(&label, percent));
+                }
+            }
+            _ => {
+                if last_redraw.elapsed() >= REDRAW_INTERVAL {
+                    last_redraw = Instant::now();
+                    console.overwrite_line(&format!("{} {}", label, format_size(downloaded)));
+                }
+            }
+        }
+    }
+
+    Ok(downloaded)
+}
+
+// Describes why a request failed, without repeating the URL like ureq's own error messages do
+fn failure_reason(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, response) => format!("{} {}", code, response.status_text()),
+        ureq::Error::Transport(transport) => match transport.message() {
+            Some(message) => format!("{}: {}", transport.kind(), message),
+            None => transport.kind().to_string(),
+        },
+    }
+}
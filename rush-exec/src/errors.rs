@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use rush_state::errors::{PathError, ShellError};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -13,6 +14,10 @@ pub enum BuiltinError {
     // $ This is way too general
     #[error("Runtime error")]
     FailedToRun,
+    #[error("Interrupted by user")]
+    Interrupted,
+    #[error("Failed to download '{0}': {1}")]
+    FailedToDownload(String, String),
     #[error("Unable to read Path: {0}")]
     FailedReadingPath(PathBuf),
     #[error("Unable to read file type from path: {0}")]
@@ -21,6 +26,26 @@ pub enum BuiltinError {
     FailedReadingFileName(PathBuf),
     #[error("Unable to read dir: {0}")]
     FailedReadingDir(PathBuf),
+    #[error("File is already being sourced: {0}")]
+    AlreadySourcing(PathBuf),
+}
+
+impl BuiltinError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::InvalidArgumentCount(..) => "InvalidArgumentCount",
+            Self::InvalidArgument(..) => "InvalidArgument",
+            Self::InvalidValue(..) => "InvalidValue",
+            Self::FailedToRun => "FailedToRun",
+            Self::Interrupted => "Interrupted",
+            Self::FailedToDownload(..) => "FailedToDownload",
+            Self::FailedReadingPath(..) => "FailedReadingPath",
+            Self::FailedReadingFileType(..) => "FailedReadingFileType",
+            Self::FailedReadingFileName(..) => "FailedReadingFileName",
+            Self::FailedReadingDir(..) => "FailedReadingDir",
+            Self::AlreadySourcing(..) => "AlreadySourcing",
+        }
+    }
 }
 
 #[derive(Error, Debug)]
@@ -33,4 +58,164 @@ pub enum ExecutableError {
     FailedToParseStdout(String),
     #[error("Failed to parse executable stderr: {0}")]
     FailedToParseStderr(String),
+    #[error("Failed to wait for executable to exit: {0}")]
+    FailedToWait(String),
+    #[error("Failed to start executable: {0}")]
+    FailedToSpawn(String),
+    #[error("Interpreter could not be found in PATH: {0}")]
+    InterpreterNotFound(String),
+}
+
+impl ExecutableError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::PathNoLongerExists(..) => "PathNoLongerExists",
+            Self::FailedToExecute(..) => "FailedToExecute",
+            Self::FailedToParseStdout(..) => "FailedToParseStdout",
+            Self::FailedToParseStderr(..) => "FailedToParseStderr",
+            Self::FailedToWait(..) => "FailedToWait",
+            Self::FailedToSpawn(..) => "FailedToSpawn",
+            Self::InterpreterNotFound(..) => "InterpreterNotFound",
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum FilesystemError {
+    #[error("Path does not exist: {0}")]
+    PathNotFound(PathBuf),
+    #[error("Path already exists: {0}")]
+    AlreadyExists(PathBuf),
+    #[error("Path is a directory: {0}")]
+    IsADirectory(PathBuf),
+    #[error("Cannot copy a directory into itself: {0}")]
+    CopyIntoSelf(PathBuf),
+    #[error("Cannot copy a file onto itself: {0}")]
+    CopyOntoSelf(PathBuf),
+    #[error("Failed to copy '{0}' to '{1}'")]
+    FailedToCopy(PathBuf, PathBuf),
+    #[error("Failed to move '{0}' to '{1}'")]
+    FailedToMove(PathBuf, PathBuf),
+    #[error("Failed to read file: {0}")]
+    FailedToReadFile(PathBuf),
+    #[error("Failed to write file: {0}")]
+    FailedToWriteFile(PathBuf),
+    #[error("Unknown archive format: {0}")]
+    UnknownArchiveFormat(PathBuf),
+    #[error("Failed to create archive: {0}")]
+    FailedToCreateArchive(PathBuf),
+    #[error("Failed to extract archive: {0}")]
+    FailedToExtractArchive(PathBuf),
+    #[error("Failed to move to the trash: {0}")]
+    FailedToTrash(PathBuf),
+    #[error("No item in the trash matches: {0}")]
+    NotInTrash(String),
+    #[error("Failed to restore from the trash: {0}")]
+    FailedToRestore(PathBuf),
+    #[cfg(not(any(windows, all(unix, not(target_os = "macos")))))]
+    #[error("The trash cannot be listed or restored from on this platform")]
+    TrashUnsupported,
+    #[error("Failed to delete: {0}")]
+    FailedToDelete(PathBuf),
+    #[error("Failed to set mode '{1}' on: {0}")]
+    FailedToChangePermissions(PathBuf, String),
+    #[error("Failed to create or update file: {0}")]
+    FailedToTouch(PathBuf),
+    #[error("Failed to create directory: {0}")]
+    FailedToCreateDirectory(PathBuf),
+    #[error("Failed to read directory: {0}")]
+    FailedToReadDirectory(PathBuf),
+    #[error("Directory does not exist: {0}")]
+    DirectoryNotFound(PathBuf),
+    #[error("Directory is not empty: {0}")]
+    DirectoryNotEmpty(PathBuf),
+    #[error("Path is not a directory: {0}")]
+    NotADirectory(PathBuf),
+    #[error("Failed to create symbolic link '{0}' to '{1}'")]
+    FailedToCreateSymlink(PathBuf, PathBuf),
+    #[error("Path is not a symbolic link: {0}")]
+    NotASymlink(PathBuf),
+}
+
+impl FilesystemError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::PathNotFound(..) => "PathNotFound",
+            Self::AlreadyExists(..) => "AlreadyExists",
+            Self::IsADirectory(..) => "IsADirectory",
+            Self::CopyIntoSelf(..) => "CopyIntoSelf",
+            Self::CopyOntoSelf(..) => "CopyOntoSelf",
+            Self::FailedToCopy(..) => "FailedToCopy",
+            Self::FailedToMove(..) => "FailedToMove",
+            Self::FailedToReadFile(..) => "FailedToReadFile",
+            Self::FailedToWriteFile(..) => "FailedToWriteFile",
+            Self::UnknownArchiveFormat(..) => "UnknownArchiveFormat",
+            Self::FailedToCreateArchive(..) => "FailedToCreateArchive",
+            Self::FailedToExtractArchive(..) => "FailedToExtractArchive",
+            Self::FailedToTrash(..) => "FailedToTrash",
+            Self::NotInTrash(..) => "NotInTrash",
+            Self::FailedToRestore(..) => "FailedToRestore",
+            #[cfg(not(any(windows, all(unix, not(target_os = "macos")))))]
+            Self::TrashUnsupported => "TrashUnsupported",
+            Self::FailedToDelete(..) => "FailedToDelete",
+            Self::FailedToChangePermissions(..) => "FailedToChangePermissions",
+            Self::FailedToTouch(..) => "FailedToTouch",
+            Self::FailedToCreateDirectory(..) => "FailedToCreateDirectory",
+            Self::FailedToReadDirectory(..) => "FailedToReadDirectory",
+            Self::DirectoryNotFound(..) => "DirectoryNotFound",
+            Self::DirectoryNotEmpty(..) => "DirectoryNotEmpty",
+            Self::NotADirectory(..) => "NotADirectory",
+            Self::FailedToCreateSymlink(..) => "FailedToCreateSymlink",
+            Self::NotASymlink(..) => "NotASymlink",
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ArithmeticError {
+    #[error("Unexpected character in expression: '{0}'")]
+    UnexpectedCharacter(char),
+    #[error("Unexpected end of expression")]
+    UnexpectedEnd,
+    #[error("Invalid number: {0}")]
+    InvalidNumber(String),
+    #[error("Unknown function: {0}")]
+    UnknownFunction(String),
+    #[error("Function '{0}' takes {1} argument(s)")]
+    WrongArgumentCount(String, usize),
+    #[error("Division by zero")]
+    DivisionByZero,
+}
+
+impl ArithmeticError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::UnexpectedCharacter(..) => "UnexpectedCharacter",
+            Self::UnexpectedEnd => "UnexpectedEnd",
+            Self::InvalidNumber(..) => "InvalidNumber",
+            Self::UnknownFunction(..) => "UnknownFunction",
+            Self::WrongArgumentCount(..) => "WrongArgumentCount",
+            Self::DivisionByZero => "DivisionByZero",
+        }
+    }
+}
+
+// Finds the kind of the outermost error in the chain that comes from one of the shell's error types
+// * Errors added as plain context, or raised by other libraries, are skipped over
+pub fn error_kind(error: &anyhow::Error) -> Option<&'static str> {
+    error.chain().find_map(|cause| {
+        if let Some(error) = cause.downcast_ref::<BuiltinError>() {
+            Some(error.kind())
+        } else if let Some(error) = cause.downcast_ref::<ExecutableError>() {
+            Some(error.kind())
+        } else if let Some(error) = cause.downcast_ref::<FilesystemError>() {
+            Some(error.kind())
+        } else if let Some(error) = cause.downcast_ref::<ArithmeticError>() {
+            Some(error.kind())
+        } else if let Some(error) = cause.downcast_ref::<ShellError>() {
+            Some(error.kind())
+        } else {
+            cause.downcast_ref::<PathError>().map(PathError::kind)
+        }
+    })
 }
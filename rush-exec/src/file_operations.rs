@@ -0,0 +1,1003 @@
+use std::fs::Metadata;
+use std::io::{ErrorKind, Read, Write};
+use std::path::{Path as StdPath, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use md5::Md5;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+
+use rush_state::console::Console;
+use rush_state::glob::glob_to_regex;
+
+use crate::builtin_arguments::{ChecksumAlgorithm, EntryType};
+use crate::errors::{BuiltinError, FilesystemError};
+
+// Files at least this large get a progress indicator while they are being copied or hashed
+const PROGRESS_THRESHOLD: u64 = 8 * 1024 * 1024;
+// The size of each chunk read while copying or hashing a file
+const CHUNK_SIZE: usize = 64 * 1024;
+// How often search results are flushed to the console, so that large searches do not block the UI
+const SEARCH_FLUSH_INTERVAL: Duration = Duration::from_millis(50);
+
+// Represents the filters used when searching for files
+pub struct FindOptions {
+    // Only entries whose names match this pattern are shown
+    pub pattern: Regex,
+    // Only entries of this type are shown, if given
+    pub entry_type: Option<EntryType>,
+    // How many directories deep to search, if limited
+    pub max_depth: Option<usize>,
+}
+
+impl FindOptions {
+    // Creates a set of search filters from a glob-style name pattern
+    pub fn new(
+        pattern: &str,
+        ignore_case: bool,
+        entry_type: Option<EntryType>,
+        max_depth: Option<usize>,
+    ) -> Result<Self> {
+        Ok(Self {
+            pattern: glob_to_regex(pattern, ignore_case)?,
+            entry_type,
+            max_depth,
+        })
+    }
+}
+
+// Represents the options shared by file operations such as copying
+#[derive(Clone, Copy)]
+pub struct CopyOptions {
+    // Whether directories should be copied along with their contents
+    pub recursive: bool,
+    // Whether existing files should be overwritten without asking
+    pub force: bool,
+}
+
+// Copies a file or directory to a destination path
+// * If the user declines to overwrite an existing file, that file is skipped rather than treated as an error
+// * Recursive copies recreate symbolic links as links instead of following them, so that a link to a parent directory
+// * cannot make the copy recurse forever
+pub fn copy_path(
+    console: &mut Console,
+    source: &StdPath,
+    destination: &StdPath,
+    options: CopyOptions,
+) -> Result<()> {
+    let metadata = match options.recursive {
+        true => fs_err::symlink_metadata(source),
+        false => fs_err::metadata(source),
+    }
+    .map_err(|_| FilesystemError::PathNotFound(source.to_path_buf()))?;
+
+    if metadata.is_symlink() {
+        return copy_symlink(console, source, destination, options);
+    }
+
+    if !metadata.is_dir() {
+        return copy_file(console, source, destination, metadata.len(), options);
+    }
+
+    if !options.recursive {
+        return Err(FilesystemError::IsADirectory(source.to_path_buf()).into());
+    }
+
+    // Copying a directory into itself would recurse forever
+    if let (Ok(source), Some(Ok(parent))) = (
+        fs_err::canonicalize(source),
+        destination.parent().map(fs_err::canonicalize),
+    ) {
+        if parent.starts_with(&source) {
+            return Err(FilesystemError::CopyIntoSelf(source).into());
+        }
+    }
+
+    fs_err::create_dir_all(destination)
+        .map_err(|_| FilesystemError::FailedToCreateDirectory(destination.to_path_buf()))?;
+
+    let entries = fs_err::read_dir(source)
+        .map_err(|_| FilesystemError::FailedToReadDirectory(source.to_path_buf()))?;
+    for entry in entries {
+        let entry =
+            entry.map_err(|_| FilesystemError::FailedToReadDirectory(source.to_path_buf()))?;
+        copy_path(
+            console,
+            &entry.path(),
+            &destination.join(entry.file_name()),
+            options,
+        )?;
+    }
+
+    Ok(())
+}
+
+// Moves a file or directory to a destination path
+// * Renaming only works within a single filesystem, so moves across filesystems fall back to a copy followed by a delete
+pub fn move_path(
+    console: &mut Console,
+    source: &StdPath,
+    destination: &StdPath,
+    force: bool,
+) -> Result<()> {
+    let metadata = fs_err::symlink_metadata(source)
+        .map_err(|_| FilesystemError::PathNotFound(source.to_path_buf()))?;
+
+    if destination.exists()
+        && !force
+        && !console.confirm(&format!("Overwrite '{}'?", destination.display()))?
+    {
+        return Ok(());
+    }
+
+    let failed_to_move =
+        || FilesystemError::FailedToMove(source.to_path_buf(), destination.to_path_buf());
+
+    match std::fs::rename(source, destination) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+            // The user has already been asked about overwriting, so the copy should not ask again
+            // * A recursive copy recreates symbolic links as links, so moving a link moves the link rather than what it points to
+            let options = CopyOptions {
+                recursive: true,
+                force: true,
+            };
+
+            let existed = fs_err::symlink_metadata(destination).is_ok();
+            if let Err(e) = copy_path(console, source, destination, options) {
+                // * The source is left as it was, so a partial copy is removed rather than leaving the files in two places
+                if !existed {
+                    _ = fs_err::remove_dir_all(destination)
+                        .or_else(|_| fs_err::remove_file(destination));
+                }
+
+                return Err(e);
+            }
+
+            if metadata.is_dir() {
+                fs_err::remove_dir_all(source)
+            } else {
+                fs_err::remove_file(source)
+            }
+            .map_err(|_| FilesystemError::FailedToDelete(source.to_path_buf()).into())
+        }
+        Err(_) => Err(failed_to_move().into()),
+    }
+}
+
+// Deletes a file, or a directory along with all of its contents if recursive deletion is enabled
+// * Recursive deletions ask for confirmation first unless forced, because they cannot be undone
+pub fn delete_path(
+    console: &mut Console,
+    path: &StdPath,
+    recursive: bool,
+    force: bool,
+) -> Result<()> {
+    let metadata = fs_err::symlink_metadata(path)
+        .map_err(|_| FilesystemError::PathNotFound(path.to_path_buf()))?;
+    let failed_to_delete = || FilesystemError::FailedToDelete(path.to_path_buf());
+
+    if !metadata.is_dir() {
+        fs_err::remove_file(path).map_err(|_| failed_to_delete())?;
+        return Ok(());
+    }
+
+    if !recursive {
+        return Err(FilesystemError::IsADirectory(path.to_path_buf()).into());
+    }
+
+    if !force
+        && !console.confirm(&format!(
+            "Are you sure you want to delete '{}' and all of its contents?",
+            path.display()
+        ))?
+    {
+        return Ok(());
+    }
+
+    fs_err::remove_dir_all(path).map_err(|_| failed_to_delete().into())
+}
+
+// Moves a file or directory to the trash, so that it can be restored later
+pub fn move_to_trash(path: &StdPath, recursive: bool) -> Result<()> {
+    let metadata = fs_err::symlink_metadata(path)
+        .map_err(|_| FilesystemError::PathNotFound(path.to_path_buf()))?;
+
+    // * Directories need the same flag as when deleting them permanently, even though they can be restored
+    if metadata.is_dir() && !recursive {
+        return Err(FilesystemError::IsADirectory(path.to_path_buf()).into());
+    }
+
+    trash::delete(path).map_err(|_| FilesystemError::FailedToTrash(path.to_path_buf()).into())
+}
+
+// Gets the original path and deletion time of everything in the trash, oldest first
+#[cfg(any(windows, all(unix, not(target_os = "macos"))))]
+pub fn list_trash() -> Result<Vec<(PathBuf, SystemTime)>> {
+    let mut items: Vec<(PathBuf, SystemTime)> = trash::os_limited::list()
+        .map_err(|_| FilesystemError::FailedToReadDirectory(PathBuf::from("trash")))?
+        .into_iter()
+        .map(|item| (item.original_path(), deletion_time(&item)))
+        .collect();
+
+    items.sort_by_key(|(_, time)| *time);
+    Ok(items)
+}
+
+// Moves the most recently trashed item that was originally at the given path (or has the given name) back
+// Returns the path that the item was restored to
+#[cfg(any(windows, all(unix, not(target_os = "macos"))))]
+pub fn restore_from_trash(name: &str, original_path: &StdPath) -> Result<PathBuf> {
+    let item = trash::os_limited::list()
+        .map_err(|_| FilesystemError::FailedToReadDirectory(PathBuf::from("trash")))?
+        .into_iter()
+        .filter(|item| item.original_path() == original_path || item.name == name)
+        .max_by_key(deletion_time)
+        .ok_or_else(|| FilesystemError::NotInTrash(name.to_string()))?;
+
+    let restored_path = item.original_path();
+    if fs_err::symlink_metadata(&restored_path).is_ok() {
+        return Err(FilesystemError::AlreadyExists(restored_path).into());
+    }
+
+    trash::os_limited::restore_all([item])
+        .map_err(|_| FilesystemError::FailedToRestore(restored_path.clone()))?;
+    Ok(restored_path)
+}
+
+// * The trash on macOS can only be written to, not read from, so listing and restoring are unavailable there
+#[cfg(not(any(windows, all(unix, not(target_os = "macos")))))]
+pub fn list_trash() -> Result<Vec<(PathBuf, SystemTime)>> {
+    Err(FilesystemError::TrashUnsupported.into())
+}
+
+#[cfg(not(any(windows, all(unix, not(target_os = "macos")))))]
+pub fn restore_from_trash(_name: &str, _original_path: &StdPath) -> Result<PathBuf> {
+    Err(FilesystemError::TrashUnsupported.into())
+}
+
+// Converts the deletion time of a trashed item into a SystemTime
+#[cfg(any(windows, all(unix, not(target_os = "macos"))))]
+fn deletion_time(item: &trash::TrashItem) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(item.time_deleted.max(0) as u64)
+}
+
+// Deletes a directory, which must be empty unless recursive deletion is enabled
+// * Symbolic links to directories are not treated as directories, so that their targets are never deleted by accident
+pub fn delete_directory(
+    console: &mut Console,
+    path: &StdPath,
+    recursive: bool,
+    force: bool,
+) -> Result<()> {
+    let metadata = fs_err::symlink_metadata(path)
+        .map_err(|_| FilesystemError::DirectoryNotFound(path.to_path_buf()))?;
+
+    if !metadata.is_dir() {
+        return Err(FilesystemError::NotADirectory(path.to_path_buf()).into());
+    }
+
+    if recursive {
+        return delete_path(console, path, true, force);
+    }
+
+    let mut entries = fs_err::read_dir(path)
+        .map_err(|_| FilesystemError::FailedToReadDirectory(path.to_path_buf()))?;
+    if entries.next().is_some() {
+        return Err(FilesystemError::DirectoryNotEmpty(path.to_path_buf()).into());
+    }
+
+    fs_err::remove_dir(path).map_err(|_| FilesystemError::FailedToDelete(path.to_path_buf()).into())
+}
+
+// Recursively searches a directory for entries matching the given filters, printing them as they are found
+// Returns the number of matching entries
+// * Results are printed in batches, and the search can be cancelled with Ctrl+C
+// * Symbolic links to directories are not followed, to avoid infinite loops
+pub fn find_paths(console: &mut Console, root: &StdPath, options: &FindOptions) -> Result<usize> {
+    fs_err::read_dir(root)
+        .map_err(|_| FilesystemError::FailedToReadDirectory(root.to_path_buf()))?;
+
+    let mut pending: Vec<(PathBuf, usize)> = vec![(root.to_path_buf(), 0)];
+    let mut batch = Vec::new();
+    let mut last_flush = Instant::now();
+    let mut found = 0;
+
+    while let Some((directory, depth)) = pending.pop() {
+        // * Unreadable subdirectories (e.g. due to permissions) are skipped rather than aborting the whole search
+        let Ok(entries) = fs_err::read_dir(&directory) else {
+            continue;
+        };
+
+        let mut subdirectories = Vec::new();
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+
+            let entry_type = if file_type.is_symlink() {
+                EntryType::Symlink
+            } else if file_type.is_dir() {
+                EntryType::Directory
+            } else {
+                EntryType::File
+            };
+
+            let path = entry.path();
+            let name = entry.file_name();
+            if options.pattern.is_match(&name.to_string_lossy())
+                && options.entry_type.is_none_or(|t| t == entry_type)
+            {
+                batch.push(path.display().to_string());
+                found += 1;
+            }
+
+            if entry_type == EntryType::Directory
+                && options.max_depth.is_none_or(|max| depth + 1 < max)
+            {
+                subdirectories.push((path, depth + 1));
+            }
+        }
+
+        // Push in reverse so that directories are visited in the order they were read
+        pending.extend(subdirectories.into_iter().rev());
+
+        if last_flush.elapsed() >= SEARCH_FLUSH_INTERVAL {
+            console.println_lines(batch.drain(..));
+            last_flush = Instant::now();
+
+            if console.poll_interrupt(Duration::ZERO)? {
+                return Ok(found);
+            }
+        }
+    }
+
+    console.println_lines(batch);
+    Ok(found)
+}
+
+// Creates a file if it does not exist, or updates its modification time if it does
+// * Unlike File::create(), this never truncates an existing file
+pub fn touch_file(path: &StdPath, create_parents: bool) -> Result<()> {
+    let failed_to_touch = || FilesystemError::FailedToTouch(path.to_path_buf());
+
+    if create_parents {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs_err::create_dir_all(parent)
+                .map_err(|_| FilesystemError::FailedToCreateDirectory(parent.to_path_buf()))?;
+        }
+    }
+
+    let file = fs_err::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|_| failed_to_touch())?;
+
+    file.file()
+        .set_modified(SystemTime::now())
+        .map_err(|_| failed_to_touch().into())
+}
+
+// Creates a directory, returning every directory that was actually created
+// If create_parents is true, any missing parent directories are created as well,
+// and it is not an error for the directory to already exist
+pub fn create_directory(path: &StdPath, create_parents: bool) -> Result<Vec<PathBuf>> {
+    let failed_to_create =
+        |path: &StdPath| FilesystemError::FailedToCreateDirectory(path.to_path_buf());
+
+    if !create_parents {
+        if path.exists() {
+            return Err(FilesystemError::AlreadyExists(path.to_path_buf()).into());
+        }
+
+        fs_err::create_dir(path).map_err(|_| failed_to_create(path))?;
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    // The missing directories are collected from the bottom up, so they have to be created in reverse
+    let missing_directories: Vec<&StdPath> = path
+        .ancestors()
+        .take_while(|ancestor| !ancestor.exists())
+        .collect();
+
+    let mut created = Vec::new();
+    for directory in missing_directories.into_iter().rev() {
+        fs_err::create_dir(directory).map_err(|_| failed_to_create(directory))?;
+        created.push(directory.to_path_buf());
+    }
+
+    if !path.is_dir() {
+        return Err(failed_to_create(path).into());
+    }
+
+    Ok(created)
+}
+
+// Applies an octal or symbolic mode to a file or directory
+// * Only the read-only flag exists on Windows, so any mode without a write permission makes the file read-only
+pub fn change_permissions(path: &StdPath, mode: &str) -> Result<()> {
+    let failed_to_change =
+        || FilesystemError::FailedToChangePermissions(path.to_path_buf(), mode.to_string());
+
+    let metadata =
+        fs_err::metadata(path).map_err(|_| FilesystemError::PathNotFound(path.to_path_buf()))?;
+    let mut permissions = metadata.permissions();
+
+    #[cfg(unix)]
+    let current = {
+        use std::os::unix::fs::PermissionsExt;
+        permissions.mode() & 0o7777
+    };
+    #[cfg(not(unix))]
+    let current = if permissions.readonly() { 0o555 } else { 0o777 };
+
+    let new_mode = parse_mode(mode, current, metadata.is_dir()).ok_or_else(failed_to_change)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        permissions.set_mode(new_mode);
+    }
+    #[cfg(not(unix))]
+    permissions.set_readonly(new_mode & 0o222 == 0);
+
+    fs_err::set_permissions(path, permissions).map_err(|_| failed_to_change().into())
+}
+
+// Computes a new mode from an octal mode string (e.g. '755') or a comma-separated list of symbolic clauses (e.g. 'u+x,go-w')
+// Returns None if the mode string is invalid
+pub fn parse_mode(mode: &str, current: u32, is_directory: bool) -> Option<u32> {
+    if !mode.is_empty() && mode.chars().all(|c| c.is_digit(8)) {
+        return u32::from_str_radix(mode, 8)
+            .ok()
+            .filter(|mode| *mode <= 0o7777);
+    }
+
+    let mut result = current;
+    for clause in mode.split(',') {
+        // The 'who' part of the clause selects which permission classes are affected
+        let operators_start = clause.find(['+', '-', '='])?;
+        let (who, mut rest) = clause.split_at(operators_start);
+
+        let mut class_mask = 0;
+        for c in who.chars() {
+            class_mask |= match c {
+                'u' => 0o4700,
+                'g' => 0o2070,
+                'o' => 0o1007,
+                'a' => 0o7777,
+                _ => return None,
+            };
+        }
+
+        // * Leaving out the 'who' part is the same as specifying 'a'
+        if class_mask == 0 {
+            class_mask = 0o7777;
+        }
+
+        // Each clause can have several operations, such as 'u+r-w'
+        while let Some(operator) = rest.chars().next() {
+            let permissions_end = rest[1..]
+                .find(['+', '-', '='])
+                .map_or(rest.len(), |i| i + 1);
+            let permissions = &rest[1..permissions_end];
+            rest = &rest[permissions_end..];
+
+            let mut bits = 0;
+            for c in permissions.chars() {
+                bits |= match c {
+                    'r' => 0o444,
+                    'w' => 0o222,
+                    'x' => 0o111,
+                    // Execute only if the target is a directory or is already executable by someone
+                    'X' if is_directory || current & 0o111 != 0 => 0o111,
+                    'X' => 0,
+                    's' => 0o6000,
+                    't' => 0o1000,
+                    _ => return None,
+                };
+            }
+
+            let bits = bits & class_mask;
+            result = match operator {
+                '+' => result | bits,
+                '-' => result & !bits,
+                '=' => (result & !class_mask) | bits,
+                _ => return None,
+            };
+        }
+    }
+
+    Some(result)
+}
+
+// Gets the files at the given path, descending into directories if recursive listing is enabled
+// * Symbolic links to directories are not followed, to avoid infinite loops
+pub fn collect_files(path: &StdPath, recursive: bool) -> Result<Vec<PathBuf>> {
+    let metadata =
+        fs_err::metadata(path).map_err(|_| FilesystemError::PathNotFound(path.to_path_buf()))?;
+
+    if !metadata.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    if !recursive {
+        return Err(FilesystemError::IsADirectory(path.to_path_buf()).into());
+    }
+
+    Ok(collect_entries(path)?
+        .into_iter()
+        .filter(|entry| entry.is_file())
+        .collect())
+}
+
+// Gets every file and directory inside a directory and its subdirectories, in sorted order
+// * Symbolic links are skipped entirely, to avoid infinite loops and changes to files outside the directory
+pub fn collect_entries(directory: &StdPath) -> Result<Vec<PathBuf>> {
+    let mut found = Vec::new();
+    let mut pending = vec![directory.to_path_buf()];
+    while let Some(directory) = pending.pop() {
+        let entries = fs_err::read_dir(&directory)
+            .map_err(|_| FilesystemError::FailedToReadDirectory(directory.clone()))?;
+
+        for entry in entries.flatten() {
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => {
+                    pending.push(entry.path());
+                    found.push(entry.path());
+                }
+                Ok(file_type) if file_type.is_file() => found.push(entry.path()),
+                _ => (),
+            }
+        }
+    }
+
+    found.sort();
+    Ok(found)
+}
+
+// Copies a single file, asking before overwriting and showing progress for large files
+// * Copying a large file can take a while, so it can be cancelled with Ctrl+C
+fn copy_file(
+    console: &mut Console,
+    source: &StdPath,
+    destination: &StdPath,
+    size: u64,
+    options: CopyOptions,
+) -> Result<()> {
+    // Copying a file onto itself would empty it before it could be read
+    if let (Ok(source), Ok(destination)) = (
+        fs_err::canonicalize(source),
+        fs_err::canonicalize(destination),
+    ) {
+        if source == destination {
+            return Err(FilesystemError::CopyOntoSelf(source).into());
+        }
+    }
+
+    if destination.exists()
+        && !options.force
+        && !console.confirm(&format!("Overwrite '{}'?", destination.display()))?
+    {
+        return Ok(());
+    }
+
+    let failed_to_copy =
+        || FilesystemError::FailedToCopy(source.to_path_buf(), destination.to_path_buf());
+
+    if size < PROGRESS_THRESHOLD {
+        fs_err::copy(source, destination).map_err(|_| failed_to_copy())?;
+        return Ok(());
+    }
+
+    let reader = fs_err::File::open(source).map_err(|_| failed_to_copy())?;
+    let writer = fs_err::File::create(destination).map_err(|_| failed_to_copy())?;
+    let result = copy_chunks(console, source, reader, writer, size);
+
+    console.overwrite_line("");
+    // A partially copied file is useless, so it is removed if anything goes wrong (including cancellation)
+    if let Err(e) = result {
+        _ = fs_err::remove_file(destination);
+        return match e.downcast_ref::<BuiltinError>() {
+            Some(BuiltinError::Interrupted) => Err(e),
+            _ => Err(failed_to_copy().into()),
+        };
+    }
+
+    // * Copying in chunks does not carry over the permissions like fs::copy() does, so they are copied separately
+    let permissions = fs_err::metadata(source)
+        .map_err(|_| failed_to_copy())?
+        .permissions();
+    fs_err::set_permissions(destination, permissions).map_err(|_| failed_to_copy().into())
+}
+
+// Copies the contents of one file to another in chunks, showing the progress as a percentage of the given size
+fn copy_chunks(
+    console: &mut Console,
+    source: &StdPath,
+    mut reader: fs_err::File,
+    mut writer: fs_err::File,
+    size: u64,
+) -> Result<()> {
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut copied = 0;
+    let mut last_percent = None;
+
+    loop {
+        let count = reader.read(&mut buffer)?;
+        if count == 0 {
+            return Ok(());
+        }
+
+        writer.write_all(&buffer[..count])?;
+        copied += count as u64;
+
+        if console.poll_interrupt(Duration::ZERO)? {
+            return Err(BuiltinError::Interrupted.into());
+        }
+
+        // Only redraw when the percentage actually changes
+        let percent = copied * 100 / size;
+        if last_percent != Some(percent) {
+            last_percent = Some(percent);
+            console.overwrite_line(&progress_line(&source.display().to_string(), percent));
+        }
+    }
+}
+
+// Recreates a symbolic link at the destination, pointing to the same target as the original
+// * The target is copied as it is written, so a relative link still points to the same relative path
+fn copy_symlink(
+    console: &mut Console,
+    source: &StdPath,
+    destination: &StdPath,
+    options: CopyOptions,
+) -> Result<()> {
+    let failed_to_copy =
+        || FilesystemError::FailedToCopy(source.to_path_buf(), destination.to_path_buf());
+
+    // * The link itself is compared rather than its target, since canonicalizing the paths would follow it
+    let parent = |path: &StdPath| {
+        let parent = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty());
+        fs_err::canonicalize(parent.unwrap_or(StdPath::new("."))).ok()
+    };
+    if source.file_name() == destination.file_name()
+        && parent(source).is_some()
+        && parent(source) == parent(destination)
+    {
+        return Err(FilesystemError::CopyOntoSelf(source.to_path_buf()).into());
+    }
+
+    if fs_err::symlink_metadata(destination).is_ok() {
+        if !options.force && !console.confirm(&format!("Overwrite '{}'?", destination.display()))? {
+            return Ok(());
+        }
+
+        fs_err::remove_file(destination).map_err(|_| failed_to_copy())?;
+    }
+
+    let target = fs_err::read_link(source).map_err(|_| failed_to_copy())?;
+    symlink(destination, &target).map_err(|_| failed_to_copy().into())
+}
+
+// Computes the checksum of a file as a lowercase hexadecimal string
+// * The file is read in chunks so that large files are never loaded into memory all at once
+pub fn checksum_file(
+    console: &mut Console,
+    path: &StdPath,
+    algorithm: ChecksumAlgorithm,
+) -> Result<String> {
+    match algorithm {
+        ChecksumAlgorithm::Sha256 => hash_file::<Sha256>(console, path),
+        ChecksumAlgorithm::Md5 => hash_file::<Md5>(console, path),
+    }
+}
+
+// Feeds the contents of a file through a hash function, showing progress for large files
+// * Hashing a large file can take a while, so it can be cancelled with Ctrl+C
+fn hash_file<D: Digest>(console: &mut Console, path: &StdPath) -> Result<String> {
+    let failed_to_read = || FilesystemError::FailedToReadFile(path.to_path_buf());
+
+    let metadata =
+        fs_err::metadata(path).map_err(|_| FilesystemError::PathNotFound(path.to_path_buf()))?;
+    if metadata.is_dir() {
+        return Err(FilesystemError::IsADirectory(path.to_path_buf()).into());
+    }
+
+    let size = metadata.len();
+    let show_progress = size >= PROGRESS_THRESHOLD;
+    let mut reader = fs_err::File::open(path).map_err(|_| failed_to_read())?;
+    let mut hasher = D::new();
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut hashed = 0;
+    let mut last_percent = None;
+
+    loop {
+        let count = reader.read(&mut buffer).map_err(|_| failed_to_read())?;
+        if count == 0 {
+            break;
+        }
+
+        hasher.update(&buffer[..count]);
+        hashed += count as u64;
+
+        if show_progress {
+            if console.poll_interrupt(Duration::ZERO)? {
+                console.overwrite_line("");
+                return Err(BuiltinError::Interrupted.into());
+            }
+
+            // Only redraw when the percentage actually changes
+            let percent = hashed * 100 / size;
+            if last_percent != Some(percent) {
+                last_percent = Some(percent);
+                console.overwrite_line(&progress_line(&path.display().to_string(), percent));
+            }
+        }
+    }
+
+    if show_progress {
+        console.overwrite_line("");
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+// Creates a symbolic link at the given path that points to the target
+// * The target is stored as-is, so a relative target is relative to the directory containing the link
+pub fn create_symlink(link: &StdPath, target: &StdPath) -> Result<()> {
+    if fs_err::symlink_metadata(link).is_ok() {
+        return Err(FilesystemError::AlreadyExists(link.to_path_buf()).into());
+    }
+
+    symlink(link, target).map_err(|_| {
+        FilesystemError::FailedToCreateSymlink(link.to_path_buf(), target.to_path_buf()).into()
+    })
+}
+
+#[cfg(unix)]
+fn symlink(link: &StdPath, target: &StdPath) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+// * Windows has separate kinds of symbolic links for files and directories,
+// * and creating either one requires Developer Mode or administrator privileges
+#[cfg(windows)]
+fn symlink(link: &StdPath, target: &StdPath) -> std::io::Result<()> {
+    let resolved_target = link
+        .parent()
+        .unwrap_or_else(|| StdPath::new("."))
+        .join(target);
+
+    if resolved_target.is_dir() {
+        std::os::windows::fs::symlink_dir(target, link)
+    } else {
+        std::os::windows::fs::symlink_file(target, link)
+    }
+}
+
+// Gets the path that a symbolic link points to
+pub fn read_symlink(link: &StdPath) -> Result<PathBuf> {
+    let metadata = fs_err::symlink_metadata(link)
+        .map_err(|_| FilesystemError::PathNotFound(link.to_path_buf()))?;
+
+    if !metadata.is_symlink() {
+        return Err(FilesystemError::NotASymlink(link.to_path_buf()).into());
+    }
+
+    fs_err::read_link(link).map_err(|_| FilesystemError::NotASymlink(link.to_path_buf()).into())
+}
+
+// Formats the type and permissions of a file in the style of 'ls -l', e.g. 'drwxr-xr-x'
+#[cfg(unix)]
+pub fn permissions_string(metadata: &Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = metadata.permissions().mode();
+    let mut text = String::from(file_type_character(metadata));
+    for shift in [6, 3, 0] {
+        let bits = (mode >> shift) & 0o7;
+        text.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        text.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        text.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+    }
+
+    text
+}
+
+// * Windows only has a read-only flag, so that is all that can be shown
+#[cfg(not(unix))]
+pub fn permissions_string(metadata: &Metadata) -> String {
+    let write = if metadata.permissions().readonly() {
+        '-'
+    } else {
+        'w'
+    };
+    format!("{}r{}", file_type_character(metadata), write)
+}
+
+fn file_type_character(metadata: &Metadata) -> char {
+    if metadata.is_symlink() {
+        'l'
+    } else if metadata.is_dir() {
+        'd'
+    } else {
+        '-'
+    }
+}
+
+// Checks whether a file can be executed
+#[cfg(unix)]
+pub fn is_executable(_path: &StdPath, metadata: &Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+}
+
+// * Windows has no executable permission, so executables are recognized by their extension instead
+#[cfg(not(unix))]
+pub fn is_executable(path: &StdPath, metadata: &Metadata) -> bool {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    metadata.is_file()
+        && matches!(
+            extension.as_deref(),
+            Some("exe" | "bat" | "cmd" | "com" | "ps1")
+        )
+}
+
+// Gets the name of the user that owns a file, or their ID if the name cannot be found
+#[cfg(unix)]
+pub fn owner_name(metadata: &Metadata) -> String {
+    use std::ffi::CStr;
+    use std::os::unix::fs::MetadataExt;
+
+    let uid = metadata.uid();
+    // SAFETY: getpwuid() returns either null or a pointer to a valid passwd entry,
+    // SAFETY: whose name is copied out immediately before any other call could overwrite it
+    unsafe {
+        let entry = libc::getpwuid(uid);
+        if entry.is_null() {
+            return uid.to_string();
+        }
+
+        CStr::from_ptr((*entry).pw_name)
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+#[cfg(not(unix))]
+pub fn owner_name(_metadata: &Metadata) -> String {
+    String::from("-")
+}
+
+// Gets a readable name for the type of a file
+pub fn file_type_name(metadata: &Metadata) -> &'static str {
+    if metadata.is_symlink() {
+        "symbolic link"
+    } else if metadata.is_dir() {
+        "directory"
+    } else if metadata.is_file() {
+        "file"
+    } else {
+        "special file"
+    }
+}
+
+// Formats a number of bytes with a binary unit, e.g. '4.2 KiB'
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+// Formats a file timestamp in the local time zone
+pub fn format_time(time: SystemTime) -> String {
+    DateTime::<Local>::from(time)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+// Formats a progress bar line, e.g. "big.iso [##########          ] 50%"
+pub fn progress_line(label: &str, percent: u64) -> String {
+    const BAR_WIDTH: u64 = 20;
+    let filled = (percent.min(100) * BAR_WIDTH / 100) as usize;
+    format!(
+        "{} [{}{}] {}%",
+        label,
+        "#".repeat(filled),
+        " ".repeat(BAR_WIDTH as usize - filled),
+        percent
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_octal_modes() {
+        //given
+        let modes = ["755", "0644", "17777"];
+
+        //when
+        let parsed = modes.map(|mode| parse_mode(mode, 0o600, false));
+
+        //then
+        assert_eq!(parsed, [Some(0o755), Some(0o644), None]);
+    }
+
+    #[test]
+    fn apply_symbolic_modes_to_current_permissions() {
+        //given
+        let modes = ["u+x", "go-w", "a=r", "u+x,o-r", "u=rw-w+x", "+w"];
+
+        //when
+        let parsed = modes.map(|mode| parse_mode(mode, 0o664, false));
+
+        //then
+        assert_eq!(
+            parsed,
+            [
+                Some(0o764),
+                Some(0o644),
+                Some(0o444),
+                Some(0o760),
+                Some(0o564),
+                Some(0o666)
+            ]
+        );
+    }
+
+    #[test]
+    fn only_add_conditional_execute_to_directories_and_executables() {
+        //given
+        let mode = "a+X";
+
+        //when
+        let file = parse_mode(mode, 0o644, false);
+        let directory = parse_mode(mode, 0o644, true);
+        let executable = parse_mode(mode, 0o744, false);
+
+        //then
+        assert_eq!(file, Some(0o644));
+        assert_eq!(directory, Some(0o755));
+        assert_eq!(executable, Some(0o755));
+    }
+
+    #[test]
+    fn reject_invalid_modes() {
+        //given
+        let modes = ["", "u", "z+x", "u+q", "8"];
+
+        //when
+        let parsed = modes.map(|mode| parse_mode(mode, 0o644, false));
+
+        //then
+        assert_eq!(parsed, [None; 5]);
+    }
+}
@@ -0,0 +1,87 @@
+use std::path::Path;
+use std::sync::OnceLock;
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Span, Spans};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+
+// The color scheme used for highlighted file contents
+// TODO: Make this configurable
+const HIGHLIGHT_THEME: &str = "base16-ocean.dark";
+
+// * Loading the syntax definitions is fairly slow, so they are only loaded the first time a file is highlighted
+static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+// Highlights the lines of a file one at a time, keeping track of state that spans multiple lines (e.g. block comments)
+pub struct Highlighter {
+    lines: HighlightLines<'static>,
+}
+
+impl Highlighter {
+    // Creates a highlighter for a file based on its extension, or on its first line if that fails (e.g. for shebangs)
+    // Returns None if the file type is not recognized
+    pub fn for_file(path: &Path, first_line: &str) -> Option<Self> {
+        let syntax_set = SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines);
+        let syntax = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| syntax_set.find_syntax_by_extension(extension))
+            .or_else(|| syntax_set.find_syntax_by_first_line(first_line))?;
+
+        if syntax.name == syntax_set.find_syntax_plain_text().name {
+            return None;
+        }
+
+        let theme = THEME.get_or_init(|| {
+            let mut themes = ThemeSet::load_defaults().themes;
+            themes.remove(HIGHLIGHT_THEME).unwrap_or_default()
+        });
+
+        Some(Self {
+            lines: HighlightLines::new(syntax, theme),
+        })
+    }
+
+    // Highlights the next line of the file
+    // * The line should include its trailing newline, since some syntax definitions depend on it
+    pub fn highlight(&mut self, line: &str) -> Spans<'static> {
+        let syntax_set = SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines);
+        match self.lines.highlight_line(line, syntax_set) {
+            Ok(regions) => Spans::from(
+                regions
+                    .into_iter()
+                    .map(|(style, text)| {
+                        let text = text.trim_end_matches(['\n', '\r']).to_string();
+                        Span::styled(text, convert_style(style))
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            // If the syntax definition fails to parse the line, it is just shown without highlighting
+            Err(_) => Spans::from(line.trim_end_matches(['\n', '\r']).to_string()),
+        }
+    }
+}
+
+// Converts a syntect style into the equivalent ratatui style
+// * The background color is ignored so that highlighted text matches the rest of the output panel
+fn convert_style(style: syntect::highlighting::Style) -> Style {
+    let foreground = style.foreground;
+    let mut converted = Style::default().fg(Color::Rgb(foreground.r, foreground.g, foreground.b));
+
+    if style.font_style.contains(FontStyle::BOLD) {
+        converted = converted.add_modifier(Modifier::BOLD);
+    }
+
+    if style.font_style.contains(FontStyle::ITALIC) {
+        converted = converted.add_modifier(Modifier::ITALIC);
+    }
+
+    if style.font_style.contains(FontStyle::UNDERLINE) {
+        converted = converted.add_modifier(Modifier::UNDERLINED);
+    }
+
+    converted
+}
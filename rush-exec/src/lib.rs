@@ -1,4 +1,9 @@
-mod builtin_arguments;
+mod archives;
+pub mod arithmetic;
+pub mod builtin_arguments;
 pub mod builtins;
 pub mod commands;
-mod errors;
+mod downloads;
+pub mod errors;
+mod file_operations;
+mod highlighting;
@@ -0,0 +1,317 @@
+use std::collections::VecDeque;
+use std::io::{self, Stdout, Write};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use anyhow::Result;
+use crossterm::cursor;
+use crossterm::event::{
+    self, DisableFocusChange, DisableMouseCapture, EnableFocusChange, Event, KeyCode, KeyEvent,
+    KeyModifiers,
+};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::backend::{Backend, CrosstermBackend, TestBackend};
+use ratatui::buffer::Cell;
+use ratatui::layout::Rect;
+
+use crate::console::RAW_MODE;
+
+// Represents the terminal that the console is drawn on and reads events from
+// * Escape sequences that ratatui does not know about (such as OSC 8 hyperlinks) are written through Write
+pub trait ConsoleIo: Backend + Write {
+    // Prepares the terminal for drawing the console
+    fn enter(&mut self) -> Result<()>;
+    // Gives the terminal back to its normal state, so that another full-screen program can use it until enter() is called again
+    fn suspend(&mut self) -> Result<()>;
+    // Waits up to the given amount of time for an event, returning true if one is ready to be read
+    fn poll_event(&mut self, timeout: Duration) -> Result<bool>;
+    // Waits for the next event and returns it
+    fn read_event(&mut self) -> Result<Event>;
+}
+
+impl ConsoleIo for CrosstermBackend<Stdout> {
+    fn enter(&mut self) -> Result<()> {
+        enable_raw_mode()?;
+        // ? Is mouse capture enabled by default?
+        execute!(
+            self,
+            EnterAlternateScreen,
+            DisableMouseCapture,
+            // * Focus changes are reported so that a notification can be shown when a command finishes in the background
+            EnableFocusChange,
+            // * A bar shows that text is inserted between characters, rather than replacing the one under the cursor
+            cursor::SetCursorStyle::BlinkingBar
+        )?;
+
+        RAW_MODE.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    fn suspend(&mut self) -> Result<()> {
+        execute!(
+            self,
+            LeaveAlternateScreen,
+            DisableFocusChange,
+            cursor::SetCursorStyle::DefaultUserShape,
+            cursor::Show
+        )?;
+        disable_raw_mode()?;
+
+        RAW_MODE.store(false, Ordering::Release);
+        Ok(())
+    }
+
+    fn poll_event(&mut self, timeout: Duration) -> Result<bool> {
+        Ok(event::poll(timeout)?)
+    }
+
+    fn read_event(&mut self) -> Result<Event> {
+        Ok(event::read()?)
+    }
+}
+
+// Represents a terminal that only exists in memory, which reads events from a queue instead of the keyboard
+// * This allows the console, the builtins, and the dispatcher to be tested without a real terminal
+pub struct HeadlessBackend {
+    backend: TestBackend,
+    events: VecDeque<Event>,
+}
+
+impl HeadlessBackend {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            backend: TestBackend::new(width, height),
+            events: VecDeque::new(),
+        }
+    }
+
+    // Adds an event to the end of the queue, to be read after the events that are already in it
+    pub fn push_event(&mut self, event: Event) {
+        self.events.push_back(event);
+    }
+
+    // Adds a key press to the queue for each character of the given text, with newlines pressing Enter
+    pub fn push_text(&mut self, text: &str) {
+        for c in text.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                c => KeyCode::Char(c),
+            };
+
+            self.push_event(Event::Key(KeyEvent::new(code, KeyModifiers::NONE)));
+        }
+    }
+
+    // Adds a Ctrl+C key press to the queue, e.g. to stop a command that runs until it is interrupted
+    pub fn push_interrupt(&mut self) {
+        self.push_event(Event::Key(KeyEvent::new(
+            KeyCode::Char('c'),
+            KeyModifiers::CONTROL,
+        )));
+    }
+
+    // Gets the text of every row of the screen as it was last drawn, without trailing whitespace
+    pub fn rows(&self) -> Vec<String> {
+        let buffer = self.backend.buffer();
+        let area = buffer.area();
+        (area.top()..area.bottom())
+            .map(|y| {
+                let row: String = (area.left()..area.right())
+                    .map(|x| buffer.get(x, y).symbol.as_str())
+                    .collect();
+                row.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.backend.resize(width, height);
+    }
+}
+
+impl ConsoleIo for HeadlessBackend {
+    fn enter(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn suspend(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    // * There is nothing to wait for, since no more events can arrive while the console is waiting
+    fn poll_event(&mut self, _timeout: Duration) -> Result<bool> {
+        Ok(!self.events.is_empty())
+    }
+
+    // * Running out of events is treated like the end of a file, so that a test cannot hang waiting for input
+    fn read_event(&mut self) -> Result<Event> {
+        self.events
+            .pop_front()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof).into())
+    }
+}
+
+// * Escape sequences have no effect on the in-memory terminal, so they are discarded
+impl Write for HeadlessBackend {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Backend for HeadlessBackend {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        self.backend.draw(content)
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.backend.hide_cursor()
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.backend.show_cursor()
+    }
+
+    fn get_cursor(&mut self) -> io::Result<(u16, u16)> {
+        self.backend.get_cursor()
+    }
+
+    fn set_cursor(&mut self, x: u16, y: u16) -> io::Result<()> {
+        self.backend.set_cursor(x, y)
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.backend.clear()
+    }
+
+    fn size(&self) -> io::Result<Rect> {
+        self.backend.size()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Backend::flush(&mut self.backend)
+    }
+}
+
+// Represents either kind of terminal, so that the console does not need to be generic over its backend
+// * Builtins are stored as plain function pointers that take a Console, so the Console must have a single type
+pub enum ConsoleBackend {
+    Crossterm(CrosstermBackend<Stdout>),
+    Headless(HeadlessBackend),
+}
+
+impl ConsoleIo for ConsoleBackend {
+    fn enter(&mut self) -> Result<()> {
+        match self {
+            Self::Crossterm(backend) => backend.enter(),
+            Self::Headless(backend) => backend.enter(),
+        }
+    }
+
+    fn suspend(&mut self) -> Result<()> {
+        match self {
+            Self::Crossterm(backend) => backend.suspend(),
+            Self::Headless(backend) => backend.suspend(),
+        }
+    }
+
+    fn poll_event(&mut self, timeout: Duration) -> Result<bool> {
+        match self {
+            Self::Crossterm(backend) => backend.poll_event(timeout),
+            Self::Headless(backend) => backend.poll_event(timeout),
+        }
+    }
+
+    fn read_event(&mut self) -> Result<Event> {
+        match self {
+            Self::Crossterm(backend) => backend.read_event(),
+            Self::Headless(backend) => backend.read_event(),
+        }
+    }
+}
+
+impl Write for ConsoleBackend {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Crossterm(backend) => backend.write(buf),
+            Self::Headless(backend) => backend.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Crossterm(backend) => Write::flush(backend),
+            Self::Headless(backend) => Write::flush(backend),
+        }
+    }
+}
+
+impl Backend for ConsoleBackend {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        match self {
+            Self::Crossterm(backend) => backend.draw(content),
+            Self::Headless(backend) => backend.draw(content),
+        }
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        match self {
+            Self::Crossterm(backend) => backend.hide_cursor(),
+            Self::Headless(backend) => backend.hide_cursor(),
+        }
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        match self {
+            Self::Crossterm(backend) => backend.show_cursor(),
+            Self::Headless(backend) => backend.show_cursor(),
+        }
+    }
+
+    fn get_cursor(&mut self) -> io::Result<(u16, u16)> {
+        match self {
+            Self::Crossterm(backend) => backend.get_cursor(),
+            Self::Headless(backend) => backend.get_cursor(),
+        }
+    }
+
+    fn set_cursor(&mut self, x: u16, y: u16) -> io::Result<()> {
+        match self {
+            Self::Crossterm(backend) => backend.set_cursor(x, y),
+            Self::Headless(backend) => backend.set_cursor(x, y),
+        }
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        match self {
+            Self::Crossterm(backend) => backend.clear(),
+            Self::Headless(backend) => backend.clear(),
+        }
+    }
+
+    fn size(&self) -> io::Result<Rect> {
+        match self {
+            Self::Crossterm(backend) => backend.size(),
+            Self::Headless(backend) => backend.size(),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Crossterm(backend) => Backend::flush(backend),
+            Self::Headless(backend) => Backend::flush(backend),
+        }
+    }
+}
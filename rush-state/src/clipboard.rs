@@ -0,0 +1,144 @@
+use std::io::{stdout, Write};
+use std::process::{Command, Stdio};
+
+use anyhow::Result;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+// Copies text to the system clipboard
+// * A clipboard program is tried first, and if none of them work, the terminal is asked to do it with an OSC 52 sequence,
+// * which also works over SSH as long as the terminal supports it
+pub fn copy(text: &str) -> Result<()> {
+    for (program, args) in clipboard_programs() {
+        if copy_with_program(program, args, text) {
+            return Ok(());
+        }
+    }
+
+    copy_with_osc52(text)
+}
+
+// Gets the programs that can write to the clipboard on this platform, in the order they should be tried
+#[cfg(target_os = "macos")]
+fn clipboard_programs() -> Vec<(&'static str, &'static [&'static str])> {
+    vec![("pbcopy", &[])]
+}
+
+#[cfg(windows)]
+fn clipboard_programs() -> Vec<(&'static str, &'static [&'static str])> {
+    vec![("clip", &[])]
+}
+
+// * Which programs work depends on whether a Wayland or X11 session is running, and neither is available over SSH
+#[cfg(all(unix, not(target_os = "macos")))]
+fn clipboard_programs() -> Vec<(&'static str, &'static [&'static str])> {
+    let mut programs: Vec<(&'static str, &'static [&'static str])> = Vec::new();
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        programs.push(("wl-copy", &[]));
+    }
+
+    if std::env::var_os("DISPLAY").is_some() {
+        programs.push(("xclip", &["-selection", "clipboard"]));
+        programs.push(("xsel", &["--clipboard", "--input"]));
+    }
+
+    programs
+}
+
+// Attempts to copy text by writing it to the standard input of a clipboard program, returning true if it worked
+fn copy_with_program(program: &str, args: &[&str], text: &str) -> bool {
+    let Ok(mut child) = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return false;
+    };
+
+    // * The standard input must be closed before waiting, or the program will wait for more text forever
+    let written = match child.stdin.take() {
+        Some(mut stdin) => stdin.write_all(text.as_bytes()).is_ok(),
+        None => false,
+    };
+
+    child.wait().is_ok_and(|status| status.success()) && written
+}
+
+// Reads text from the system clipboard with a clipboard program, returning None if none of them work
+// * Reading with OSC 52 needs the terminal to reply through the standard input, so the Console does that itself
+pub fn paste() -> Option<String> {
+    paste_programs()
+        .into_iter()
+        .find_map(|(program, args)| paste_with_program(program, args))
+}
+
+// Gets the programs that can read from the clipboard on this platform, in the order they should be tried
+#[cfg(target_os = "macos")]
+fn paste_programs() -> Vec<(&'static str, &'static [&'static str])> {
+    vec![("pbpaste", &[])]
+}
+
+#[cfg(windows)]
+fn paste_programs() -> Vec<(&'static str, &'static [&'static str])> {
+    vec![("powershell", &["-NoProfile", "-Command", "Get-Clipboard"])]
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn paste_programs() -> Vec<(&'static str, &'static [&'static str])> {
+    let mut programs: Vec<(&'static str, &'static [&'static str])> = Vec::new();
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        programs.push(("wl-paste", &["--no-newline"]));
+    }
+
+    if std::env::var_os("DISPLAY").is_some() {
+        programs.push(("xclip", &["-selection", "clipboard", "-out"]));
+        programs.push(("xsel", &["--clipboard", "--output"]));
+    }
+
+    programs
+}
+
+// Attempts to read text from the standard output of a clipboard program
+fn paste_with_program(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    match output.status.success() {
+        true => String::from_utf8(output.stdout).ok(),
+        false => None,
+    }
+}
+
+// Asks the terminal to send the contents of the clipboard, which it replies with as an OSC 52 sequence
+pub fn request_osc52(writer: &mut impl Write) -> Result<()> {
+    write!(writer, "\x1b]52;c;?\x07")?;
+    writer.flush()?;
+    Ok(())
+}
+
+// Gets the text from a terminal's reply to an OSC 52 request, if it is complete and valid
+// * Both of the terminators that OSC sequences can end with (BEL and ST) are accepted
+pub fn decode_osc52_reply(reply: &str) -> Option<String> {
+    let start = reply.find("\x1b]52;")? + 5;
+    let body = reply[start..]
+        .strip_suffix('\x07')
+        .or_else(|| reply[start..].strip_suffix("\x1b\\"))?;
+    // * The selection (usually 'c') comes before the data
+    let (_, data) = body.split_once(';')?;
+    String::from_utf8(STANDARD.decode(data).ok()?).ok()
+}
+
+// Asks the terminal to copy text to the clipboard
+// $ There is no way to know whether the terminal actually supports this, so it is always assumed to have worked
+fn copy_with_osc52(text: &str) -> Result<()> {
+    let mut stdout = stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    stdout.flush()?;
+    Ok(())
+}
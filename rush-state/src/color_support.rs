@@ -0,0 +1,283 @@
+use std::env;
+use std::fmt::{Display, Formatter};
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::widgets::Widget;
+
+// The colors that the 16 basic colors are shown as by xterm, in the order of their indices
+// * Other terminals use slightly different shades, but these are close enough to find the nearest basic color
+const BASIC_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+// The levels of each component of the colors in the 6x6x6 cube of the 256-color palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+// The directories that compiled terminfo entries are looked for in, after the ones given by the environment
+#[cfg(unix)]
+const TERMINFO_DIRECTORIES: [&str; 4] = [
+    "/etc/terminfo",
+    "/lib/terminfo",
+    "/usr/share/terminfo",
+    "/usr/lib/terminfo",
+];
+
+// Represents how many colors the terminal can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorSupport {
+    // The 16 basic colors, whose exact shades are chosen by the terminal
+    Basic,
+    // The 256-color palette
+    Indexed,
+    // Any RGB color
+    TrueColor,
+}
+
+impl Display for ColorSupport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Basic => "16",
+                Self::Indexed => "256",
+                Self::TrueColor => "truecolor",
+            }
+        )
+    }
+}
+
+impl FromStr for ColorSupport {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "16" => Ok(Self::Basic),
+            "256" => Ok(Self::Indexed),
+            "truecolor" | "24bit" => Ok(Self::TrueColor),
+            _ => Err(()),
+        }
+    }
+}
+
+impl ColorSupport {
+    // Works out how many colors the terminal can show from the environment and the terminal's terminfo entry
+    // * This is only done once, since the terminal cannot change while the shell is running
+    pub fn detect() -> Self {
+        static DETECTED: OnceLock<ColorSupport> = OnceLock::new();
+        *DETECTED.get_or_init(detect_color_support)
+    }
+
+    // Converts a color to the closest one that the terminal can show
+    pub fn convert(&self, color: Color) -> Color {
+        match (self, color) {
+            (Self::TrueColor, color) => color,
+            (Self::Indexed, Color::Rgb(r, g, b)) => Color::Indexed(nearest_indexed((r, g, b))),
+            (Self::Basic, Color::Rgb(r, g, b)) => nearest_basic((r, g, b)),
+            (Self::Basic, Color::Indexed(index)) => nearest_basic(indexed_rgb(index)),
+            (_, color) => color,
+        }
+    }
+}
+
+// Represents a pass over everything drawn in a frame that converts the colors to ones the terminal can show
+// * This is drawn last, so that it catches every color (including ones printed by child processes) in one place
+pub struct ColorConversion(pub ColorSupport);
+
+impl Widget for ColorConversion {
+    fn render(self, _area: Rect, buffer: &mut Buffer) {
+        for cell in &mut buffer.content {
+            cell.fg = self.0.convert(cell.fg);
+            cell.bg = self.0.convert(cell.bg);
+        }
+    }
+}
+
+fn detect_color_support() -> ColorSupport {
+    // * COLORTERM is set by terminals that support RGB colors, since terminfo entries rarely say so
+    if matches!(env::var("COLORTERM").as_deref(), Ok("truecolor" | "24bit")) {
+        return ColorSupport::TrueColor;
+    }
+
+    let term = match env::var("TERM") {
+        Ok(term) if term != "dumb" => term,
+        // * Windows terminals do not set TERM, and all of the current ones support RGB colors
+        _ if cfg!(windows) => return ColorSupport::TrueColor,
+        _ => return ColorSupport::Basic,
+    };
+
+    match terminfo_colors(&term) {
+        Some(colors) if colors >= 1 << 24 => ColorSupport::TrueColor,
+        Some(colors) if colors >= 256 => ColorSupport::Indexed,
+        Some(_) => ColorSupport::Basic,
+        // If the terminal has no terminfo entry, its name is the only hint
+        None if term.ends_with("-direct") || term.contains("truecolor") => ColorSupport::TrueColor,
+        None if term.contains("256color") => ColorSupport::Indexed,
+        None => ColorSupport::Basic,
+    }
+}
+
+// Reads the number of colors that a terminal supports from its compiled terminfo entry
+#[cfg(unix)]
+fn terminfo_colors(term: &str) -> Option<i32> {
+    let first_letter = term.chars().next()?;
+    let mut directories: Vec<PathBuf> = Vec::new();
+    directories.extend(env::var_os("TERMINFO").map(PathBuf::from));
+    directories.extend(env::var_os("HOME").map(|home| PathBuf::from(home).join(".terminfo")));
+    if let Ok(terminfo_dirs) = env::var("TERMINFO_DIRS") {
+        directories.extend(
+            terminfo_dirs
+                .split(':')
+                .filter(|directory| !directory.is_empty())
+                .map(PathBuf::from),
+        );
+    }
+
+    directories.extend(TERMINFO_DIRECTORIES.iter().map(PathBuf::from));
+
+    // * Entries are grouped by their first letter, which macOS writes as a hex code instead
+    let entry = directories.iter().find_map(|directory| {
+        [
+            first_letter.to_string(),
+            format!("{:x}", first_letter as u32),
+        ]
+        .iter()
+        .find_map(|group| std::fs::read(directory.join(group).join(term)).ok())
+    })?;
+
+    max_colors(&entry)
+}
+
+#[cfg(windows)]
+fn terminfo_colors(_term: &str) -> Option<i32> {
+    None
+}
+
+// Gets the max_colors capability from a compiled terminfo entry, as described in term(5)
+#[cfg(unix)]
+fn max_colors(entry: &[u8]) -> Option<i32> {
+    // The index of max_colors among the numeric capabilities
+    const MAX_COLORS: usize = 13;
+
+    let header = |index: usize| {
+        let bytes = entry.get(index * 2..index * 2 + 2)?;
+        Some(i16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+    };
+
+    // * The newer format stores numbers in 32 bits, so that values like 2^24 colors fit
+    let number_size = match header(0)? {
+        0o432 => 2,
+        0o1036 => 4,
+        _ => return None,
+    };
+
+    let (names_size, bool_count, number_count) = (header(1)?, header(2)?, header(3)?);
+    if number_count <= MAX_COLORS {
+        return None;
+    }
+
+    // * The numbers start on an even byte, so a padding byte is skipped if needed
+    let numbers_start = (12 + names_size + bool_count).next_multiple_of(2);
+    let start = numbers_start + MAX_COLORS * number_size;
+    let bytes = entry.get(start..start + number_size)?;
+    let colors = match number_size {
+        2 => i16::from_le_bytes([bytes[0], bytes[1]]) as i32,
+        _ => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    };
+
+    // * A negative number means that the capability is missing
+    (colors >= 0).then_some(colors)
+}
+
+// Gets the RGB value of a color in the 256-color palette
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => BASIC_COLORS[index as usize].1,
+        16..=231 => {
+            let index = index - 16;
+            (
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+// Gets how far apart two colors are, as the squared distance between their RGB values
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let component = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    component(a.0, b.0) + component(a.1, b.1) + component(a.2, b.2)
+}
+
+// Finds the closest color in the 256-color palette, which is either in the color cube or on the grayscale ramp
+// * The 16 basic colors are skipped, since their shades are chosen by the terminal
+fn nearest_indexed(rgb: (u8, u8, u8)) -> u8 {
+    let cube_level = |component: u8| match component {
+        0..=47 => 0,
+        48..=114 => 1,
+        component => (component - 35) / 40,
+    };
+    let cube_index = 16 + 36 * cube_level(rgb.0) + 6 * cube_level(rgb.1) + cube_level(rgb.2);
+
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray_index = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+    match distance(rgb, indexed_rgb(gray_index)) < distance(rgb, indexed_rgb(cube_index)) {
+        true => gray_index,
+        false => cube_index,
+    }
+}
+
+// Finds the closest of the 16 basic colors
+fn nearest_basic(rgb: (u8, u8, u8)) -> Color {
+    BASIC_COLORS
+        .iter()
+        .min_by_key(|(_, basic)| distance(rgb, *basic))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_rgb_to_nearest_palette_color() {
+        //given
+        let color = Color::Rgb(0, 150, 255);
+
+        //when
+        let indexed = ColorSupport::Indexed.convert(color);
+        let basic = ColorSupport::Basic.convert(color);
+
+        //then
+        assert_eq!(indexed, Color::Indexed(33));
+        assert_eq!(basic, Color::Cyan);
+    }
+}
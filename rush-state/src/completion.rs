@@ -0,0 +1,186 @@
+use std::path::MAIN_SEPARATOR;
+
+use crate::shell::Shell;
+
+// Represents the possible completions for the word that ends at the cursor
+pub struct Completion {
+    // The index in the line buffer where the word being completed starts, including any opening quote
+    pub start: usize,
+    // The word being completed, without any quotes
+    pub word: String,
+    // Every way that the word could be completed, sorted and without duplicates
+    pub candidates: Vec<Candidate>,
+    // Whether the word was started with a quote, in which case the completion must stay inside the quotes
+    quoted: bool,
+}
+
+// Represents a single way to complete a word
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+pub struct Candidate {
+    // The complete word, without any quotes
+    pub word: String,
+    // How the candidate is shown when every candidate is listed (e.g. just the file name instead of the whole path)
+    pub display: String,
+    // Whether the word can be continued after it is completed, which is true for directories
+    pub is_directory: bool,
+}
+
+impl Completion {
+    // Finds the completions for the word that ends at the cursor
+    // * The first word of a command is completed as a command name (builtins, aliases, and executables in the PATH),
+    // * and every other word is completed as a file path
+    // * Only the command after the last '&&' is looked at, since that is the one being typed
+    pub fn for_line(line: &str, cursor: usize, shell: &mut Shell) -> Self {
+        let before_cursor = &line[..cursor];
+        let start = word_start(before_cursor);
+        let quoted = before_cursor[start..].starts_with(['"', '\'']);
+        let word: String = before_cursor[start..]
+            .chars()
+            .filter(|c| !matches!(c, '"' | '\''))
+            .collect();
+
+        let command = before_cursor[..start]
+            .rsplit("&&")
+            .next()
+            .unwrap_or_default();
+        let mut candidates = match command.trim().is_empty() {
+            true => command_candidates(&word, shell),
+            false => path_candidates(&word, shell),
+        };
+
+        candidates.sort();
+        candidates.dedup();
+
+        Self {
+            start,
+            word,
+            candidates,
+            quoted,
+        }
+    }
+
+    // Gets the longest prefix that every candidate starts with
+    pub fn common_prefix(&self) -> String {
+        let Some((first, rest)) = self.candidates.split_first() else {
+            return String::new();
+        };
+
+        let mut prefix = first.word.as_str();
+        for candidate in rest {
+            let length = prefix
+                .char_indices()
+                .zip(candidate.word.chars())
+                .find(|((_, a), b)| a != b)
+                .map_or(prefix.len().min(candidate.word.len()), |((index, _), _)| {
+                    index
+                });
+            prefix = &prefix[..length];
+        }
+
+        prefix.to_string()
+    }
+
+    // Gets the text that should replace the word being completed, quoting it if it contains whitespace
+    // If the word is finished, the quotes are closed and a space is added so that the next word can be typed right away
+    pub fn replacement(&self, word: &str, finished: bool) -> String {
+        let needs_quotes = self.quoted || word.contains(char::is_whitespace);
+        let mut replacement = match needs_quotes {
+            true => format!("\"{}", word),
+            false => word.to_string(),
+        };
+
+        if finished {
+            if needs_quotes {
+                replacement.push('"');
+            }
+
+            replacement.push(' ');
+        }
+
+        replacement
+    }
+}
+
+// Finds where the word that ends at the end of the text starts
+// * Whitespace inside of quotes does not separate words, so that quoted paths with spaces can be completed
+pub(crate) fn word_start(text: &str) -> usize {
+    let mut start = 0;
+    let mut quote = None;
+
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, c) if c.is_whitespace() => start = index + c.len_utf8(),
+            _ => (),
+        }
+    }
+
+    start
+}
+
+// Finds every command name that starts with the given prefix
+// * An empty prefix would match every executable in the PATH, which is not useful, so nothing is completed
+fn command_candidates(prefix: &str, shell: &mut Shell) -> Vec<Candidate> {
+    if prefix.is_empty() {
+        return Vec::new();
+    }
+
+    shell
+        .builtin_names
+        .iter()
+        .chain(shell.config.aliases.keys())
+        .chain(shell.environment.executable_names())
+        .filter(|name| name.starts_with(prefix))
+        .map(|name| Candidate {
+            word: name.clone(),
+            display: name.clone(),
+            is_directory: false,
+        })
+        .collect()
+}
+
+// Finds every file and directory whose path starts with the given prefix
+// * The directory part of the prefix is kept as it was typed (e.g. with '~'), and only the file name is completed
+// * Hidden files are only included if the file name being completed starts with a dot
+fn path_candidates(prefix: &str, shell: &Shell) -> Vec<Candidate> {
+    let (directory, name_prefix) = match prefix.rfind(['/', MAIN_SEPARATOR]) {
+        Some(index) => prefix.split_at(index + 1),
+        None => ("", prefix),
+    };
+
+    let home = shell.environment.HOME();
+    let search_directory = match directory.strip_prefix('~') {
+        Some(rest) => home.join(rest.trim_start_matches(['/', MAIN_SEPARATOR])),
+        None => shell.environment.CWD().path().join(directory),
+    };
+
+    let Ok(entries) = fs_err::read_dir(&search_directory) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(name_prefix)
+                || (name.starts_with('.') && !name_prefix.starts_with('.'))
+            {
+                return None;
+            }
+
+            // * Symbolic links to directories are completed like directories, since they can be used the same way
+            let is_directory = entry.path().is_dir();
+            let display = match is_directory {
+                true => format!("{}{}", name, MAIN_SEPARATOR),
+                false => name,
+            };
+
+            Some(Candidate {
+                word: format!("{}{}", directory, display),
+                display,
+                is_directory,
+            })
+        })
+        .collect()
+}
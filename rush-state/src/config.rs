@@ -1,74 +1,773 @@
-use fs_err::File;
-use std::io::{BufRead, BufReader};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::{Display, Formatter};
+use std::io::ErrorKind;
+use std::str::FromStr;
 
 use anyhow::Result;
+use ratatui::style::{Color, Modifier};
+use ratatui::widgets::BorderType;
 
+use crate::color_support::ColorSupport;
+use crate::config_file::{self, ConfigFile};
 use crate::errors::ShellError;
+use crate::keymap::Keymap;
+use crate::prompt::PromptTemplate;
+use crate::theme::{self, Theme, COLOR_NAMES};
 
 // Represents any settings for the shell, most of which can be configured by the user
 pub struct Configuration {
+    // The layout of the prompt panel header
+    pub prompt: PromptTemplate,
+    // The name of the color scheme used to draw the console
+    pub theme_name: String,
+    // Colors that replace the ones from the color scheme, by color name
+    pub colors: HashMap<String, Color>,
+    // The line style of the panel borders, if it should replace the one from the color scheme
+    pub border_style: Option<BorderType>,
+    // How many colors the terminal can show, if it should not be detected automatically
+    pub color_support: Option<ColorSupport>,
+    // The symbol shown before the line being typed and before each command in the output
+    pub tick_symbol: String,
+    // The text modifiers (such as bold or blink) of the tick
+    pub tick_modifiers: Modifier,
+    // Whether or not the exit code of a failed command is shown before the tick, rather than only coloring it
+    pub tick_exit_code: bool,
+    // The dim text shown in the prompt panel while nothing has been typed, if any
+    pub placeholder: Option<String>,
     // The truncation length for the prompt
     pub truncation_factor: Option<usize>,
     // How many directories to store in the back/forward history
     pub history_limit: Option<usize>,
+    // Which repeats of a command are left out of the command history
+    pub history_dedup: HistoryDedup,
+    // Whether or not commands that start with a space are left out of the command history
+    pub history_ignore_space: bool,
+    // Wildcard patterns for commands that are left out of the command history, such as ones that contain secrets
+    pub history_ignore: Vec<String>,
     // Whether or not to print out full error messages and status codes when a command fails
     pub show_errors: bool,
+    // What to do when a child process prints faster than the console can keep up with
+    pub output_overflow: OutputOverflow,
+    // What pressing Ctrl+C at the prompt does
+    pub interrupt_behavior: InterruptBehavior,
+    // The most lines of output to keep in the output panel, after which the oldest lines are dropped
+    pub scrollback_lines: Option<usize>,
+    // Whether or not links and file paths in the output are made clickable, for terminals that support OSC 8 hyperlinks
+    pub hyperlinks: bool,
+    // Whether or not to print the time and memory used by an executable after it exits
+    pub show_resource_usage: bool,
+    // How many seconds a command must run for before its duration is shown next to it in the output
+    pub show_duration_after: Option<f64>,
+    // How many seconds a command must run for before a desktop notification is shown when it finishes in the background
+    pub notify_after: Option<u64>,
+    // Whether or not delete-file moves files to the trash instead of deleting them permanently
+    pub use_trash: bool,
+    // Maps file extensions (without the leading dot) to the interpreters used to run them
+    pub interpreters: HashMap<String, String>,
+    // The default scheduling priority (niceness) to launch executables with
+    pub niceness: Option<i32>,
+    // The names of programs that draw over the whole terminal, which are given the terminal instead of having their output captured
+    pub full_screen_programs: Vec<String>,
+    // Maps user-defined alias names to the command lines they expand to
+    pub aliases: HashMap<String, String>,
+    // Maps key combinations to the Console actions they trigger
+    pub keymap: Keymap,
+    // Settings for plugins, by plugin name, which are kept as they were written in the configuration file
+    pub plugins: toml::Table,
+    // The file that the configuration was loaded from, and that persistent changes are written to
+    pub file_path: Option<String>,
+    // Problems with entries in the configuration file, which are shown once the console is ready
+    pub warnings: Vec<String>,
+    // Where the current value of each setting came from, for settings that are not at their defaults
+    sources: HashMap<String, SettingSource>,
+}
+
+// The number of lines of output that are kept by default
+const DEFAULT_SCROLLBACK_LINES: usize = 10_000;
+// The symbol of the tick by default
+const DEFAULT_TICK_SYMBOL: &str = "❯";
+// The text shown in the empty prompt by default
+const DEFAULT_PLACEHOLDER: &str = "type a command, Ctrl+D to exit";
+// The number of seconds a command must run for before its duration is shown by default
+const DEFAULT_SHOW_DURATION_AFTER: f64 = 1.0;
+// The number of seconds a command must run for before a notification is shown by default
+const DEFAULT_NOTIFY_AFTER: u64 = 10;
+
+// The programs that are given the whole terminal by default
+const DEFAULT_FULL_SCREEN_PROGRAMS: [&str; 14] = [
+    "vi", "vim", "nvim", "nano", "emacs", "less", "more", "man", "top", "htop", "btop", "tmux",
+    "screen", "ssh",
+];
+
+// The keys of the settings that hold a single value, in the order they are listed
+// * Each color in the theme can also be set with a 'color-' key, e.g. 'color-user'
+pub const SETTING_KEYS: [&str; 24] = [
+    "prompt",
+    "theme",
+    "border-style",
+    "color-support",
+    "tick-symbol",
+    "tick-style",
+    "tick-exit-code",
+    "placeholder",
+    "truncation-factor",
+    "history-limit",
+    "history-dedup",
+    "history-ignore-space",
+    "history-ignore",
+    "show-errors",
+    "output-overflow",
+    "interrupt-behavior",
+    "scrollback-lines",
+    "hyperlinks",
+    "show-resource-usage",
+    "show-duration-after",
+    "notify-after",
+    "niceness",
+    "full-screen-programs",
+    "use-trash",
+];
+
+// Represents where the current value of a setting came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingSource {
+    Default,
+    ConfigFile,
+    // Changed with the configure builtin since the shell started
+    Runtime,
+}
+
+impl Display for SettingSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Default => "default",
+                Self::ConfigFile => "config file",
+                Self::Runtime => "runtime",
+            }
+        )
+    }
+}
+
+// Represents the policy for handling child output that arrives faster than it can be displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputOverflow {
+    // Stop reading from the child until the console catches up, which eventually blocks the child
+    Block,
+    // Keep reading from the child, but only display the most recent lines of each frame
+    DropOldest,
+}
+
+impl Display for OutputOverflow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Block => "block",
+                Self::DropOldest => "drop-oldest",
+            }
+        )
+    }
+}
+
+impl FromStr for OutputOverflow {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "block" => Ok(Self::Block),
+            "drop-oldest" => Ok(Self::DropOldest),
+            _ => Err(()),
+        }
+    }
+}
+
+// Represents which repeats of a command are left out of the command history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryDedup {
+    // Keep every command, even if it was just run
+    Off,
+    // Leave out a command if it is the same as the one before it
+    Consecutive,
+    // Remove any earlier occurrence of a command, so that it only appears once as the most recent command
+    All,
+}
+
+impl Display for HistoryDedup {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Off => "off",
+                Self::Consecutive => "consecutive",
+                Self::All => "all",
+            }
+        )
+    }
+}
+
+impl FromStr for HistoryDedup {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "off" => Ok(Self::Off),
+            "consecutive" => Ok(Self::Consecutive),
+            "all" => Ok(Self::All),
+            _ => Err(()),
+        }
+    }
+}
+
+// Represents what pressing Ctrl+C at the prompt does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterruptBehavior {
+    // Cancel the line being typed
+    Clear,
+    // Cancel the line being typed, or exit if Ctrl+C was the last key pressed
+    DoublePress,
+    // Exit the shell right away
+    Exit,
+}
+
+impl Display for InterruptBehavior {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Clear => "clear",
+                Self::DoublePress => "double-press",
+                Self::Exit => "exit",
+            }
+        )
+    }
+}
+
+impl FromStr for InterruptBehavior {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "clear" => Ok(Self::Clear),
+            "double-press" => Ok(Self::DoublePress),
+            "exit" => Ok(Self::Exit),
+            _ => Err(()),
+        }
+    }
 }
 
 impl Default for Configuration {
     fn default() -> Self {
         Self {
+            prompt: PromptTemplate::default(),
+            theme_name: Theme::default().name,
+            colors: HashMap::new(),
+            border_style: None,
+            color_support: None,
+            tick_symbol: String::from(DEFAULT_TICK_SYMBOL),
+            tick_modifiers: Modifier::BOLD,
+            tick_exit_code: false,
+            placeholder: Some(String::from(DEFAULT_PLACEHOLDER)),
             truncation_factor: None,
             history_limit: None,
+            history_dedup: HistoryDedup::Off,
+            history_ignore_space: false,
+            history_ignore: Vec::new(),
             show_errors: true,
+            output_overflow: OutputOverflow::Block,
+            interrupt_behavior: InterruptBehavior::DoublePress,
+            scrollback_lines: Some(DEFAULT_SCROLLBACK_LINES),
+            hyperlinks: true,
+            show_resource_usage: false,
+            show_duration_after: Some(DEFAULT_SHOW_DURATION_AFTER),
+            notify_after: Some(DEFAULT_NOTIFY_AFTER),
+            use_trash: false,
+            interpreters: HashMap::new(),
+            niceness: None,
+            full_screen_programs: DEFAULT_FULL_SCREEN_PROGRAMS
+                .iter()
+                .map(|program| program.to_string())
+                .collect(),
+            aliases: HashMap::new(),
+            keymap: Keymap::default(),
+            plugins: toml::Table::new(),
+            file_path: None,
+            warnings: Vec::new(),
+            sources: HashMap::new(),
         }
     }
 }
 
 impl Configuration {
-    // Scans a configuration file for settings and updates the configuration accordingly
+    // Reads the settings, aliases, key bindings, and interpreters from a TOML configuration file
+    // * Entries with invalid values are skipped with a warning, so that one mistake does not discard the rest of the file
     pub fn from_file(filename: &str) -> Result<Self> {
         let filename = filename.to_string();
+        let text = fs_err::read_to_string(&filename).map_err(|e| match e.kind() {
+            ErrorKind::InvalidData => ShellError::FailedToReadConfigFile(filename.clone()),
+            _ => ShellError::FailedToOpenConfigFile(filename.clone()),
+        })?;
+        let file = ConfigFile::parse(&text).map_err(|(line, message)| {
+            ShellError::InvalidConfigFile(filename.clone(), line, message)
+        })?;
+
+        let mut config = Self {
+            file_path: Some(filename.clone()),
+            ..Default::default()
+        };
+
+        for (section, entries) in file.sections() {
+            for (name, value) in entries {
+                if let Err(e) = config.apply_entry(section, name.get_ref(), value.get_ref()) {
+                    let line = config_file::line_number(&text, value.span().start);
+                    config
+                        .warnings
+                        .push(format!("{}:{}: {}", filename, line, e));
+                }
+            }
+        }
 
-        let mut config = Self::default();
-        let file = File::open(filename.clone())
-            .map_err(|_| ShellError::FailedToOpenConfigFile(filename.clone()))?;
-        let reader = BufReader::new(file);
+        config.plugins = file.plugins;
+        Ok(config)
+    }
 
-        for line in reader.lines() {
-            let line = line.map_err(|_| ShellError::FailedToOpenConfigFile(filename.clone()))?;
-            let tokens = line.split(": ").collect::<Vec<&str>>();
-            if tokens.len() != 2 {
-                return Err(ShellError::FailedToReadConfigFile(filename).into());
+    // Applies a single entry from a section of the configuration file
+    fn apply_entry(&mut self, section: &str, name: &str, value: &toml::Value) -> Result<()> {
+        let text = config_file::value_text(value).ok_or_else(|| {
+            ShellError::InvalidConfigValue(format!("{}.{}", section, name), value.to_string())
+        })?;
+
+        match section {
+            "aliases" => self.set_alias(&format!("{}={}", name, text)),
+            "keybinds" => self.keymap.apply(&format!("{}={}", name, text)),
+            "interpreters" => self.set_interpreter(&format!("{}={}", name, text)),
+            section => {
+                let key = config_file::setting_key(section, name)
+                    .ok_or_else(|| ShellError::UnknownConfigKey(format!("{}.{}", section, name)))?;
+                self.set(&key, &text, SettingSource::ConfigFile)
             }
+        }
+    }
+
+    // Takes the warnings about entries that could not be applied when the configuration file was read
+    pub fn take_warnings(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.warnings)
+    }
 
-            let (key, value) = (tokens[0], tokens[1]);
+    // Changes a setting from its configuration file representation, e.g. 'false' to disable an optional setting
+    // Interpreter associations can be added this way too, but aliases and key bindings have their own methods
+    pub fn set(&mut self, key: &str, value: &str, source: SettingSource) -> Result<()> {
+        let invalid = || ShellError::InvalidConfigValue(key.to_string(), value.to_string());
+        let key = canonical_key(key);
 
-            // ? Should these be underscores instead of hyphens?
-            match key {
-                "truncation-factor" => {
-                    if let Ok(length) = value.parse::<usize>() {
-                        config.truncation_factor = Some(length);
-                    } else if value == "false" {
-                        config.truncation_factor = None;
-                    }
+        match key {
+            "prompt" => self.prompt = value.parse().map_err(|_| invalid())?,
+            "theme" => {
+                Theme::from_name(value).ok_or_else(invalid)?;
+                self.theme_name = value.to_string();
+            }
+            "border-style" => {
+                self.border_style = match value {
+                    "false" => None,
+                    value => Some(theme::parse_border_type(value).ok_or_else(invalid)?),
                 }
-                "history-limit" => {
-                    if let Ok(limit) = value.parse::<usize>() {
-                        config.history_limit = Some(limit);
-                    } else if value == "false" {
-                        config.history_limit = None;
-                    }
+            }
+            // * 'auto' goes back to detecting how many colors the terminal can show
+            "color-support" => {
+                self.color_support = match value {
+                    "auto" => None,
+                    value => Some(value.parse().map_err(|_| invalid())?),
                 }
-                "show-errors" => {
-                    if let Ok(show) = value.parse::<bool>() {
-                        config.show_errors = show;
+            }
+            // * Setting a color to 'false' goes back to the color from the theme
+            key if color_name(key).is_some() => {
+                let name = color_name(key).unwrap_or_default().to_string();
+                match value {
+                    "false" => {
+                        self.colors.remove(&name);
+                    }
+                    value => {
+                        self.colors
+                            .insert(name, theme::parse_color(value).ok_or_else(invalid)?);
                     }
                 }
-                _ => return Err(ShellError::FailedToReadConfigFile(filename).into()),
             }
+            // * Whitespace is not allowed, since a space is already added after the tick
+            "tick-symbol" if !value.is_empty() && !value.contains(char::is_whitespace) => {
+                self.tick_symbol = value.to_string()
+            }
+            "tick-symbol" => return Err(invalid().into()),
+            "tick-style" => {
+                self.tick_modifiers = theme::parse_modifiers(value).ok_or_else(invalid)?
+            }
+            "tick-exit-code" => self.tick_exit_code = value.parse().map_err(|_| invalid())?,
+            "placeholder" => self.placeholder = parse_optional(value).ok_or_else(invalid)?,
+            "truncation-factor" => {
+                self.truncation_factor = parse_optional(value).ok_or_else(invalid)?
+            }
+            "history-limit" => self.history_limit = parse_optional(value).ok_or_else(invalid)?,
+            "history-dedup" => self.history_dedup = value.parse().map_err(|_| invalid())?,
+            "history-ignore-space" => {
+                self.history_ignore_space = value.parse().map_err(|_| invalid())?
+            }
+            "history-ignore" => self.history_ignore = parse_list(value),
+            "show-errors" => self.show_errors = value.parse().map_err(|_| invalid())?,
+            "output-overflow" => self.output_overflow = value.parse().map_err(|_| invalid())?,
+            "interrupt-behavior" => {
+                self.interrupt_behavior = value.parse().map_err(|_| invalid())?
+            }
+            "scrollback-lines" => {
+                self.scrollback_lines = parse_optional(value).ok_or_else(invalid)?
+            }
+            "hyperlinks" => self.hyperlinks = value.parse().map_err(|_| invalid())?,
+            "show-resource-usage" => {
+                self.show_resource_usage = value.parse().map_err(|_| invalid())?
+            }
+            "show-duration-after" => {
+                self.show_duration_after = parse_optional(value)
+                    .filter(|seconds: &Option<f64>| seconds.is_none_or(|s| s >= 0.0))
+                    .ok_or_else(invalid)?
+            }
+            "notify-after" => self.notify_after = parse_optional(value).ok_or_else(invalid)?,
+            "niceness" => self.niceness = parse_optional(value).ok_or_else(invalid)?,
+            "full-screen-programs" => self.full_screen_programs = parse_list(value),
+            "use-trash" => self.use_trash = value.parse().map_err(|_| invalid())?,
+            "interpreter" => self.set_interpreter(value)?,
+            _ => return Err(ShellError::UnknownConfigKey(key.to_string()).into()),
         }
 
-        Ok(config)
+        self.sources.insert(key.to_string(), source);
+        Ok(())
+    }
+
+    // Gets the current value of a setting in its configuration file representation
+    // * Every interpreter association is included for 'interpreter', separated by commas
+    pub fn get(&self, key: &str) -> Option<String> {
+        Some(match key {
+            "prompt" => self.prompt.to_string(),
+            "theme" => self.theme_name.clone(),
+            "border-style" => theme::format_border_type(self.theme().border_type),
+            key if color_name(key).is_some() => {
+                theme::format_color(self.theme().color(color_name(key)?)?)
+            }
+            "color-support" => self
+                .color_support
+                .map_or_else(|| String::from("auto"), |support| support.to_string()),
+            "tick-symbol" => self.tick_symbol.clone(),
+            "tick-style" => theme::format_modifiers(self.tick_modifiers),
+            "tick-exit-code" => self.tick_exit_code.to_string(),
+            "placeholder" => format_optional(self.placeholder.as_ref()),
+            "truncation" | "truncation-factor" => format_optional(self.truncation_factor),
+            "history-limit" => format_optional(self.history_limit),
+            "history-dedup" => self.history_dedup.to_string(),
+            "history-ignore-space" => self.history_ignore_space.to_string(),
+            "history-ignore" => format_list(&self.history_ignore),
+            "show-errors" => self.show_errors.to_string(),
+            "output-overflow" => self.output_overflow.to_string(),
+            "interrupt-behavior" => self.interrupt_behavior.to_string(),
+            "scrollback-lines" => format_optional(self.scrollback_lines),
+            "hyperlinks" => self.hyperlinks.to_string(),
+            "show-resource-usage" => self.show_resource_usage.to_string(),
+            "show-duration-after" => format_optional(self.show_duration_after),
+            "notify-after" => format_optional(self.notify_after),
+            "niceness" => format_optional(self.niceness),
+            "full-screen-programs" => format_list(&self.full_screen_programs),
+            "use-trash" => self.use_trash.to_string(),
+            "interpreter" => self.interpreter_entries().join(", "),
+            _ => return None,
+        })
+    }
+
+    // Gets where the current value of a setting came from
+    pub fn source(&self, key: &str) -> SettingSource {
+        self.sources
+            .get(canonical_key(key))
+            .copied()
+            .unwrap_or(SettingSource::Default)
+    }
+
+    // Adds, replaces, or removes an interpreter association from a string in the form 'extension=interpreter'
+    // Setting the interpreter to 'false' removes the association
+    pub fn set_interpreter(&mut self, association: &str) -> Result<()> {
+        let (extension, interpreter) =
+            association
+                .split_once('=')
+                .ok_or(ShellError::InvalidInterpreterAssociation(
+                    association.to_string(),
+                ))?;
+        let extension = extension.trim().trim_start_matches('.');
+        let interpreter = interpreter.trim();
+
+        if extension.is_empty() || interpreter.is_empty() {
+            return Err(ShellError::InvalidInterpreterAssociation(association.to_string()).into());
+        }
+
+        if interpreter == "false" {
+            self.interpreters.remove(extension);
+        } else {
+            self.interpreters
+                .insert(extension.to_string(), interpreter.to_string());
+        }
+
+        Ok(())
+    }
+
+    // Adds or replaces an alias from a string in the form 'name=command'
+    pub fn set_alias(&mut self, definition: &str) -> Result<()> {
+        let (name, command) = definition
+            .split_once('=')
+            .ok_or(ShellError::InvalidAliasDefinition(definition.to_string()))?;
+        let name = name.trim();
+        let command = command.trim();
+
+        if name.is_empty() || name.contains(char::is_whitespace) || command.is_empty() {
+            return Err(ShellError::InvalidAliasDefinition(definition.to_string()).into());
+        }
+
+        self.aliases.insert(name.to_string(), command.to_string());
+        Ok(())
+    }
+
+    // Writes the current aliases to the configuration file, replacing any aliases that were already in it
+    pub fn save_aliases(&self) -> Result<()> {
+        let aliases = self
+            .aliases
+            .iter()
+            .map(|(name, command)| (name.clone(), command.clone()));
+        self.save_section("aliases", aliases)
+    }
+
+    // Writes the key bindings that differ from the defaults to the configuration file, replacing any that were already in it
+    pub fn save_keybindings(&self) -> Result<()> {
+        self.save_section("keybinds", self.keymap.overrides())
+    }
+
+    // Writes the current settings back to the configuration file
+    // * The file is edited in place, so comments, aliases, key bindings, plugin settings, and the order of the file are kept as they were
+    pub fn save(&mut self) -> Result<()> {
+        let file_path = self
+            .file_path
+            .as_ref()
+            .ok_or(ShellError::MissingConfigFilePath)?;
+
+        let mut document = config_file::read_document(file_path)?;
+        for key in setting_keys() {
+            let Some((section, name)) = config_file::setting_location(&key) else {
+                continue;
+            };
+
+            // Settings that are not in the file yet are only added if they were changed from their defaults
+            if !config_file::has_entry(&document, section, &name)
+                && self.source(&key) == SettingSource::Default
+            {
+                continue;
+            }
+
+            let item = match key.as_str() {
+                "history-ignore" => config_file::list_item(&self.history_ignore),
+                "full-screen-programs" => config_file::list_item(&self.full_screen_programs),
+                key => config_file::setting_item(&self.get(key).unwrap_or_default()),
+            };
+
+            config_file::set_entry(&mut document, section, &name, item);
+        }
+
+        let interpreters = self
+            .interpreters
+            .iter()
+            .map(|(extension, interpreter)| (extension.clone(), interpreter.clone()));
+        config_file::replace_section(&mut document, "interpreters", interpreters);
+        config_file::write_document(file_path, &document)?;
+
+        for source in self.sources.values_mut() {
+            *source = SettingSource::ConfigFile;
+        }
+
+        Ok(())
+    }
+
+    // Gets every interpreter association in the form 'extension=interpreter', sorted by extension
+    fn interpreter_entries(&self) -> Vec<String> {
+        let mut interpreters: Vec<String> = self
+            .interpreters
+            .iter()
+            .map(|(extension, interpreter)| format!("{}={}", extension, interpreter))
+            .collect();
+        interpreters.sort();
+        interpreters
+    }
+
+    // Replaces every entry in a section of the configuration file with the given ones
+    // * All other sections in the file are left untouched
+    fn save_section(
+        &self,
+        section: &str,
+        entries: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<()> {
+        let file_path = self
+            .file_path
+            .as_ref()
+            .ok_or(ShellError::MissingConfigFilePath)?;
+
+        let mut document = config_file::read_document(file_path)?;
+        config_file::replace_section(&mut document, section, entries);
+        config_file::write_document(file_path, &document)
+    }
+
+    // Describes each difference from an earlier configuration, e.g. 'theme: dark -> light'
+    pub fn changes_from(&self, previous: &Self) -> Vec<String> {
+        let mut changes: Vec<String> = setting_keys()
+            .filter_map(|key| {
+                let (old, new) = (previous.get(&key)?, self.get(&key)?);
+                (old != new).then(|| format!("{}: {} -> {}", key, old, new))
+            })
+            .collect();
+
+        let bindings = |config: &Self| {
+            config
+                .keymap
+                .bindings()
+                .into_iter()
+                .map(|(binding, action)| (binding.to_string(), action.to_string()))
+                .collect()
+        };
+        changes.extend(entry_changes("keybind", bindings(previous), bindings(self)));
+        changes.extend(entry_changes(
+            "alias",
+            previous.aliases.clone().into_iter().collect(),
+            self.aliases.clone().into_iter().collect(),
+        ));
+        changes.extend(entry_changes(
+            "interpreter",
+            previous.interpreters.clone().into_iter().collect(),
+            self.interpreters.clone().into_iter().collect(),
+        ));
+
+        if self.plugins != previous.plugins {
+            changes.push(String::from("plugin settings"));
+        }
+
+        changes
+    }
+
+    // Gets the color scheme to draw the console with, including any colors and border style that replace its own
+    pub fn theme(&self) -> Theme {
+        let mut theme = Theme::from_name(&self.theme_name).unwrap_or_default();
+        for (name, color) in &self.colors {
+            theme.set_color(name, *color);
+        }
+
+        if let Some(border_style) = self.border_style {
+            theme.border_type = border_style;
+        }
+
+        theme
+    }
+
+    // Gets how many colors the terminal can show, detecting it unless it has been set
+    pub fn color_support(&self) -> ColorSupport {
+        self.color_support.unwrap_or_else(ColorSupport::detect)
+    }
+
+    // Gets the interpreter associated with the extension of the given file, if there is one
+    pub fn interpreter_for(&self, file: &std::path::Path) -> Option<&String> {
+        let extension = file.extension()?.to_str()?;
+        self.interpreters.get(extension)
+    }
+
+    // Checks whether an executable is one of the programs that should be given the whole terminal
+    pub fn is_full_screen_program(&self, executable: &std::path::Path) -> bool {
+        executable
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| {
+                self.full_screen_programs
+                    .iter()
+                    .any(|program| program == name)
+            })
+    }
+}
+
+// Gets the keys of every setting that holds a single value, including one for each color in the theme
+pub fn setting_keys() -> impl Iterator<Item = String> {
+    SETTING_KEYS
+        .iter()
+        .map(|key| key.to_string())
+        .chain(COLOR_NAMES.iter().map(|name| format!("color-{}", name)))
+}
+
+// Describes each entry that was added, removed, or changed between two sets of entries, e.g. 'alias ll: unset -> ls -l'
+fn entry_changes(
+    kind: &str,
+    previous: BTreeMap<String, String>,
+    current: BTreeMap<String, String>,
+) -> Vec<String> {
+    let names: BTreeSet<&String> = previous.keys().chain(current.keys()).collect();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let (old, new) = (previous.get(name), current.get(name));
+            let describe =
+                |value: Option<&String>| value.cloned().unwrap_or_else(|| String::from("unset"));
+            (old != new)
+                .then(|| format!("{} {}: {} -> {}", kind, name, describe(old), describe(new)))
+        })
+        .collect()
+}
+
+// Gets the name of the theme color that a 'color-' key refers to, if it refers to one
+fn color_name(key: &str) -> Option<&str> {
+    key.strip_prefix("color-")
+        .filter(|name| COLOR_NAMES.contains(name))
+}
+
+// Gets the name that a setting is stored under
+// * 'truncation' is an older name for the truncation factor that is still accepted
+fn canonical_key(key: &str) -> &str {
+    match key {
+        "truncation" => "truncation-factor",
+        key => key,
+    }
+}
+
+// Parses the value of an optional setting, where 'false' means the setting is disabled
+fn parse_optional<T: FromStr>(value: &str) -> Option<Option<T>> {
+    match value {
+        "false" => Some(None),
+        value => value.parse().ok().map(Some),
+    }
+}
+
+// Formats the value of an optional setting the same way it is written in the configuration file
+fn format_optional<T: Display>(value: Option<T>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => String::from("false"),
+    }
+}
+
+// Parses the value of a setting that holds a list, where the items are separated by commas and 'false' means an empty list
+fn parse_list(value: &str) -> Vec<String> {
+    match value {
+        "false" => Vec::new(),
+        value => value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(String::from)
+            .collect(),
+    }
+}
+
+// Formats the value of a setting that holds a list the same way it is written in the configuration file
+fn format_list(items: &[String]) -> String {
+    match items.is_empty() {
+        true => String::from("false"),
+        false => items.join(", "),
     }
 }
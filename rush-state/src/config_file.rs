@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::Deserialize;
+use toml::{Spanned, Value};
+use toml_edit::{Array, DocumentMut, Item, Table};
+
+use crate::errors::ShellError;
+
+// The section and name that each setting is written under in the configuration file, along with its setting key
+// * Colors are written in their own section, under the name of the color
+const SETTING_LOCATIONS: [(&str, &str, &str); 24] = [
+    ("prompt", "template", "prompt"),
+    ("prompt", "tick-symbol", "tick-symbol"),
+    ("prompt", "tick-style", "tick-style"),
+    ("prompt", "tick-exit-code", "tick-exit-code"),
+    ("prompt", "placeholder", "placeholder"),
+    ("prompt", "truncation-factor", "truncation-factor"),
+    ("theme", "name", "theme"),
+    ("theme", "border-style", "border-style"),
+    ("theme", "color-support", "color-support"),
+    ("output", "overflow", "output-overflow"),
+    ("output", "scrollback-lines", "scrollback-lines"),
+    ("output", "hyperlinks", "hyperlinks"),
+    ("output", "show-errors", "show-errors"),
+    ("output", "show-resource-usage", "show-resource-usage"),
+    ("output", "show-duration-after", "show-duration-after"),
+    ("output", "notify-after", "notify-after"),
+    ("history", "directory-limit", "history-limit"),
+    ("history", "dedup", "history-dedup"),
+    ("history", "ignore-space", "history-ignore-space"),
+    ("history", "ignore", "history-ignore"),
+    ("shell", "interrupt-behavior", "interrupt-behavior"),
+    ("shell", "niceness", "niceness"),
+    ("shell", "use-trash", "use-trash"),
+    ("shell", "full-screen-programs", "full-screen-programs"),
+];
+
+// The section that theme colors are written in
+const COLORS_SECTION: &str = "colors";
+
+// The entries of a section of the configuration file, each of which keeps its position in the file
+pub type Section = BTreeMap<Spanned<String>, Spanned<Value>>;
+
+// Represents the configuration file as it is written, before any of its values are checked
+// * Sections that are not listed here are rejected, so that a misspelled section name is reported instead of being ignored
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    prompt: Section,
+    theme: Section,
+    colors: Section,
+    output: Section,
+    history: Section,
+    shell: Section,
+    keybinds: Section,
+    aliases: Section,
+    interpreters: Section,
+    // Settings for plugins, which are kept as they are written for each plugin to read
+    pub plugins: toml::Table,
+}
+
+impl ConfigFile {
+    // Parses the text of a configuration file, returning the line number and a description of the problem if it is not valid TOML
+    pub fn parse(text: &str) -> Result<Self, (usize, String)> {
+        toml::from_str(text).map_err(|e| {
+            let line = e.span().map_or(1, |span| line_number(text, span.start));
+            (line, e.message().to_string())
+        })
+    }
+
+    // Gets every section that holds settings, aliases, key bindings, or interpreters, by name
+    pub fn sections(&self) -> [(&'static str, &Section); 9] {
+        [
+            ("prompt", &self.prompt),
+            ("theme", &self.theme),
+            (COLORS_SECTION, &self.colors),
+            ("output", &self.output),
+            ("history", &self.history),
+            ("shell", &self.shell),
+            ("keybinds", &self.keybinds),
+            ("aliases", &self.aliases),
+            ("interpreters", &self.interpreters),
+        ]
+    }
+}
+
+// Gets the line that a position in the text is on, counting from 1
+pub fn line_number(text: &str, offset: usize) -> usize {
+    let offset = offset.min(text.len());
+    text.as_bytes()[..offset]
+        .iter()
+        .filter(|&&byte| byte == b'\n')
+        .count()
+        + 1
+}
+
+// Gets the key of the setting that is written under the given section and name
+pub fn setting_key(section: &str, name: &str) -> Option<String> {
+    if section == COLORS_SECTION {
+        return Some(format!("color-{}", name));
+    }
+
+    SETTING_LOCATIONS
+        .iter()
+        .find(|(setting_section, setting_name, _)| {
+            *setting_section == section && *setting_name == name
+        })
+        .map(|(_, _, key)| key.to_string())
+}
+
+// Gets the section and name that a setting is written under
+pub fn setting_location(key: &str) -> Option<(&'static str, String)> {
+    if let Some(color) = key.strip_prefix("color-") {
+        return Some((COLORS_SECTION, color.to_string()));
+    }
+
+    SETTING_LOCATIONS
+        .iter()
+        .find(|(_, _, setting_key)| *setting_key == key)
+        .map(|(section, name, _)| (*section, name.to_string()))
+}
+
+// Converts a value from the configuration file to the text that the configure builtin would be given for it
+// * An array becomes a comma-separated list, and an empty array means that there is nothing in the list
+pub fn value_text(value: &Value) -> Option<String> {
+    Some(match value {
+        Value::String(text) => text.clone(),
+        Value::Integer(number) => number.to_string(),
+        Value::Float(number) => number.to_string(),
+        Value::Boolean(value) => value.to_string(),
+        Value::Array(items) if items.is_empty() => String::from("false"),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| item.as_str().map(String::from))
+            .collect::<Option<Vec<_>>>()?
+            .join(", "),
+        Value::Datetime(_) | Value::Table(_) => return None,
+    })
+}
+
+// Converts the text of a setting to the value it is written as in the configuration file
+// * Booleans and numbers are written without quotes, so that the file reads like any other TOML file
+pub fn setting_item(text: &str) -> Item {
+    if let Ok(value) = text.parse::<bool>() {
+        toml_edit::value(value)
+    } else if let Ok(number) = text.parse::<i64>() {
+        toml_edit::value(number)
+    } else if let Some(number) = text.parse::<f64>().ok().filter(|number| number.is_finite()) {
+        toml_edit::value(number)
+    } else {
+        toml_edit::value(text)
+    }
+}
+
+// Converts the items of a setting that holds a list to the array it is written as in the configuration file
+pub fn list_item(items: &[String]) -> Item {
+    toml_edit::value(items.iter().collect::<Array>())
+}
+
+// Reads the configuration file so that it can be changed without losing its comments and formatting
+// * A missing file is not an error here, because it will be created when the document is written
+pub fn read_document(file_path: &str) -> Result<DocumentMut> {
+    let existing = fs_err::read_to_string(file_path).unwrap_or_default();
+    existing
+        .parse()
+        .map_err(|_| ShellError::FailedToReadConfigFile(file_path.to_string()).into())
+}
+
+pub fn write_document(file_path: &str, document: &DocumentMut) -> Result<()> {
+    fs_err::write(file_path, document.to_string())
+        .map_err(|_| ShellError::FailedToWriteConfigFile(file_path.to_string()).into())
+}
+
+// Checks whether the document already has an entry for the given section and name
+pub fn has_entry(document: &DocumentMut, section: &str, name: &str) -> bool {
+    document
+        .get(section)
+        .and_then(|section| section.get(name))
+        .is_some()
+}
+
+// Sets an entry in the document, adding its section if needed
+pub fn set_entry(document: &mut DocumentMut, section: &str, name: &str, item: Item) {
+    document[section][name] = item;
+}
+
+// Replaces the entries of a section with the given ones, removing the section if there are none
+// * Entries that are kept are updated where they are, so that any comments next to them stay in place
+pub fn replace_section(
+    document: &mut DocumentMut,
+    section: &str,
+    entries: impl IntoIterator<Item = (String, String)>,
+) {
+    let entries: BTreeMap<String, String> = entries.into_iter().collect();
+    if entries.is_empty() {
+        document.remove(section);
+        return;
+    }
+
+    let item = document
+        .entry(section)
+        .or_insert_with(|| Item::Table(Table::new()));
+    let Some(table) = item.as_table_like_mut() else {
+        return;
+    };
+
+    let stale: Vec<String> = table
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .filter(|name| !entries.contains_key(name))
+        .collect();
+    for name in stale {
+        table.remove(&name);
+    }
+
+    for (name, value) in entries {
+        table.insert(&name, toml_edit::value(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_line_of_invalid_section() {
+        //given
+        let text = "[prompt]\ntemplate = \"{cwd}\"\n\n[promtp]\ntick-symbol = \">\"\n";
+
+        //when
+        let result = ConfigFile::parse(text);
+
+        //then
+        let (line, message) = result.unwrap_err();
+        assert_eq!(line, 4);
+        assert!(message.contains("promtp"));
+    }
+}
@@ -0,0 +1,56 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+// Watches the configuration file for changes made outside of the shell, such as by a text editor
+// * The directory is watched rather than the file itself, because many editors save by replacing the file,
+// * which would end a watch on the old file
+pub struct ConfigWatcher {
+    // * The watcher stops when it is dropped, so it is kept even though it is never used directly
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    file_name: PathBuf,
+}
+
+impl ConfigWatcher {
+    pub fn new(file_path: &str) -> notify::Result<Self> {
+        let path = Path::new(file_path);
+        let directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            _ = sender.send(event);
+        })?;
+        watcher.watch(directory, RecursiveMode::NonRecursive)?;
+
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            file_name: path.file_name().map(PathBuf::from).unwrap_or_default(),
+        })
+    }
+
+    // Checks whether the configuration file has been written, created, or replaced since this was last called
+    // * Saving a file usually produces several events, so all of the waiting events are handled together
+    pub fn poll(&self) -> bool {
+        let mut changed = false;
+        for event in self.events.try_iter().flatten() {
+            if matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) && event
+                .paths
+                .iter()
+                .any(|path| path.file_name() == Some(self.file_name.as_os_str()))
+            {
+                changed = true;
+            }
+        }
+
+        changed
+    }
+}
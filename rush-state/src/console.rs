@@ -1,23 +1,77 @@
+use std::collections::VecDeque;
 use std::fmt::Debug;
-use std::io::{stdout, Stdout};
+use std::io::{stdout, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::process::Command as Process;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
+use ansi_to_tui::IntoText;
 use anyhow::Result;
 use bitflags::bitflags;
 use crossterm::cursor;
-use crossterm::event::{self, DisableMouseCapture, Event, KeyCode, KeyModifiers};
-use crossterm::execute;
-use crossterm::terminal::{
-    disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen,
-};
-use ratatui::backend::CrosstermBackend;
+use crossterm::event::{DisableFocusChange, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{disable_raw_mode, Clear, ClearType, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use ratatui::backend::{Backend, CrosstermBackend};
 use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Span, Spans, Text};
 use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 use ratatui::{Frame, Terminal};
-
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::backend::{ConsoleBackend, ConsoleIo, HeadlessBackend};
+use crate::clipboard;
+use crate::color_support::{ColorConversion, ColorSupport};
+use crate::completion::Completion;
+use crate::config::{self, Configuration, InterruptBehavior};
+use crate::keymap::{ConsoleAction, KeyAction, KeyBinding, Keymap};
+use crate::line_editor::LineEditor;
+use crate::links::{self, ExplicitLink, VisibleLink};
+use crate::log_follower::LogFollower;
+use crate::notification;
+use crate::prompt::{self, GitStatusCache, PromptSegment};
 use crate::shell::Shell;
+use crate::signature::{CommandSignature, ExpectedArgument};
+use crate::temp_file;
+use crate::theme::Theme;
+
+// How often to check on work happening in the background (such as computing the Git status for the prompt), while waiting for input
+const BACKGROUND_POLL_INTERVAL: Duration = Duration::from_millis(50);
+// How often to check the log of the job shown in the job pane for new output
+const JOB_PANE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// The frames of the spinner shown in the prompt panel title while a command is running
+const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+// How long each frame of the spinner is shown for
+const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+
+// The number of links printed by child processes that are remembered, so that they stay clickable
+const EXPLICIT_LINK_LIMIT: usize = 256;
+// The keys that are used to choose a link while link hints are shown, in the order the links are labeled
+const LINK_HINT_KEYS: &str = "asdfghjklqwertyuiopzxcvbnm";
+// The marker shown in the corner of the output panel when there is output below the part that has been scrolled to
+const NEW_OUTPUT_MARKER: &str = " new output ↓ ";
+
+// The shortest time between two frames, which limits the console to about 60 frames per second
+// * Changes made sooner than this after the last frame are drawn together in the next one
+const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+// The number of events that are kept for the event log in the debug panel
+const EVENT_LOG_SIZE: usize = 200;
+
+// How long to wait for the terminal to reply with the contents of the clipboard
+const OSC52_REPLY_TIMEOUT: Duration = Duration::from_millis(200);
+
+// The editor used to edit the line buffer when neither VISUAL nor EDITOR is set
+#[cfg(unix)]
+const DEFAULT_EDITOR: &str = "vi";
+#[cfg(windows)]
+const DEFAULT_EDITOR: &str = "notepad";
 
 // Macros for printing to the TUI console
 #[macro_export]
@@ -51,11 +105,298 @@ enum ReplAction {
     Ignore,
 }
 
-// Switch between "BACKSPACE" and "DELETE" keypresses for ConsoleData.remove_char()
-#[derive(PartialEq)]
-enum RemoveMode {
-    Backspace,
-    Delete,
+// Represents how the text in a column should be aligned by Console.println_columns()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnAlignment {
+    Left,
+    Right,
+}
+
+// Pads rows of text so that each column is as wide as its widest cell
+// * Columns without a specified alignment are left-aligned, and the last column is never padded
+pub fn format_columns(rows: &[Vec<String>], alignments: &[ColumnAlignment]) -> Vec<String> {
+    let column_count = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut widths = vec![0; column_count];
+    for row in rows {
+        for (index, cell) in row.iter().enumerate() {
+            widths[index] = widths[index].max(cell.chars().count());
+        }
+    }
+
+    rows.iter()
+        .map(|row| {
+            let mut line = String::new();
+            for (index, cell) in row.iter().enumerate() {
+                let padding = widths[index] - cell.chars().count();
+                let is_last = index == row.len() - 1;
+
+                if index > 0 {
+                    line.push(' ');
+                }
+
+                match alignments.get(index) {
+                    Some(ColumnAlignment::Right) => {
+                        line.push_str(&" ".repeat(padding));
+                        line.push_str(cell);
+                    }
+                    _ if is_last => line.push_str(cell),
+                    _ => {
+                        line.push_str(cell);
+                        line.push_str(&" ".repeat(padding));
+                    }
+                }
+            }
+
+            line
+        })
+        .collect()
+}
+
+// Splits the terminal into the areas for the output panel and the prompt panel
+fn split_frame(area: Rect) -> (Rect, Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(85), Constraint::Percentage(15)])
+        .split(area);
+    (chunks[0], chunks[1])
+}
+
+// Splits a line into rows that are each at most the given number of columns wide,
+// and finds the row and column where the cursor goes, given the number of columns before it
+// * This is done here instead of by the Paragraph, which wraps at word boundaries, so that the cursor position is known exactly
+// * Wide characters (e.g. CJK characters and most emoji) are never split across rows, so a row may end early
+fn wrap_line(
+    line: &Spans,
+    width: usize,
+    cursor_offset: usize,
+) -> (Vec<Spans<'static>>, (usize, usize)) {
+    let mut rows = vec![Spans::default()];
+    let mut row_width = 0;
+    let mut line_width = 0;
+    let mut cursor = None;
+
+    for span in &line.0 {
+        let mut content = String::new();
+        for grapheme in span.content.graphemes(true) {
+            let grapheme_width = grapheme.width();
+            if row_width + grapheme_width > width && row_width > 0 {
+                let row = rows.last_mut().unwrap();
+                row.0
+                    .push(Span::styled(std::mem::take(&mut content), span.style));
+                rows.push(Spans::default());
+                row_width = 0;
+            }
+
+            if line_width == cursor_offset && cursor.is_none() {
+                cursor = Some((rows.len() - 1, row_width));
+            }
+
+            content.push_str(grapheme);
+            row_width += grapheme_width;
+            line_width += grapheme_width;
+        }
+
+        if !content.is_empty() {
+            rows.last_mut()
+                .unwrap()
+                .0
+                .push(Span::styled(content, span.style));
+        }
+    }
+
+    // If the cursor is after the last character, it goes on the next row when the last row is full
+    let cursor = cursor.unwrap_or(match row_width >= width {
+        true => (rows.len(), 0),
+        false => (rows.len() - 1, row_width),
+    });
+
+    (rows, cursor)
+}
+
+// Formats the usage of a command like clap does, e.g. 'ls [OPTIONS] [PATH]', highlighting the argument that it expects next
+// * The values that the expected argument accepts, or the options if one is being typed, are listed after the usage
+fn argument_hint(
+    name: &str,
+    signature: &CommandSignature,
+    arguments: &[&str],
+    theme: &Theme,
+) -> Spans<'static> {
+    let expected = signature.expected_argument(arguments);
+    let (ExpectedArgument::OptionValue(command, _)
+    | ExpectedArgument::Options(command)
+    | ExpectedArgument::Positional(command, _)
+    | ExpectedArgument::Nothing(command)) = expected;
+
+    let style = Style::default().fg(theme.pending);
+    let highlighted = Style::default()
+        .fg(theme.title)
+        .add_modifier(Modifier::BOLD);
+    let mut usage = match std::ptr::eq(command, signature) {
+        true => name.to_string(),
+        false => format!("{} {}", name, command.name),
+    };
+
+    let mut spans = Vec::new();
+    let mut push = |text: String, is_expected: bool| {
+        usage.push(' ');
+        spans.push(Span::styled(std::mem::take(&mut usage), style));
+        match is_expected {
+            true => spans.push(Span::styled(text, highlighted)),
+            false => usage.push_str(&text),
+        }
+    };
+
+    match expected {
+        ExpectedArgument::OptionValue(_, option) => push(option.usage(), true),
+        ExpectedArgument::Options(_) => push(String::from("[OPTIONS]"), true),
+        _ if !command.options.is_empty() => push(String::from("[OPTIONS]"), false),
+        _ => (),
+    }
+
+    for index in 0..command.positional_count() {
+        let is_expected = expected == ExpectedArgument::Positional(command, index);
+        push(command.positional_usage(index), is_expected);
+    }
+
+    let details = match expected {
+        ExpectedArgument::OptionValue(_, option) => option.possible_values.join(", "),
+        ExpectedArgument::Options(_) => {
+            let options: Vec<_> = command.options.iter().map(|o| o.usage()).collect();
+            options.join(", ")
+        }
+        ExpectedArgument::Positional(_, index) => {
+            let has_subcommands = !command.subcommands.is_empty();
+            match index.checked_sub(usize::from(has_subcommands)) {
+                Some(index) => command.positionals[index].possible_values.join(", "),
+                None => String::new(),
+            }
+        }
+        ExpectedArgument::Nothing(_) => String::from("no more arguments"),
+    };
+
+    if !details.is_empty() {
+        usage.push_str(&format!("  ({})", details));
+    }
+
+    spans.push(Span::styled(usage, style));
+    Spans::from(spans)
+}
+
+// Creates a tick in the configured symbol and style, with the exit code of the last command before it if that is enabled
+// * The exit code is only shown once the command has finished, and only if it failed
+fn tick(shell: &Shell, color: Color, finished: bool) -> Span<'static> {
+    let config = shell.config();
+    let text = match finished && config.tick_exit_code && !shell.success() {
+        true => format!("{} {} ", shell.exit_code(), config.tick_symbol),
+        false => format!("{} ", config.tick_symbol),
+    };
+
+    Span::styled(
+        text,
+        Style::default()
+            .fg(color)
+            .add_modifier(config.tick_modifiers),
+    )
+}
+
+// Formats how long a command took, e.g. '1.24s' or '3m 07s'
+fn format_duration(duration: Duration) -> String {
+    match duration.as_secs() {
+        0..=59 => format!("{:.2}s", duration.as_secs_f64()),
+        _ => format_elapsed(duration),
+    }
+}
+
+// Formats a duration for the running indicator, e.g. '4.2s' or '3m 07s'
+fn format_elapsed(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    match seconds {
+        0..=59 => format!("{:.1}s", elapsed.as_secs_f64()),
+        _ => format!("{}m {:02}s", seconds / 60, seconds % 60),
+    }
+}
+
+// Escapes an argument so that the shell reads it as a single argument, exactly as it is written
+// * Every character that could mean something to the parser (quotes, '$', '&', whitespace, etc.) is preceded by a backslash,
+// * since a link can come from a child process and must never be able to end the argument or start another command
+fn escape_argument(argument: &str) -> String {
+    let mut escaped = String::with_capacity(argument.len());
+    for c in argument.chars() {
+        if !c.is_ascii_alphanumeric() && !matches!(c, '-' | '_' | '.' | '/' | ':') {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+// Describes a number of lines, e.g. '1 line' or '3 lines'
+fn line_count(count: usize) -> String {
+    match count {
+        1 => String::from("1 line"),
+        _ => format!("{} lines", count),
+    }
+}
+
+// Gets the text of a line of output without any styling
+fn line_text(line: &Spans) -> String {
+    line.0.iter().map(|span| span.content.as_ref()).collect()
+}
+
+// Applies a style on top of every occurrence of a query in a line, regardless of case
+// * Only ASCII letters are matched regardless of case, so that the positions of the matches are the same in the original text
+fn highlight_matches<'a>(line: &Spans<'a>, query: &str, style: Style) -> Spans<'a> {
+    let text = line_text(line).to_ascii_lowercase();
+    let ranges: Vec<(usize, usize)> = text
+        .match_indices(&query.to_ascii_lowercase())
+        .map(|(start, matched)| (start, start + matched.len()))
+        .collect();
+
+    if ranges.is_empty() {
+        return line.clone();
+    }
+
+    let mut spans = Vec::new();
+    let mut span_start = 0;
+    for span in &line.0 {
+        let content = span.content.as_ref();
+        let span_end = span_start + content.len();
+        let mut position = span_start;
+
+        // * A match can be split across spans, in which case each part of it is highlighted separately
+        for &(start, end) in &ranges {
+            let (start, end) = (start.max(position), end.min(span_end));
+            if start >= end {
+                continue;
+            }
+
+            if start > position {
+                spans.push(Span::styled(
+                    content[position - span_start..start - span_start].to_string(),
+                    span.style,
+                ));
+            }
+
+            spans.push(Span::styled(
+                content[start - span_start..end - span_start].to_string(),
+                span.style.patch(style),
+            ));
+            position = end;
+        }
+
+        if position < span_end {
+            spans.push(Span::styled(
+                content[position - span_start..].to_string(),
+                span.style,
+            ));
+        }
+
+        span_start = span_end;
+    }
+
+    Spans::from(spans)
 }
 
 // Represents a variety of switchable modes for clearing the TUI console/frame
@@ -67,6 +408,62 @@ bitflags! {
     }
 }
 
+// Represents how the user chose to continue when paged output is paused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PagerAction {
+    NextPage,
+    NextLine,
+    Quit,
+}
+
+// Represents one of the views of the debug panel, which F12 cycles through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DebugSection {
+    // The state of the console itself, such as the line buffer and scroll position
+    Console,
+    // The shell variables
+    Environment,
+    // The current value of every setting
+    Configuration,
+    // The most recent error, along with everything that caused it
+    LastError,
+    // Recent key bindings, resizes and commands, newest first
+    Events,
+}
+
+impl DebugSection {
+    const ALL: [Self; 5] = [
+        Self::Console,
+        Self::Environment,
+        Self::Configuration,
+        Self::LastError,
+        Self::Events,
+    ];
+
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Console => "Console",
+            Self::Environment => "Environment",
+            Self::Configuration => "Configuration",
+            Self::LastError => "Last Error",
+            Self::Events => "Events",
+        }
+    }
+
+    // Gets the position of the section among all of the sections, starting at 0
+    fn index(&self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|section| section == self)
+            .unwrap()
+    }
+
+    // Gets the section that comes after this one, or None if this is the last one
+    fn next(&self) -> Option<Self> {
+        Self::ALL.get(self.index() + 1).copied()
+    }
+}
+
 // Represents either a "history up" or "history down" keypress (arrow keys)
 #[derive(PartialEq)]
 enum HistoryDirection {
@@ -76,7 +473,7 @@ enum HistoryDirection {
 
 // Represents the TUI console
 pub struct Console<'a> {
-    terminal: Terminal<CrosstermBackend<Stdout>>,
+    terminal: Terminal<ConsoleBackend>,
     data: ConsoleData<'a>,
 }
 
@@ -99,23 +496,241 @@ struct ConsoleData<'a> {
     // An index to the Span of the tick next to the most recently executed command
     // Used to recolor the tick based on the success of the command
     // * If the tick index is None, then no command has been executed yet
+    // * Like output positions, this counts lines that have been dropped from the scrollback
     success_tick_index: Option<usize>,
-    // The line buffer for the prompt panel
-    line_buffer: String,
-    // The framebuffer for the output panel
-    output_buffer: Text<'a>,
+    // The line being edited in the prompt panel
+    editor: LineEditor,
+    // The framebuffer for the output panel, which only keeps the most recent lines once the scrollback limit is reached
+    output_buffer: VecDeque<Spans<'a>>,
+    // How many times the output buffer has been changed, so that the output panel can tell when it needs to be rebuilt
+    output_revision: u64,
+    // The output panel as it was built for the last frame, along with what it was built from
+    output_cache: Option<(OutputWindow, Paragraph<'a>)>,
+    // The number of lines that have been dropped from the start of the output buffer
+    dropped_lines: usize,
+    // The most lines that the output buffer can hold, if it is limited
+    scrollback_limit: Option<usize>,
     // The framebuffer for the debug panel
     debug_buffer: Text<'a>,
-    // The index of the cursor in the line buffer
-    cursor_index: usize,
     // If the line buffer can autocomplete to a command from the history, this stores the characters that will be added if the user presses TAB
     autocomplete_buffer: Option<String>,
+    // The completions for the word under the cursor, while the user is choosing between them
+    completion_menu: Option<CompletionMenu>,
+    // The usage of the builtin being typed, with the argument that it expects next highlighted
+    argument_hint: Option<Spans<'static>>,
+    // The output of each command that has been submitted, oldest first
+    sections: VecDeque<OutputSection<'a>>,
     // Information to be stored while the user is looking through history
     history: Option<History>,
-    // The number of lines that have been scrolled down in the output panel
-    scroll: usize,
-    // Whether or not to show the debug panel
-    debug_mode: bool,
+    // Information to be stored while the user is searching backwards through history
+    history_search: Option<HistorySearch>,
+    // Information to be stored while the user is searching through the output panel
+    output_search: Option<OutputSearch>,
+    // Information to be stored while the user is selecting lines in the output panel to copy
+    output_selection: Option<OutputSelection>,
+    // Whether or not the visible links are labeled so that the user can choose one to open
+    link_hints: bool,
+    // The links that were shown in the output panel when it was last drawn
+    visible_links: Vec<VisibleLink>,
+    // The most recent links printed by child processes with OSC 8 sequences, oldest first
+    explicit_links: VecDeque<ExplicitLink>,
+    // Whether or not links are made clickable in the terminal
+    hyperlinks: bool,
+    // The area inside the borders of the output panel when it was last drawn
+    output_text_area: Rect,
+    // The first row shown in the output panel
+    scroll: ScrollPosition,
+    // Whether the output panel scrolls down to show new output as it arrives, which stops while the user has scrolled up
+    following: bool,
+    // Where the output ended when the output panel stopped following it, so that new output below the view can be pointed out
+    unfollowed_end: usize,
+    // The pane below the output panel that follows the output of a background job, if it is open
+    job_pane: Option<JobPane>,
+    // Whether scrolling moves the job pane rather than the output panel
+    job_pane_focused: bool,
+    // The size of the output panel when it was last drawn, which determines how far it can be scrolled
+    // * The width is the width of the text inside the borders, which is where lines are wrapped
+    output_width: usize,
+    output_height: usize,
+    // The status of the Git repository that the working directory is in, for the prompt
+    git_status: GitStatusCache,
+    // Whether the user is typing a line, in which case the cursor is shown where they are editing it
+    editing: bool,
+    // When the command that is currently running was started, if there is one
+    running_since: Option<Instant>,
+    // The number of spinner intervals that had passed when the running indicator was last drawn
+    running_ticks: u128,
+    // Whether there are changes that have not been drawn yet, and if so, whether the output panel should scroll to the bottom
+    pending_redraw: Option<bool>,
+    // When the last frame was drawn
+    last_frame: Instant,
+    // The section shown in the debug panel, or None if the debug panel is hidden
+    debug_section: Option<DebugSection>,
+    // The number of lines that have been scrolled down in the debug panel
+    debug_scroll: usize,
+    // The most recent events handled by the console, oldest first, for the debug panel
+    event_log: VecDeque<String>,
+    // When the console was created, which the times in the event log are relative to
+    start_time: Instant,
+    // Events that were read ahead of time while checking for focus changes, oldest first
+    queued_events: VecDeque<Event>,
+    // The dim text shown in the prompt panel until a key is pressed, if the line is empty
+    placeholder: Option<String>,
+    // Whether the last key pressed was Ctrl+C, so that pressing it again can exit the shell
+    interrupted: bool,
+    // The first key of a sequence that has been pressed, while waiting for the key that completes it
+    key_prefix: Option<KeyBinding>,
+    // Whether the terminal window has focus, as last reported by the terminal
+    // * Terminals that do not report focus changes are assumed to always have focus
+    focused: bool,
+    // The color scheme used to draw the console
+    theme: Theme,
+    // The key bindings, which are needed to scroll the output panel while a command is running
+    keymap: Keymap,
+    // How many colors the terminal can show, which every color is converted to fit before being drawn
+    color_support: ColorSupport,
+}
+
+// Represents an incremental search backwards through the command history
+struct HistorySearch {
+    // The text that the matching command must contain
+    query: String,
+    // The index in the history of the command currently being shown, if any command has matched
+    match_index: Option<usize>,
+    // Whether the last search failed to find anything, in which case the previous match is still shown
+    failing: bool,
+    // The line buffer from before the search started, which is restored if the search is cancelled
+    original_line: String,
+}
+
+// Represents a search through the lines in the output panel
+struct OutputSearch {
+    // The text to search for, which is matched regardless of case
+    query: String,
+    // The indices in the output buffer of the lines that contain the query, from oldest to newest
+    matches: Vec<usize>,
+    // The index in the list of matches of the line that was jumped to, if any line matched
+    selected: Option<usize>,
+}
+
+// Represents a position in the output panel, as a line of output and a row within that line
+// * Long lines are wrapped onto several rows, so the output panel can be scrolled to any row of a line
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct ScrollPosition {
+    line: usize,
+    row: usize,
+}
+
+// Represents everything that the output panel is built from, other than the output itself
+// * If none of this changed and the output buffer was not changed either, the output panel from the last frame can be reused
+#[derive(PartialEq)]
+struct OutputWindow {
+    revision: u64,
+    scroll: ScrollPosition,
+    width: usize,
+    height: usize,
+    // The anchor and cursor of the selection, if output is being selected
+    selection: Option<(usize, usize)>,
+    // The query and the selected line of the search, if the output is being searched
+    search: Option<(String, Option<usize>)>,
+    // Whether the output panel is highlighted as having focus, which is only done while the job pane is open
+    focused: bool,
+    theme: Theme,
+}
+
+// Represents the pane that shows the output of a background job as it is written, beside the output of interactive commands
+struct JobPane {
+    // The ID and command line of the job, for the title
+    id: usize,
+    command: String,
+    log: LogFollower,
+    // When the log was last checked for new output
+    last_poll: Instant,
+    // The number of lines that the pane has been scrolled up from the newest output
+    // * While this is zero, the pane keeps following the newest output
+    scroll_back: usize,
+    // The number of lines that fit in the pane when it was last drawn
+    height: usize,
+}
+
+// Represents the output of a command, from the line where it was submitted up to the line where the next command was submitted
+// * The submitted line is the header of the section, which stays visible when the section is collapsed
+struct OutputSection<'a> {
+    // The output position of the header
+    start: usize,
+    // The command that produced the output
+    command: String,
+    // The lines after the header, while the section is collapsed
+    // * Collapsed lines are taken out of the output buffer, so that scrolling, searching, and selecting skip over them
+    folded: Option<Vec<Spans<'a>>>,
+}
+
+// Represents a range of lines in the output panel that the user is selecting to copy to the clipboard
+struct OutputSelection {
+    // The index in the output buffer of the line where the selection starts
+    anchor: usize,
+    // The index in the output buffer of the line that the selection cursor is on, where the selection ends
+    cursor: usize,
+}
+
+impl OutputSelection {
+    // Gets the indices of the first and last selected lines
+    fn range(&self) -> (usize, usize) {
+        (self.anchor.min(self.cursor), self.anchor.max(self.cursor))
+    }
+}
+
+// Represents the menu shown below the line buffer when the word under the cursor has more than one completion
+struct CompletionMenu {
+    completion: Completion,
+    // The text that was typed before the menu was opened, which is restored if the menu is cancelled
+    original: String,
+    // The index of the highlighted candidate, if one has been chosen yet
+    selected: Option<usize>,
+}
+
+impl CompletionMenu {
+    // Lays out the candidates in as many columns as fit in the given width,
+    // showing only as many rows as fit in the given height, scrolled so that the highlighted candidate is visible
+    fn rows(&self, width: usize, height: usize, theme: &Theme) -> Vec<Spans<'static>> {
+        let candidates = &self.completion.candidates;
+        let column_width = candidates
+            .iter()
+            .map(|candidate| candidate.display.width())
+            .max()
+            .unwrap_or(0)
+            + 2;
+        let columns = (width / column_width).max(1);
+        let height = height.max(1);
+        let selected_row = self.selected.unwrap_or(0) / columns;
+        let first_row = selected_row.saturating_sub(height - 1);
+
+        candidates
+            .chunks(columns)
+            .enumerate()
+            .skip(first_row)
+            .take(height)
+            .map(|(row, chunk)| {
+                let mut spans = Vec::new();
+                for (column, candidate) in chunk.iter().enumerate() {
+                    let mut style = match candidate.is_directory {
+                        true => Style::default().fg(theme.directory),
+                        false => Style::default(),
+                    };
+
+                    if self.selected == Some(row * columns + column) {
+                        style = style.add_modifier(Modifier::REVERSED);
+                    }
+
+                    let padding = column_width - candidate.display.width();
+                    spans.push(Span::styled(candidate.display.clone(), style));
+                    spans.push(Span::raw(" ".repeat(padding)));
+                }
+
+                Spans::from(spans)
+            })
+            .collect()
+    }
 }
 
 pub static RAW_MODE: AtomicBool = AtomicBool::new(false);
@@ -127,7 +742,9 @@ pub fn restore_terminal() {
     execute!(
         stdout(),
         LeaveAlternateScreen,
+        DisableFocusChange,
         cursor::MoveTo(0, 0),
+        cursor::SetCursorStyle::DefaultUserShape,
         cursor::Show,
         Clear(ClearType::All)
     )
@@ -137,7 +754,17 @@ pub fn restore_terminal() {
 
 impl<'a> Console<'a> {
     pub fn new() -> Result<Self> {
-        let backend = CrosstermBackend::new(stdout());
+        Self::with_backend(ConsoleBackend::Crossterm(CrosstermBackend::new(stdout())))
+    }
+
+    // Creates a console that is drawn in memory and reads events from a queue, for use in tests
+    pub fn headless(width: u16, height: u16) -> Result<Self> {
+        Self::with_backend(ConsoleBackend::Headless(HeadlessBackend::new(
+            width, height,
+        )))
+    }
+
+    fn with_backend(backend: ConsoleBackend) -> Result<Self> {
         let terminal = Terminal::new(backend)?;
 
         Ok(Self {
@@ -148,19 +775,19 @@ impl<'a> Console<'a> {
 
     // Enters the TUI console
     pub fn enter(&mut self) -> Result<()> {
-        enable_raw_mode()?;
-        // ? Is mouse capture enabled by default?
-        execute!(
-            self.terminal.backend_mut(),
-            EnterAlternateScreen,
-            DisableMouseCapture
-        )?;
+        self.terminal.backend_mut().enter()?;
         self.terminal.show_cursor()?;
-
-        RAW_MODE.store(true, Ordering::Release);
         self.clear(ClearMode::LINE)
     }
 
+    // Gets the in-memory terminal of a headless console, so that events can be injected into it
+    pub fn headless_backend(&mut self) -> Option<&mut HeadlessBackend> {
+        match self.terminal.backend_mut() {
+            ConsoleBackend::Headless(backend) => Some(backend),
+            ConsoleBackend::Crossterm(_) => None,
+        }
+    }
+
     // Closes the TUI console and exits the program
     // * Error handling here is unnecessary because the program is exiting
     // TODO: This assumption may need to be reevaluated in the future
@@ -170,16 +797,100 @@ impl<'a> Console<'a> {
         std::process::exit(code);
     }
 
+    // Applies any changes that have been saved to the configuration file, returning true if the console needs to be redrawn
+    // * What changed is noted in the event log of the debug panel, rather than in the output, so that editing the file stays quiet
+    fn reload_changed_config(&mut self, shell: &mut Shell) -> bool {
+        let Some(result) = shell.reload_changed_config() else {
+            return false;
+        };
+
+        match result {
+            Ok(changes) => {
+                for warning in shell.config_mut().take_warnings() {
+                    self.data
+                        .log_event(format!("Configuration warning: {}", warning));
+                }
+
+                if changes.is_empty() {
+                    self.data
+                        .log_event(String::from("Configuration reloaded, nothing changed"));
+                }
+
+                for change in changes {
+                    self.data
+                        .log_event(format!("Configuration changed: {}", change));
+                }
+
+                // * The placeholder stays hidden if a key has already been pressed
+                let placeholder_shown = self.data.placeholder.is_some();
+                self.data.load_config(shell.config());
+                if !placeholder_shown {
+                    self.data.placeholder = None;
+                }
+
+                self.data.update_prompt(shell);
+            }
+            Err(e) => self
+                .data
+                .log_event(format!("Configuration not reloaded: {}", e)),
+        }
+
+        self.data.update_debug(shell);
+        true
+    }
+
     // Reads a line of input from the user
     // Handles all TUI interaction between the user and the prompt
-    pub fn read_line(&mut self, shell: &Shell) -> Result<String> {
+    pub fn read_line(&mut self, shell: &mut Shell) -> Result<String> {
+        // * The theme, key bindings, and scrollback limit are read from the configuration every time,
+        // * so that changes made with configure or reload-config show up
+        self.data.load_config(shell.config());
+        // * A tick is only saved once a command has been submitted, so this is skipped before the first command
+        if self.data.success_tick_index.is_some() {
+            let result = match shell.success() {
+                true => "succeeded",
+                false => "failed",
+            };
+            self.data.log_event(format!("Command {}", result));
+        }
+
         self.data.update_output_tick(shell);
+        if shell.config().prompt.contains(&PromptSegment::Git) {
+            self.data.git_status.refresh(shell.env().CWD().path());
+        }
+
         self.data.update_prompt(shell);
         self.data.update_debug(shell);
+        self.data.editing = true;
+        // * This is also cleared here in case a command returned early without hiding the indicator
+        self.data.running_since = None;
         self.draw_frame(true)?;
 
         loop {
-            let event = event::read()?;
+            // While the Git status is being computed in the background, the prompt is updated as soon as it is ready,
+            // while the job pane is open, the output of the job is shown as it is written,
+            // and while the configuration file is watched, changes to it are applied as soon as they are saved
+            // * Input is still handled while waiting, so a slow repository never delays keystrokes
+            while (self.data.git_status.is_pending()
+                || self.data.job_pane.is_some()
+                || shell.watches_config())
+                && !self.poll_event(BACKGROUND_POLL_INTERVAL)?
+            {
+                if self.reload_changed_config(shell) {
+                    self.draw_frame(false)?;
+                }
+
+                if self.data.git_status.poll() {
+                    self.data.update_prompt(shell);
+                    self.draw_frame(false)?;
+                }
+
+                if self.data.poll_job_pane() {
+                    self.draw_frame(false)?;
+                }
+            }
+
+            let event = self.read_event()?;
             let action = self.handle_event(event, shell)?;
 
             match action {
@@ -188,26 +899,29 @@ impl<'a> Console<'a> {
                     self.data.enforce_spacing();
 
                     // Save the line buffer for returning and reset it to make way for the next Console.read_line() call
-                    let line = self.data.line_buffer.clone();
-                    self.data.reset_line_buffer();
+                    let line = self.data.editor.take();
+                    self.data.log_event(format!("Submitted {:?}", line));
 
                     // Clear the history buffer and index
                     self.data.history = None;
 
-                    // Clear the autocomplete buffer
+                    // Clear the autocomplete buffer and the argument hint
                     self.data.autocomplete_buffer = None;
+                    self.data.argument_hint = None;
 
                     // Save the line buffer as part of the output buffer, along with a tick which will be colored grey at first
                     // while the command is executing, and then green or red depending on the eventual success or failure of the command
-                    self.data.success_tick_index = Some(self.data.output_buffer.lines.len());
+                    // * Submitting a command always scrolls back down, so that its output can be followed
+                    self.data.scroll_to_bottom();
+                    self.data.success_tick_index = Some(self.data.output_position_end());
+                    self.data.sections.push_back(OutputSection {
+                        start: self.data.output_position_end(),
+                        command: line.clone(),
+                        folded: None,
+                    });
                     let mut line_spans = Spans::from(vec![
-                        Span::styled(
-                            "❯ ",
-                            Style::default()
-                                .fg(Color::DarkGray)
-                                .add_modifier(Modifier::BOLD),
-                        ),
-                        Span::styled(line.clone(), Style::default().fg(Color::LightYellow)),
+                        tick(shell, self.data.theme.pending, false),
+                        Span::styled(line.clone(), Style::default().fg(self.data.theme.command)),
                     ]);
 
                     // TODO: Change this to line_spans.patch_style() once the ratatui PR is merged
@@ -219,17 +933,22 @@ impl<'a> Console<'a> {
 
                     self.data.append_spans_newline(line_spans);
 
-                    // Draw the frame with the new output
+                    // Draw the frame with the new output, without the cursor since the line is no longer being edited
                     self.data.update_debug(shell);
+                    self.data.editing = false;
                     self.draw_frame(true)?;
 
                     return Ok(line);
                 }
-                ReplAction::Exit => self.exit(0),
+                ReplAction::Exit => {
+                    shell.jobs_mut().hangup_all();
+                    self.exit(0)
+                }
                 ReplAction::RedrawFrame => {
                     self.data.update_autocomplete(shell);
+                    self.data.update_argument_hint(shell);
                     self.data.update_debug(shell);
-                    self.draw_frame(false)?;
+                    self.request_redraw(false);
                 }
                 ReplAction::Ignore => (),
             }
@@ -237,382 +956,2231 @@ impl<'a> Console<'a> {
     }
 
     // Handles a key event by queueing appropriate commands based on the given keypress
-    fn handle_event(&mut self, event: Event, shell: &Shell) -> Result<ReplAction> {
+    fn handle_event(&mut self, event: Event, shell: &mut Shell) -> Result<ReplAction> {
         // TODO: Break up event handling into separate functions for different event categories
         match event {
-            Event::Key(event) => {
+            // * Windows reports both key presses and key releases, so releases must be ignored to avoid handling every key twice
+            Event::Key(event) if event.kind == KeyEventKind::Release => {
+                return Ok(ReplAction::Ignore)
+            }
+            // The placeholder disappears as soon as any key is pressed, even one that leaves the line empty
+            Event::Key(event) if self.data.placeholder.is_some() => {
+                self.data.placeholder = None;
+                return self.handle_event(Event::Key(event), shell);
+            }
+            // While link hints are shown, pressing the key next to a link opens it with the 'open' builtin, and any other key hides the hints
+            Event::Key(event) if self.data.link_hints => {
+                self.data.link_hints = false;
+                match event.code {
+                    KeyCode::Char(c) => {
+                        let Some(link) = LINK_HINT_KEYS
+                            .find(c)
+                            .and_then(|index| self.data.visible_links.get(index))
+                        else {
+                            return Ok(ReplAction::RedrawFrame);
+                        };
+
+                        // * A control character cannot be part of a command line, so a link with one in it is not opened
+                        if link.target.chars().any(char::is_control) {
+                            return Ok(ReplAction::RedrawFrame);
+                        }
+
+                        let command = format!("open {}", escape_argument(&link.target));
+                        return Ok(self.data.submit_command(command));
+                    }
+                    KeyCode::Esc => (),
+                    _ => return self.handle_event(Event::Key(event), shell),
+                }
+            }
+            // While selecting output, the navigation keys move the selection cursor
+            // * The selection is done by keyboard because mouse capture would prevent the terminal's own selection from working
+            Event::Key(event) if self.data.output_selection.is_some() => match event.code {
+                KeyCode::Up => self.data.move_selection_cursor(-1),
+                KeyCode::Down => self.data.move_selection_cursor(1),
+                KeyCode::PageUp => self
+                    .data
+                    .move_selection_cursor(-(self.data.page_height() as isize)),
+                KeyCode::PageDown => self
+                    .data
+                    .move_selection_cursor(self.data.page_height() as isize),
+                KeyCode::Char(' ') => {
+                    if let Some(selection) = &mut self.data.output_selection {
+                        selection.anchor = selection.cursor;
+                    }
+                }
+                KeyCode::Enter | KeyCode::Char('y') => self.data.copy_selection(),
+                KeyCode::Esc => self.data.output_selection = None,
+                // Any other key cancels the selection and then does what it normally would
+                _ => {
+                    self.data.output_selection = None;
+                    return self.handle_event(Event::Key(event), shell);
+                }
+            },
+            // While searching the output, typing edits the search and the arrow keys move between matches
+            Event::Key(event) if self.data.output_search.is_some() => {
+                let binding = KeyBinding::new(event.modifiers, event.code);
                 match (event.modifiers, event.code) {
-                    (KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(c)) => {
-                        self.data.insert_char(c)
+                    _ if shell.config().keymap.console_action_for(&binding)
+                        == Some(ConsoleAction::SearchOutput) =>
+                    {
+                        self.data.step_output_search(false)
                     }
-                    (KeyModifiers::NONE, KeyCode::Backspace) => {
-                        self.data.remove_char(RemoveMode::Backspace)
+                    (_, KeyCode::Up) => self.data.step_output_search(false),
+                    (_, KeyCode::Down) => self.data.step_output_search(true),
+                    (KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(c)) => {
+                        self.data.edit_output_search(Some(c))
                     }
-                    (KeyModifiers::NONE, KeyCode::Delete) => {
-                        self.data.remove_char(RemoveMode::Delete)
+                    (_, KeyCode::Backspace) => self.data.edit_output_search(None),
+                    // * The output stays scrolled to wherever the search left it
+                    (_, KeyCode::Enter | KeyCode::Esc) => self.data.output_search = None,
+                    // Any other key ends the search and then does what it normally would
+                    _ => {
+                        self.data.output_search = None;
+                        return self.handle_event(Event::Key(event), shell);
                     }
-                    (KeyModifiers::NONE, KeyCode::Left) => self.data.move_cursor_left(),
-                    (KeyModifiers::NONE, KeyCode::Right) => self.data.move_cursor_right(),
-                    (KeyModifiers::ALT, KeyCode::Left) => self.data.seek_cursor_left(),
-                    (KeyModifiers::ALT, KeyCode::Right) => self.data.seek_cursor_right(),
-                    (KeyModifiers::NONE, KeyCode::Enter) if !self.data.line_buffer.is_empty() => {
-                        return Ok(ReplAction::Return)
+                }
+            }
+            // While searching the history, typing edits the search instead of the line buffer
+            Event::Key(event) if self.data.history_search.is_some() => {
+                let binding = KeyBinding::new(event.modifiers, event.code);
+                match (event.modifiers, event.code) {
+                    _ if shell.config().keymap.console_action_for(&binding)
+                        == Some(ConsoleAction::HistorySearch) =>
+                    {
+                        self.data.search_history_older(shell)
                     }
-                    (KeyModifiers::SHIFT, KeyCode::Up) => self.data.scroll_up(),
-                    (KeyModifiers::SHIFT, KeyCode::Down) => self.data.scroll_down(),
-                    (KeyModifiers::NONE, KeyCode::Up) => {
-                        self.data.scroll_history(HistoryDirection::Up, shell)?
+                    (KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(c)) => {
+                        self.data.edit_history_search(Some(c), shell)
                     }
-                    (KeyModifiers::NONE, KeyCode::Down) => {
-                        self.data.scroll_history(HistoryDirection::Down, shell)?
+                    (_, KeyCode::Backspace) => self.data.edit_history_search(None, shell),
+                    (_, KeyCode::Enter) => self.data.close_history_search(true),
+                    (_, KeyCode::Esc) => self.data.close_history_search(false),
+                    // Any other key ends the search, keeping the match, and then does what it normally would
+                    _ => {
+                        self.data.close_history_search(true);
+                        return self.handle_event(Event::Key(event), shell);
                     }
-                    (KeyModifiers::NONE, KeyCode::Tab) => self.data.autocomplete_line(),
-                    (KeyModifiers::CONTROL, KeyCode::Char('c')) => return Ok(ReplAction::Exit),
-                    (KeyModifiers::CONTROL, KeyCode::Char('l')) => self.clear(ClearMode::OUTPUT)?,
-                    (KeyModifiers::CONTROL, KeyCode::Char('u')) => self.clear(ClearMode::LINE)?,
-                    // TODO: Make this a toggle method
-                    (KeyModifiers::CONTROL, KeyCode::Char('d')) => {
-                        self.data.debug_mode = !self.data.debug_mode
+                }
+            }
+            // While the completion menu is open, the navigation keys move through it instead of doing what they are bound to
+            Event::Key(event) if self.data.completion_menu.is_some() => match event.code {
+                KeyCode::Tab | KeyCode::Down | KeyCode::Right => self.data.select_completion(true),
+                KeyCode::BackTab | KeyCode::Up | KeyCode::Left => {
+                    self.data.select_completion(false)
+                }
+                KeyCode::Enter => self.data.close_completion_menu(true),
+                KeyCode::Esc => self.data.close_completion_menu(false),
+                // Any other key closes the menu, keeping whatever candidate was highlighted, and then does what it normally would
+                _ => {
+                    self.data.completion_menu = None;
+                    return self.handle_event(Event::Key(event), shell);
+                }
+            },
+            Event::Key(event) => {
+                let mut binding = KeyBinding::new(event.modifiers, event.code);
+                // The first key of a sequence is remembered, and the sequence is looked up once the next key is pressed
+                if let Some(prefix) = self.data.key_prefix.take() {
+                    binding = binding.after(prefix);
+                } else if shell.config().keymap.is_prefix(&binding) {
+                    self.data.log_event(format!("{} -> (sequence)", binding));
+                    self.data.key_prefix = Some(binding);
+                    return Ok(ReplAction::Ignore);
+                }
+
+                let action = shell.config().keymap.action_for(&binding).cloned();
+                // * A pasted kill or argument can only be swapped for an older one if nothing else was done in between
+                if !matches!(
+                    action,
+                    Some(KeyAction::Console(
+                        ConsoleAction::Yank
+                            | ConsoleAction::YankPop
+                            | ConsoleAction::YankLastArgument
+                    ))
+                ) {
+                    self.data.editor.forget_yank();
+                }
+
+                if action != Some(KeyAction::Console(ConsoleAction::Interrupt)) {
+                    self.data.interrupted = false;
+                }
+
+                match action {
+                    Some(action) => {
+                        self.data.log_event(format!("{} -> {}", binding, action));
+                        return match action {
+                            KeyAction::Console(action) => self.perform_action(action, shell),
+                            KeyAction::Command(command) => Ok(self.data.submit_command(command)),
+                        };
                     }
-                    _ => return Ok(ReplAction::Ignore),
+                    // * A sequence that is not bound to anything does nothing, rather than typing its last key
+                    None if binding.prefix.is_some() => return Ok(ReplAction::Ignore),
+                    None => match (event.modifiers, event.code) {
+                        (KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(c)) => {
+                            self.data.editor.insert_char(c)
+                        }
+                        _ => return Ok(ReplAction::Ignore),
+                    },
                 }
             }
-            // $ This seems like a crappy solution to prevent the Resize event from being ignored
-            Event::Resize(_, _) => (),
+            Event::Resize(width, height) => {
+                self.data
+                    .log_event(format!("Resized to {}x{}", width, height));
+                // * The links move when the output is wrapped differently, so any hints would be in the wrong place
+                self.data.link_hints = false;
+                self.data.resize(Rect::new(0, 0, width, height))
+            }
             _ => return Ok(ReplAction::Ignore),
         }
 
-        Ok(ReplAction::RedrawFrame)
+        Ok(ReplAction::RedrawFrame)
+    }
+
+    // Performs an action that was triggered by a key binding
+    fn perform_action(&mut self, action: ConsoleAction, shell: &mut Shell) -> Result<ReplAction> {
+        match action {
+            ConsoleAction::DeleteBackward => self.data.editor.delete_backward(),
+            ConsoleAction::DeleteForward => self.data.editor.delete_forward(),
+            // * Like in other shells, this exits (hanging up background jobs) when there is nothing left to delete
+            ConsoleAction::DeleteForwardOrExit if self.data.editor.is_empty() => {
+                return Ok(ReplAction::Exit)
+            }
+            ConsoleAction::DeleteForwardOrExit => self.data.editor.delete_forward(),
+            ConsoleAction::MoveLeft => self.data.editor.move_left(),
+            ConsoleAction::MoveRight => self.data.editor.move_right(),
+            ConsoleAction::MoveWordLeft => self.data.editor.move_word_left(),
+            ConsoleAction::MoveWordRight => self.data.editor.move_word_right(),
+            ConsoleAction::MoveToStart => self.data.editor.move_to_start(),
+            ConsoleAction::MoveToEnd => self.data.editor.move_to_end(),
+            ConsoleAction::SelectLeft => self.data.editor.select_left(),
+            ConsoleAction::SelectRight => self.data.editor.select_right(),
+            ConsoleAction::Submit if !self.data.editor.is_empty() => return Ok(ReplAction::Return),
+            ConsoleAction::Submit => return Ok(ReplAction::Ignore),
+            ConsoleAction::ScrollUp
+            | ConsoleAction::ScrollDown
+            | ConsoleAction::ScrollPageUp
+            | ConsoleAction::ScrollPageDown
+            | ConsoleAction::FollowOutput => _ = self.data.perform_scroll(action),
+            ConsoleAction::SplitOutput => match shell.jobs().jobs().last() {
+                Some(job) => self.show_job(job.id, &job.command, &job.log_path),
+                None => {
+                    self.data
+                        .append_str_newline("There are no background jobs to show");
+                    self.data.enforce_spacing();
+                    self.data.scroll_to_bottom();
+                }
+            },
+            ConsoleAction::SwitchPane => {
+                self.data.job_pane_focused =
+                    self.data.job_pane.is_some() && !self.data.job_pane_focused
+            }
+            ConsoleAction::ClosePane => self.close_job_pane(),
+            ConsoleAction::SelectOutput => self.data.start_selection(),
+            ConsoleAction::LinkHints => self.data.link_hints = !self.data.visible_links.is_empty(),
+            ConsoleAction::ToggleSection => self.data.toggle_section(),
+            ConsoleAction::ToggleAllSections => self.data.toggle_all_sections(),
+            ConsoleAction::SearchOutput => {
+                self.data.output_search = Some(OutputSearch {
+                    query: String::new(),
+                    matches: Vec::new(),
+                    selected: None,
+                })
+            }
+            ConsoleAction::HistoryUp => self.data.scroll_history(HistoryDirection::Up, shell)?,
+            ConsoleAction::HistoryDown => {
+                self.data.scroll_history(HistoryDirection::Down, shell)?
+            }
+            ConsoleAction::HistorySearch => self.data.search_history_older(shell),
+            ConsoleAction::Autocomplete => self.complete(shell),
+            ConsoleAction::Exit => return Ok(ReplAction::Exit),
+            ConsoleAction::Interrupt => return Ok(self.interrupt(shell)),
+            ConsoleAction::ClearOutput => self.clear(ClearMode::OUTPUT)?,
+            ConsoleAction::ClearLine => self.clear(ClearMode::LINE)?,
+            ConsoleAction::DeleteWordBackward => self.data.editor.delete_word_backward(),
+            ConsoleAction::KillToStart => self.data.editor.kill_to_start(),
+            ConsoleAction::KillToEnd => self.data.editor.kill_to_end(),
+            ConsoleAction::Yank => self.data.editor.yank(),
+            ConsoleAction::YankPop => self.data.editor.yank_pop(),
+            ConsoleAction::EditInEditor => self.edit_in_editor(shell)?,
+            ConsoleAction::PasteClipboard => self.paste_clipboard()?,
+            ConsoleAction::YankLastArgument => {
+                self.data.editor.yank_last_argument(&shell.command_history)
+            }
+            ConsoleAction::ToggleDebug => self.data.cycle_debug_section(),
+            ConsoleAction::ScrollDebugUp => self.data.scroll_debug_page(false),
+            ConsoleAction::ScrollDebugDown => self.data.scroll_debug_page(true),
+        }
+
+        Ok(ReplAction::RedrawFrame)
+    }
+
+    // Cancels the line being typed, leaving it in the output panel with '^C' after it like other shells do
+    // * Depending on the configuration, this exits the shell instead, either always or when Ctrl+C is pressed twice in a row
+    fn interrupt(&mut self, shell: &Shell) -> ReplAction {
+        let behavior = shell.config().interrupt_behavior;
+        match behavior {
+            InterruptBehavior::Exit => return ReplAction::Exit,
+            InterruptBehavior::DoublePress if self.data.interrupted => return ReplAction::Exit,
+            _ => (),
+        }
+
+        self.data.enforce_spacing();
+        let dim = Style::default().add_modifier(Modifier::DIM);
+        let mut line_spans = Spans::from(vec![
+            tick(shell, self.data.theme.pending, false),
+            Span::styled(
+                self.data.editor.take(),
+                Style::default().fg(self.data.theme.command),
+            ),
+            Span::styled("^C", dim),
+        ]);
+
+        if behavior == InterruptBehavior::DoublePress {
+            line_spans
+                .0
+                .push(Span::styled(" (press again to exit)", dim));
+        }
+
+        self.data.append_spans_newline(line_spans);
+        self.data.history = None;
+        self.data.interrupted = true;
+        self.data.scroll_to_bottom();
+        ReplAction::RedrawFrame
+    }
+
+    // Completes the word under the cursor, or opens a menu to choose between the candidates if there is more than one
+    // * If there is nothing to complete, the suggestion from the history is accepted instead
+    fn complete(&mut self, shell: &mut Shell) {
+        let editor = &self.data.editor;
+        let completion = Completion::for_line(editor.text(), editor.cursor(), shell);
+
+        match completion.candidates.len() {
+            0 => self.data.autocomplete_line(),
+            1 => {
+                let candidate = &completion.candidates[0];
+                // A unique completion is finished off so that the next argument can be typed right away,
+                // unless it is a directory (so that a path inside it can be completed next) or there is more text after the cursor
+                let finished = !candidate.is_directory && editor.cursor() == editor.text().len();
+                let replacement = completion.replacement(&candidate.word, finished);
+                self.data
+                    .editor
+                    .replace_before_cursor(completion.start, &replacement);
+            }
+            _ => {
+                let prefix = completion.common_prefix();
+                if prefix.len() > completion.word.len() {
+                    let replacement = completion.replacement(&prefix, false);
+                    self.data
+                        .editor
+                        .replace_before_cursor(completion.start, &replacement);
+                } else {
+                    let original = editor.text()[completion.start..editor.cursor()].to_string();
+                    self.data.completion_menu = Some(CompletionMenu {
+                        completion,
+                        original,
+                        selected: None,
+                    });
+                }
+            }
+        }
+    }
+
+    // Updates the TUI frame
+    // ? Should the autoscroll parameter use a custom type for readability?
+    pub fn draw_frame(&mut self, autoscroll: bool) -> Result<()> {
+        // * A pending request to scroll to the bottom is not lost when a frame is drawn without one
+        let autoscroll = self.data.pending_redraw.take() == Some(true) || autoscroll;
+        self.data.last_frame = Instant::now();
+        let frame = self
+            .terminal
+            .draw(|f| self.data.generate_frame(f, autoscroll))?;
+
+        // * The links are not looked for again while hints are shown, because the hints cover part of the link text
+        if !self.data.link_hints {
+            self.data.visible_links = match self.data.hyperlinks {
+                true => links::find_visible_links(
+                    frame.buffer,
+                    self.data.output_text_area,
+                    self.data.explicit_links.make_contiguous(),
+                ),
+                false => Vec::new(),
+            };
+        }
+
+        if !self.data.hyperlinks || self.data.visible_links.is_empty() {
+            return Ok(());
+        }
+
+        // Draw the visible links again, this time wrapped in OSC 8 sequences so that they can be clicked
+        // * ratatui has no way to attach a link to a cell, so the cells are redrawn by hand after each frame,
+        // * and terminals that do not support OSC 8 ignore the sequences and just draw the same text again
+        let links: Vec<_> = self
+            .data
+            .visible_links
+            .iter()
+            .map(|link| {
+                let cells: Vec<_> = (link.x..link.x + link.width)
+                    .map(|x| (x, link.y, frame.buffer.get(x, link.y).clone()))
+                    .collect();
+                (link.uri.clone(), cells)
+            })
+            .collect();
+
+        let backend = self.terminal.backend_mut();
+        queue!(backend, cursor::SavePosition)?;
+        for (uri, cells) in &links {
+            queue!(backend, Print(format!("\x1b]8;;{}\x1b\\", uri)))?;
+            backend.draw(cells.iter().map(|(x, y, cell)| (*x, *y, cell)))?;
+            queue!(backend, Print("\x1b]8;;\x1b\\"))?;
+        }
+
+        queue!(backend, cursor::RestorePosition)?;
+        Backend::flush(backend)?;
+        Ok(())
+    }
+
+    // Asks for the frame to be redrawn, which happens right away unless the last frame was drawn very recently
+    // * Otherwise, the frame is drawn once the next one is due or when the console waits for input,
+    // * so that a burst of output or a paste causes a handful of redraws instead of hundreds
+    fn request_redraw(&mut self, autoscroll: bool) {
+        self.data.pending_redraw = Some(autoscroll || self.data.pending_redraw == Some(true));
+        self.redraw_if_due();
+    }
+
+    // Draws the changes that have not been drawn yet, if the next frame is due
+    fn redraw_if_due(&mut self) {
+        if self.data.last_frame.elapsed() >= FRAME_INTERVAL {
+            _ = self.flush_redraw();
+        }
+    }
+
+    // Draws the changes that have not been drawn yet, even if the next frame is not due
+    fn flush_redraw(&mut self) -> Result<()> {
+        match self.data.pending_redraw {
+            Some(autoscroll) => self.draw_frame(autoscroll),
+            None => Ok(()),
+        }
+    }
+
+    // Waits up to the given amount of time for an event, returning true if one is ready to be read
+    // * Pending changes are drawn before waiting, unless there is no time to wait or an event is already waiting
+    fn poll_event(&mut self, timeout: Duration) -> Result<bool> {
+        if !self.data.queued_events.is_empty() {
+            return Ok(true);
+        }
+
+        if timeout.is_zero() {
+            self.redraw_if_due();
+        } else if !self.terminal.backend_mut().poll_event(Duration::ZERO)? {
+            self.flush_redraw()?;
+        }
+
+        self.terminal.backend_mut().poll_event(timeout)
+    }
+
+    // Waits for the next event and returns it
+    // * Pending changes are drawn before waiting, unless an event is already waiting
+    fn read_event(&mut self) -> Result<Event> {
+        if let Some(event) = self.data.queued_events.pop_front() {
+            return Ok(event);
+        }
+
+        if !self.terminal.backend_mut().poll_event(Duration::ZERO)? {
+            self.flush_redraw()?;
+        }
+
+        let event = self.terminal.backend_mut().read_event()?;
+        self.data.track_focus(&event);
+        Ok(event)
+    }
+
+    // Checks whether the terminal window has focus, including any focus changes that have not been handled yet
+    // * Events are not read while a command is running, so the ones waiting are read and queued to find the latest focus change
+    pub fn is_focused(&mut self) -> Result<bool> {
+        while self.terminal.backend_mut().poll_event(Duration::ZERO)? {
+            let event = self.terminal.backend_mut().read_event()?;
+            self.data.track_focus(&event);
+            self.data.queued_events.push_back(event);
+        }
+
+        Ok(self.data.focused)
+    }
+
+    // Shows a desktop notification that a command finished, if it ran for long enough and the terminal does not have focus
+    pub fn notify_finished(&mut self, shell: &Shell, line: &str, elapsed: Duration) -> Result<()> {
+        let Some(notify_after) = shell.config().notify_after else {
+            return Ok(());
+        };
+
+        if elapsed < Duration::from_secs(notify_after) || self.is_focused()? {
+            return Ok(());
+        }
+
+        let status = match shell.success() {
+            true => "succeeded",
+            false => "failed",
+        };
+
+        notification::send(
+            format!("Command {}", status),
+            format!("{} ({})", line, format_elapsed(elapsed)),
+        );
+        Ok(())
+    }
+
+    // Opens the line buffer in the user's editor, replacing it with whatever the editor saved
+    // * The console is suspended while the editor runs, since the editor needs the whole terminal
+    // * Lines are joined with spaces (and a trailing backslash is dropped), so a long command can be split across lines
+    fn edit_in_editor(&mut self, shell: &Shell) -> Result<()> {
+        let editor = shell
+            .env()
+            .get_var("VISUAL")
+            .or_else(|| shell.env().get_var("EDITOR"))
+            .unwrap_or_else(|| String::from(DEFAULT_EDITOR));
+        let (file_path, mut file) =
+            temp_file::create(&format!("rush-line-{}", std::process::id()), "txt")?;
+        file.write_all(self.data.editor.text().as_bytes())?;
+        drop(file);
+
+        // * The editor may be given arguments, such as 'code --wait'
+        let mut words = editor.split_whitespace();
+        let program = words.next().unwrap_or(DEFAULT_EDITOR);
+        let status =
+            self.run_full_screen(|| Process::new(program).args(words).arg(&file_path).status())?;
+
+        match status {
+            Ok(status) if status.success() => {
+                let text = fs_err::read_to_string(&file_path)?;
+                let line: Vec<&str> = text
+                    .lines()
+                    .map(|line| line.trim_end().trim_end_matches('\\').trim())
+                    .filter(|line| !line.is_empty())
+                    .collect();
+                self.data.editor.set_text(line.join(" "));
+            }
+            // * If the editor exits with an error, the line is left as it was
+            Ok(_) => (),
+            Err(e) => self.println(&format!("Failed to open editor '{}': {}", program, e)),
+        }
+
+        _ = fs_err::remove_file(&file_path);
+        Ok(())
+    }
+
+    // Inserts the contents of the system clipboard at the cursor
+    // * Line breaks are replaced with spaces, since the line buffer can only hold a single line
+    fn paste_clipboard(&mut self) -> Result<()> {
+        let text = match clipboard::paste() {
+            Some(text) => Some(text),
+            None => self.read_terminal_clipboard()?,
+        };
+
+        match text {
+            Some(text) => {
+                let lines: Vec<&str> = text.lines().collect();
+                self.data.editor.insert_str(&lines.join(" "));
+            }
+            None => self
+                .data
+                .log_event(String::from("Clipboard is empty or unavailable")),
+        }
+
+        Ok(())
+    }
+
+    // Asks the terminal for the contents of the clipboard with OSC 52, waiting a short time for it to reply
+    // * The reply arrives as if it were typed, so the events are put back together into the sequence,
+    // * and any other events that arrive in the meantime are queued to be handled afterwards
+    // $ Many terminals do not allow programs to read the clipboard, in which case nothing is returned
+    fn read_terminal_clipboard(&mut self) -> Result<Option<String>> {
+        if self.headless_backend().is_some() {
+            return Ok(None);
+        }
+
+        clipboard::request_osc52(self.terminal.backend_mut())?;
+        let deadline = Instant::now() + OSC52_REPLY_TIMEOUT;
+        let mut reply = String::new();
+
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            if !self.terminal.backend_mut().poll_event(remaining)? {
+                break;
+            }
+
+            let event = self.terminal.backend_mut().read_event()?;
+            let Event::Key(key) = event else {
+                self.data.queued_events.push_back(event);
+                continue;
+            };
+
+            // * ESC followed by a character is read as Alt and that character, and BEL is read as Ctrl+G
+            match (key.modifiers - KeyModifiers::SHIFT, key.code) {
+                (KeyModifiers::ALT, KeyCode::Char(c)) => {
+                    reply.push('\x1b');
+                    reply.push(c);
+                }
+                (KeyModifiers::CONTROL, KeyCode::Char('g')) => reply.push('\x07'),
+                (KeyModifiers::NONE, KeyCode::Char(c)) if !reply.is_empty() => reply.push(c),
+                _ => self.data.queued_events.push_back(event),
+            }
+
+            if reply.ends_with('\x07') || reply.ends_with("\x1b\\") {
+                break;
+            }
+        }
+
+        Ok(clipboard::decode_osc52_reply(&reply))
+    }
+
+    // Gives the terminal to a program that draws over the whole screen (such as an editor or a pager) while it runs,
+    // taking it back afterward
+    // * The program draws over the screen, so every cell must be drawn again afterward
+    pub fn run_full_screen<T>(&mut self, run: impl FnOnce() -> T) -> Result<T> {
+        self.terminal.backend_mut().suspend()?;
+        let result = run();
+        self.terminal.backend_mut().enter()?;
+        self.terminal.clear()?;
+        self.request_redraw(false);
+        Ok(result)
+    }
+
+    // Clears the screen and the line buffer and reprompts the user
+    fn clear(&mut self, mode: ClearMode) -> Result<()> {
+        // Clear the output panel
+        if mode.contains(ClearMode::OUTPUT) {
+            self.data.output_mut().clear();
+            self.data.sections.clear();
+        }
+
+        if mode.contains(ClearMode::LINE) {
+            self.data.editor.take();
+        }
+
+        Ok(())
+    }
+
+    // Clears the output panel
+    // * This is a public wrapper for the clear() method
+    pub fn clear_output(&mut self) -> Result<()> {
+        self.clear(ClearMode::OUTPUT)
+    }
+
+    // Opens the job pane below the output panel to follow the log of the given job, replacing the job shown in it before
+    pub fn show_job(&mut self, id: usize, command: &str, log_path: &Path) {
+        let mut log = LogFollower::new(log_path.to_path_buf());
+        log.poll();
+        self.data.job_pane = Some(JobPane {
+            id,
+            command: command.to_string(),
+            log,
+            last_poll: Instant::now(),
+            scroll_back: 0,
+            height: 0,
+        });
+        self.data.log_event(format!("Showing job {}", id));
+        self.request_redraw(false)
+    }
+
+    // Closes the job pane, giving all of the space back to the output panel
+    pub fn close_job_pane(&mut self) {
+        self.data.job_pane = None;
+        self.data.job_pane_focused = false;
+        self.request_redraw(false)
+    }
+
+    // Gets the color scheme currently used to draw the console
+    pub fn theme(&self) -> &Theme {
+        &self.data.theme
+    }
+
+    // Switches to a different color scheme, redrawing the console
+    pub fn set_theme(&mut self, theme: Theme, shell: &Shell) {
+        self.data.theme = theme;
+        self.data.update_prompt(shell);
+        self.data.update_debug(shell);
+        self.request_redraw(false)
+    }
+
+    // Prints a line of text to the console
+    // TODO: Probably make this a macro in the future, but for now just make it use &str or String
+    // TODO: Make lazy execution version of this, or a lazy execution mode
+    pub fn println(&mut self, text: &str) {
+        self.data.append_str_newline(text);
+        self.request_redraw(true)
+    }
+
+    // Prints a line of styled text to the console
+    // * This allows builtins to color or highlight parts of their output
+    pub fn println_styled(&mut self, spans: Spans<'a>) {
+        self.println_styled_lines([spans])
+    }
+
+    // Prints a batch of styled lines to the console, only redrawing the frame once at the end
+    pub fn println_styled_lines(&mut self, lines: impl IntoIterator<Item = Spans<'a>>) {
+        for spans in lines {
+            self.data.append_styled_newline(spans);
+        }
+
+        self.request_redraw(true)
+    }
+
+    // Prints a line of text to the console without a newline
+    pub fn print(&mut self, text: &str) {
+        self.data.append_str(text);
+        self.request_redraw(true)
+    }
+
+    // Replaces the contents of the current (last) line of output
+    // * This is used for in-place updates such as progress indicators
+    pub fn overwrite_line(&mut self, text: &str) {
+        self.data.replace_last_line(text);
+        self.request_redraw(true)
+    }
+
+    // Waits up to the given duration for the user to interrupt a long-running command with Ctrl+C
+    // Returns true if the command should be cancelled
+    // * Other input is discarded, but the frame is still redrawn on resize so the UI does not appear frozen
+    pub fn poll_interrupt(&mut self, timeout: Duration) -> Result<bool> {
+        // * A timeout too long to add to the current time is waited out in full each time instead
+        let deadline = Instant::now().checked_add(timeout);
+
+        loop {
+            let remaining = deadline.map_or(timeout, |deadline| {
+                deadline.saturating_duration_since(Instant::now())
+            });
+            if !self.poll_event(remaining)? {
+                return Ok(false);
+            }
+
+            match self.read_event()? {
+                Event::Key(event)
+                    if event.kind != KeyEventKind::Release
+                        && (event.modifiers, event.code)
+                            == (KeyModifiers::CONTROL, KeyCode::Char('c')) =>
+                {
+                    return Ok(true);
+                }
+                Event::Resize(_, _) => self.request_redraw(true),
+                event => _ = self.scroll_while_running(&event),
+            }
+        }
+    }
+
+    // Scrolls the output panel if the event is a key bound to one of the scrolling actions, returning whether it was
+    // * This lets the output of a running command be scrolled through while it is still being printed
+    fn scroll_while_running(&mut self, event: &Event) -> bool {
+        let Event::Key(event) = event else {
+            return false;
+        };
+
+        let binding = KeyBinding::new(event.modifiers, event.code);
+        let scrolled = event.kind != KeyEventKind::Release
+            && self
+                .data
+                .keymap
+                .console_action_for(&binding)
+                .is_some_and(|action| self.data.perform_scroll(action));
+        if scrolled {
+            self.request_redraw(false);
+        }
+
+        scrolled
+    }
+
+    // Asks the user a yes/no question in the output panel, blocking until they answer
+    // * Enter, Esc, and Ctrl+C all count as "no", so that destructive actions are never taken by accident
+    pub fn confirm(&mut self, question: &str) -> Result<bool> {
+        self.print(&format!("{} (y/n) ", question));
+
+        let answer = loop {
+            match self.read_event()? {
+                Event::Key(event) if event.kind != KeyEventKind::Release => {
+                    match (event.modifiers, event.code) {
+                        (KeyModifiers::CONTROL, KeyCode::Char('c')) => break false,
+                        (_, KeyCode::Char('y') | KeyCode::Char('Y')) => break true,
+                        (_, KeyCode::Char('n') | KeyCode::Char('N')) => break false,
+                        (_, KeyCode::Enter | KeyCode::Esc) => break false,
+                        _ => (),
+                    }
+                }
+                Event::Resize(_, _) => self.request_redraw(true),
+                _ => (),
+            }
+        };
+
+        self.println(if answer { "y" } else { "n" });
+        Ok(answer)
+    }
+
+    // Asks the user for a line of input in the output panel, blocking until they press Enter
+    // Returns None if the user cancels with Esc or Ctrl+C
+    // * Hidden input (e.g. for passwords) is not echoed at all, so that not even its length is revealed
+    pub fn read_input(&mut self, prompt: &str, hidden: bool) -> Result<Option<String>> {
+        let mut input = String::new();
+        self.print(prompt);
+
+        let submitted = loop {
+            match self.read_event()? {
+                Event::Key(event) if event.kind != KeyEventKind::Release => {
+                    match (event.modifiers, event.code) {
+                        (KeyModifiers::CONTROL, KeyCode::Char('c')) => break false,
+                        (_, KeyCode::Esc) => break false,
+                        (_, KeyCode::Enter) => break true,
+                        (_, KeyCode::Backspace) => {
+                            input.pop();
+                        }
+                        (KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(c)) => {
+                            input.push(c)
+                        }
+                        _ => continue,
+                    }
+
+                    if !hidden {
+                        self.overwrite_line(&format!("{}{}", prompt, input));
+                    }
+                }
+                Event::Resize(_, _) => self.request_redraw(true),
+                _ => (),
+            }
+        };
+
+        showln!(self);
+        Ok(submitted.then_some(input))
+    }
+
+    // Gets the text of every line in the output panel, without any styling
+    // * The empty line that the next output will be printed on is left out
+    pub fn output_text(&self) -> Vec<String> {
+        let mut lines: Vec<_> = self.data.output_buffer.iter().map(line_text).collect();
+        if lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+
+        lines
+    }
+
+    // Gets the position of the current line in the output panel
+    // * This can be passed to truncate_output() later to replace everything printed after this point
+    // * Positions count every line ever printed, so they stay valid when old lines are dropped from the scrollback
+    pub fn output_position(&self) -> usize {
+        let lines = &self.data.output_buffer;
+        match lines.back() {
+            Some(last_line) if last_line.0.iter().all(|span| span.content.is_empty()) => {
+                self.data.output_position_end() - 1
+            }
+            _ => self.data.output_position_end(),
+        }
+    }
+
+    // Removes all output printed since the given position, redrawing the frame
+    pub fn truncate_output(&mut self, position: usize) {
+        let length = position.saturating_sub(self.data.dropped_lines);
+        let lines = self.data.output_mut();
+        lines.truncate(length);
+        if position > 0 {
+            lines.push_back(Spans::default());
+        }
+
+        self.request_redraw(true)
+    }
+
+    // Gets the number of lines of output that are visible in the output panel at once
+    pub fn output_height(&mut self) -> Result<usize> {
+        let (output_area, _) = split_frame(self.terminal.size()?);
+        // * This matches the bottom margin used when autoscrolling
+        Ok((output_area.height as usize).saturating_sub(3))
+    }
+
+    // Shows a status line at the bottom of the output panel and waits for the user to choose how to continue paging
+    // * The status line is cleared afterwards, so that it does not end up mixed into the paged output
+    pub fn wait_for_pager(&mut self, status: &str) -> Result<PagerAction> {
+        self.data.append_styled(Span::styled(
+            status.to_string(),
+            Style::default().add_modifier(Modifier::REVERSED),
+        ));
+        self.draw_frame(true)?;
+
+        let action = loop {
+            match self.read_event()? {
+                Event::Key(event) if event.kind != KeyEventKind::Release => {
+                    match (event.modifiers, event.code) {
+                        (KeyModifiers::CONTROL, KeyCode::Char('c')) => break PagerAction::Quit,
+                        (_, KeyCode::Char('q') | KeyCode::Esc) => break PagerAction::Quit,
+                        (_, KeyCode::Char(' ') | KeyCode::PageDown) => break PagerAction::NextPage,
+                        (_, KeyCode::Enter | KeyCode::Down | KeyCode::Char('j')) => {
+                            break PagerAction::NextLine
+                        }
+                        _ => (),
+                    }
+                }
+                Event::Resize(_, _) => self.request_redraw(true),
+                _ => (),
+            }
+        };
+
+        self.data.replace_last_line("");
+        Ok(action)
+    }
+
+    // Prints rows of text with each column padded to the width of its widest cell
+    pub fn println_columns(&mut self, rows: &[Vec<String>], alignments: &[ColumnAlignment]) {
+        self.println_lines(format_columns(rows, alignments))
+    }
+
+    // Shows a spinner and the time elapsed since now in the prompt panel title, until end_running() is called
+    // * The indicator only moves when tick_running() is called, so it should be called regularly while the command runs
+    pub fn begin_running(&mut self) {
+        self.data.running_since = Some(Instant::now());
+        self.data.running_ticks = 0;
+        self.request_redraw(false)
+    }
+
+    // Redraws the running indicator, if enough time has passed for it to change
+    pub fn tick_running(&mut self) {
+        let Some(running_since) = self.data.running_since else {
+            return;
+        };
+
+        // * Any keys that scroll the output panel are handled right away, and other events are queued until the command finishes
+        _ = self.handle_running_events();
+
+        // * The job pane keeps following its job while commands are run
+        let ticks = running_since.elapsed().as_millis() / SPINNER_INTERVAL.as_millis();
+        if self.data.poll_job_pane() || ticks != self.data.running_ticks {
+            self.data.running_ticks = ticks;
+            self.request_redraw(false);
+        } else {
+            // * Output that arrived too soon after the last frame to be drawn right away is drawn here once it is due
+            self.redraw_if_due();
+        }
+    }
+
+    // Reads the events that have arrived while a command is running, scrolling the output panel if any keys are bound to that
+    fn handle_running_events(&mut self) -> Result<()> {
+        while self.terminal.backend_mut().poll_event(Duration::ZERO)? {
+            let event = self.terminal.backend_mut().read_event()?;
+            self.data.track_focus(&event);
+            if !self.scroll_while_running(&event) {
+                self.data.queued_events.push_back(event);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Hides the running indicator, drawing any output that has not been drawn yet
+    pub fn end_running(&mut self) {
+        self.data.running_since = None;
+        _ = self.flush_redraw();
+    }
+
+    // Prints a batch of lines to the console, only redrawing the frame once at the end
+    // * This should be preferred over repeated println() calls when printing a large amount of text at once
+    pub fn println_lines<S: AsRef<str>>(&mut self, lines: impl IntoIterator<Item = S>) {
+        for line in lines {
+            self.data.append_str_newline(line.as_ref());
+        }
+
+        self.request_redraw(true)
+    }
+
+    // Prints a batch of lines that may contain ANSI escape sequences, such as the output of a child process,
+    // turning SGR sequences into styles and discarding any other sequences
+    // * The batch is parsed as a whole so that a style that is set on one line carries over to the next
+    // $ Styles do not carry over between batches, but programs almost always reset their style before a line ends
+    // * OSC 8 hyperlinks are removed from the text and remembered, so that the text they were attached to stays clickable
+    pub fn println_ansi_lines<S: AsRef<str>>(&mut self, lines: impl IntoIterator<Item = S>) {
+        let lines: Vec<String> = lines
+            .into_iter()
+            .map(|line| {
+                let (line, links) = links::extract_hyperlinks(line.as_ref());
+                self.data.remember_links(links);
+                line
+            })
+            .collect();
+        let joined = lines.join("\n");
+
+        match joined.into_text() {
+            Ok(text) => {
+                let mut styled_lines = text.lines;
+                // * Trailing empty lines are not returned by the parser, so they are added back
+                styled_lines.resize(lines.len().max(styled_lines.len()), Spans::default());
+                self.println_styled_lines(styled_lines)
+            }
+            Err(_) => self.println_lines(lines),
+        }
+    }
+}
+
+impl Drop for Console<'_> {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+impl<'a> ConsoleData<'a> {
+    fn new() -> Self {
+        Self {
+            prompt: Spans::default(),
+            prompt_tick: Span::raw(""),
+            success_tick_index: None,
+            editor: LineEditor::new(),
+            output_buffer: VecDeque::new(),
+            output_revision: 0,
+            output_cache: None,
+            dropped_lines: 0,
+            scrollback_limit: None,
+            debug_buffer: Text::default(),
+            autocomplete_buffer: None,
+            completion_menu: None,
+            argument_hint: None,
+            sections: VecDeque::new(),
+            history_search: None,
+            output_search: None,
+            output_selection: None,
+            link_hints: false,
+            visible_links: Vec::new(),
+            explicit_links: VecDeque::new(),
+            hyperlinks: true,
+            output_text_area: Rect::default(),
+            history: None,
+            scroll: ScrollPosition::default(),
+            following: true,
+            unfollowed_end: 0,
+            job_pane: None,
+            job_pane_focused: false,
+            output_width: 0,
+            output_height: 0,
+            git_status: GitStatusCache::new(),
+            editing: false,
+            running_since: None,
+            running_ticks: 0,
+            pending_redraw: None,
+            last_frame: Instant::now(),
+            debug_section: None,
+            debug_scroll: 0,
+            event_log: VecDeque::new(),
+            start_time: Instant::now(),
+            queued_events: VecDeque::new(),
+            placeholder: None,
+            interrupted: false,
+            key_prefix: None,
+            focused: true,
+            theme: Theme::default(),
+            keymap: Keymap::default(),
+            color_support: ColorSupport::detect(),
+        }
+    }
+
+    // Remembers whether the terminal window has focus, if the event is a focus change
+    fn track_focus(&mut self, event: &Event) {
+        match event {
+            Event::FocusGained => self.focused = true,
+            Event::FocusLost => self.focused = false,
+            _ => (),
+        }
+    }
+
+    // Recolors the command output tick based on the command's exit status
+    fn update_output_tick(&mut self, shell: &Shell) {
+        let color = match shell.success() {
+            true => self.theme.success,
+            false => self.theme.failure,
+        };
+
+        // * If the tick index is None, this is an extraneous call made before a command has been executed, and should be ignored
+        let Some(index) = self.success_tick_index else {
+            return;
+        };
+
+        // * If the line has already been dropped from the scrollback, there is nothing to recolor
+        let Some(index) = index.checked_sub(self.dropped_lines) else {
+            return;
+        };
+
+        // The duration is only shown if the command took long enough
+        let duration = shell.last_duration().filter(|duration| {
+            shell
+                .config()
+                .show_duration_after
+                .is_some_and(|threshold| duration.as_secs_f64() >= threshold)
+        });
+
+        let new_tick = tick(shell, color, true);
+        let Some(line) = self.output_mut().get_mut(index) else {
+            return;
+        };
+
+        // Get the tick from the output buffer
+        // If the tick exists, it will be the first Span in the indexed Spans
+        // * The rest of the style is kept, since the line is made italic after the tick is created
+        if let Some(tick) = line.0.first_mut() {
+            tick.content = new_tick.content;
+            tick.style = tick.style.fg(color);
+        }
+
+        if let Some(duration) = duration {
+            line.0.push(Span::styled(
+                format!(" ({})", format_duration(duration)),
+                Style::default().add_modifier(Modifier::DIM),
+            ));
+        }
+    }
+
+    // Updates the prompt panel header based on the current shell state (USER, CWD, etc)
+    // TODO: This will eventually need to not be hard-coded to allow for user customization
+    fn update_prompt(&mut self, shell: &Shell) {
+        let home = shell.env().HOME();
+        let truncation = shell.config().truncation_factor;
+        let bold = |color| Style::default().fg(color).add_modifier(Modifier::BOLD);
+
+        let mut span_list = Vec::new();
+        for segment in shell.config().prompt.segments() {
+            let span = match segment {
+                PromptSegment::Text(text) => Span::from(text.clone()),
+                PromptSegment::User => {
+                    Span::styled(shell.env().USER().to_string(), bold(self.theme.user))
+                }
+                PromptSegment::Host => Span::styled(prompt::host_name(), bold(self.theme.user)),
+                PromptSegment::Cwd => Span::styled(
+                    shell.env().CWD().collapse(home, truncation),
+                    bold(self.theme.cwd),
+                ),
+                // * Outside of a repository, the segment is left out entirely
+                PromptSegment::Git => match self.git_status.status() {
+                    Some(status) => Span::styled(status.to_string(), bold(self.theme.git)),
+                    None => continue,
+                },
+                PromptSegment::Status => match shell.success() {
+                    true => Span::styled("✔", bold(self.theme.success)),
+                    false => Span::styled("✘", bold(self.theme.failure)),
+                },
+            };
+
+            span_list.push(span);
+        }
+
+        self.prompt = Spans::from(span_list);
+
+        // Color the prompt tick based on the last shell command's exit status
+        let color = match shell.success() {
+            true => self.theme.success,
+            false => self.theme.failure,
+        };
+
+        self.prompt_tick = tick(shell, color, true);
+    }
+
+    // Reads the settings that the console keeps its own copy of from the configuration
+    fn load_config(&mut self, config: &Configuration) {
+        self.theme = config.theme();
+        self.keymap = config.keymap.clone();
+        self.color_support = config.color_support();
+        self.placeholder = config.placeholder.clone();
+        self.scrollback_limit = config.scrollback_lines;
+        self.hyperlinks = config.hyperlinks;
+    }
+
+    // Updates the debug panel based on the section that is shown and the current state of the console and the shell
+    fn update_debug(&mut self, shell: &Shell) {
+        let Some(section) = self.debug_section else {
+            return;
+        };
+
+        let key_style = Style::default().add_modifier(Modifier::BOLD);
+        let value_style = Style::default().fg(self.theme.debug_value);
+        let note_style = Style::default().add_modifier(Modifier::ITALIC | Modifier::DIM);
+
+        let get_spans = |key: &str, value: &dyn Debug| {
+            Spans::from(vec![
+                Span::styled(key.to_string(), key_style),
+                Span::styled(format!(" {:?}", value), value_style),
+            ])
+        };
+
+        let lines = match section {
+            DebugSection::Console => vec![
+                get_spans("LINE BUFFER:", &self.editor.text()),
+                get_spans("CURSOR INDEX:", &self.editor.cursor()),
+                get_spans("SELECTION:", &self.editor.selection()),
+                get_spans("AUTOCOMPLETE BUFFER:", &self.autocomplete_buffer),
+                get_spans("HISTORY:", &self.history),
+                Spans::default(),
+                get_spans("OUTPUT BUFFER LENGTH:", &self.output_buffer.len()),
+                get_spans("DROPPED LINES:", &self.dropped_lines),
+                get_spans("SCROLLBACK LIMIT:", &self.scrollback_limit),
+                get_spans("SCROLL:", &self.scroll),
+                get_spans("OUTPUT WIDTH:", &self.output_width),
+                get_spans("OUTPUT HEIGHT:", &self.output_height),
+                Spans::default(),
+                get_spans("KILL RING:", &self.editor.kill_ring()),
+                get_spans("LAST RESOURCE USAGE:", &shell.last_resource_usage()),
+            ],
+            DebugSection::Environment => {
+                let mut lines = vec![
+                    get_spans("CWD:", &shell.env().CWD()),
+                    get_spans("DIRECTORY STACK:", &shell.env().directory_stack()),
+                    Spans::default(),
+                ];
+                lines.extend(
+                    shell
+                        .env()
+                        .all_vars()
+                        .iter()
+                        .map(|(name, value)| get_spans(&format!("{}:", name), value)),
+                );
+                lines
+            }
+            DebugSection::Configuration => config::setting_keys()
+                .map(|key| {
+                    let value = shell.config().get(&key).unwrap_or_default();
+                    get_spans(&format!("{}:", key.to_uppercase()), &value)
+                })
+                .collect(),
+            DebugSection::LastError => match shell.last_error() {
+                Some(last_error) => {
+                    let mut lines = vec![
+                        get_spans("SUCCESS:", &shell.success()),
+                        get_spans("COMMAND:", &last_error.command),
+                        get_spans("ERROR:", &last_error.error.to_string()),
+                    ];
+                    // * Each cause is the error that the one before it was created from
+                    lines.extend(
+                        last_error
+                            .error
+                            .chain()
+                            .skip(1)
+                            .map(|cause| get_spans("CAUSED BY:", &cause.to_string())),
+                    );
+                    lines
+                }
+                None => vec![Spans::from(Span::styled("No errors yet", note_style))],
+            },
+            DebugSection::Events => match self.event_log.is_empty() {
+                true => vec![Spans::from(Span::styled("No events yet", note_style))],
+                false => self
+                    .event_log
+                    .iter()
+                    .rev()
+                    .map(|event| Spans::from(event.clone()))
+                    .collect(),
+            },
+        };
+
+        self.debug_buffer = Text::from(lines);
+    }
+
+    // Shows the next section of the debug panel, showing the first one if the panel is hidden,
+    // and hiding the panel after the last one
+    fn cycle_debug_section(&mut self) {
+        self.debug_section = match self.debug_section {
+            Some(section) => section.next(),
+            None => Some(DebugSection::ALL[0]),
+        };
+        self.debug_scroll = 0;
+    }
+
+    // Scrolls the debug panel by a page, down if 'down' is true and up otherwise
+    fn scroll_debug_page(&mut self, down: bool) {
+        self.debug_scroll = match down {
+            true => self.debug_scroll + self.page_height(),
+            false => self.debug_scroll.saturating_sub(self.page_height()),
+        };
+    }
+
+    // Replaces the line buffer with a command line so that it is run as if the user had typed it
+    // * Whatever was being typed is moved to the kill ring, so that it can be pasted back afterwards
+    fn submit_command(&mut self, command: String) -> ReplAction {
+        self.editor.kill_line();
+        self.editor.set_text(command);
+        ReplAction::Return
+    }
+
+    // Remembers links printed by a child process, forgetting the oldest ones once there are too many
+    fn remember_links(&mut self, links: Vec<ExplicitLink>) {
+        for link in links {
+            // * The same link is often printed many times, so it is only remembered once
+            self.explicit_links.retain(|other| other != &link);
+            self.explicit_links.push_back(link);
+        }
+
+        while self.explicit_links.len() > EXPLICIT_LINK_LIMIT {
+            self.explicit_links.pop_front();
+        }
+    }
+
+    // Adds an event to the event log, dropping the oldest event if the log is full
+    fn log_event(&mut self, event: String) {
+        let elapsed = self.start_time.elapsed().as_secs_f64();
+        self.event_log
+            .push_back(format!("[{:>9.3}s] {}", elapsed, event));
+        if self.event_log.len() > EVENT_LOG_SIZE {
+            self.event_log.pop_front();
+        }
+    }
+
+    // Updates the autocomplete buffer based on the current line buffer and the command history
+    fn update_autocomplete(&mut self, shell: &Shell) {
+        // If the current line buffer matches any of the commands in the history, put the rest of the command in the autocomplete buffer
+        // Otherwise, clear the autocomplete buffer
+        let line = self.editor.text();
+        if !line.is_empty() {
+            for command in &shell.command_history {
+                if command.starts_with(line) && command != line {
+                    let rest_of_command = command.strip_prefix(line).unwrap();
+                    self.autocomplete_buffer = Some(rest_of_command.to_string());
+                    return;
+                }
+            }
+        }
+
+        self.autocomplete_buffer = None;
+    }
+
+    // Updates the argument hint based on the command before the cursor
+    // * Only the command after the last '&&' is looked at, since that is the one being typed
+    fn update_argument_hint(&mut self, shell: &Shell) {
+        let line = &self.editor.text()[..self.editor.cursor()];
+        let command = line.rsplit("&&").next().unwrap_or_default();
+        let mut words: Vec<&str> = command.split_whitespace().collect();
+        // * A trailing space means that the last word is finished, and a new one is about to be typed
+        if command.ends_with(char::is_whitespace) {
+            words.push("");
+        }
+
+        self.argument_hint = match words.split_first() {
+            // * Nothing is shown until the command name is finished, so that the hint does not flicker while it is typed
+            Some((name, arguments)) if !arguments.is_empty() => shell
+                .builtin_signatures
+                .get(*name)
+                .map(|signature| argument_hint(name, signature, arguments, &self.theme)),
+            _ => None,
+        };
+    }
+
+    // Generates a TUI frame based on the prompt/line buffer and output buffer
+    // ? Is there a way to make this a method to avoid passing in a ton of parameters?
+    fn generate_frame(&mut self, f: &mut Frame<ConsoleBackend>, autoscroll: bool) {
+        let border_style = Style::default().fg(self.theme.border);
+        let border_type = self.theme.border_type;
+        // While a command is running, a spinner and the elapsed time are added to the prompt panel title
+        let mut prompt_title = self.prompt.clone();
+        if let Some(running_since) = self.running_since {
+            let elapsed = running_since.elapsed();
+            let frame = (elapsed.as_millis() / SPINNER_INTERVAL.as_millis()) as usize;
+            prompt_title.0.push(Span::styled(
+                format!(
+                    " {} {}",
+                    SPINNER_FRAMES[frame % SPINNER_FRAMES.len()],
+                    format_elapsed(elapsed)
+                ),
+                Style::default().fg(self.theme.pending),
+            ));
+        }
+
+        let prompt_borders = Block::default()
+            .borders(Borders::ALL)
+            .border_style(border_style)
+            .border_type(border_type)
+            .title(prompt_title);
+        let mut line = Spans::from(vec![self.prompt_tick.clone()]);
+        // While searching the history, the query is shown before the match
+        if let Some(search) = &self.history_search {
+            let label = match search.failing {
+                true => "failing search",
+                false => "search",
+            };
+
+            line.0.push(Span::styled(
+                format!("({}) '{}': ", label, search.query),
+                Style::default().add_modifier(Modifier::DIM),
+            ));
+        }
+
+        // * The cursor is placed by counting the columns before it, starting from the beginning of the line
+        let cursor_offset = match (&self.output_search, &self.output_selection) {
+            // While link hints are shown, instructions take the place of the line buffer
+            _ if self.link_hints => {
+                line.0.push(Span::styled(
+                    "(open link) Press the key next to a link to open it, or Esc to cancel",
+                    Style::default().add_modifier(Modifier::DIM),
+                ));
+                line.width()
+            }
+            // While selecting output, instructions take the place of the line buffer
+            (_, Some(selection)) => {
+                let (first, last) = selection.range();
+                line.0.push(Span::styled(
+                    format!(
+                        "(select {}) Up/Down: move, Space: start here, Enter: copy, Esc: cancel",
+                        line_count(last - first + 1)
+                    ),
+                    Style::default().add_modifier(Modifier::DIM),
+                ));
+                line.width()
+            }
+            // While searching the output, the search takes the place of the line buffer, with the cursor after the query
+            (Some(search), None) => {
+                let position = match (search.selected, search.query.is_empty()) {
+                    (_, true) => String::new(),
+                    (Some(selected), false) => {
+                        format!(" {}/{}", selected + 1, search.matches.len())
+                    }
+                    (None, false) => String::from(" failing"),
+                };
+
+                line.0.push(Span::styled(
+                    format!("(find{}) ", position),
+                    Style::default().add_modifier(Modifier::DIM),
+                ));
+                line.0.push(Span::from(search.query.clone()));
+                line.width()
+            }
+            (None, None) => {
+                let text = self.editor.text();
+                let cursor_offset = line.width() + text[..self.editor.cursor()].width();
+                // * The selection is drawn in reverse video, like the selection in the output panel
+                match self.editor.selection() {
+                    Some(selection) => {
+                        line.0.push(Span::from(text[..selection.start].to_string()));
+                        line.0.push(Span::styled(
+                            text[selection.clone()].to_string(),
+                            Style::default().add_modifier(Modifier::REVERSED),
+                        ));
+                        line.0.push(Span::from(text[selection.end..].to_string()));
+                    }
+                    None => line.0.push(Span::from(text.to_string())),
+                }
+                if let Some(autocomplete) = &self.autocomplete_buffer {
+                    line.0.push(Span::styled(
+                        autocomplete.clone(),
+                        Style::default().add_modifier(Modifier::ITALIC | Modifier::DIM),
+                    ));
+                } else if let Some(placeholder) =
+                    self.placeholder.as_ref().filter(|_| text.is_empty())
+                {
+                    line.0.push(Span::styled(
+                        placeholder.clone(),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ));
+                }
+
+                cursor_offset
+            }
+        };
+
+        // Split the terminal into two windows, one for the command output, and one for the prompt
+        // The output window takes up the top 85% of the terminal, and the prompt window takes up the bottom 15%
+        // If the debug panel is enabled, the output window will be split in 60/40 sections
+        let (output_window, prompt_area) = split_frame(f.size());
+
+        // The line is wrapped to fit the prompt panel, and scrolled so that the row with the cursor is always visible
+        // * The -2 is to account for the borders
+        let prompt_width = (prompt_area.width.saturating_sub(2) as usize).max(1);
+        let prompt_height = prompt_area.height.saturating_sub(2) as usize;
+        let (rows, (cursor_row, cursor_column)) = wrap_line(&line, prompt_width, cursor_offset);
+        let mut prompt_text = Text::from(rows);
+        let prompt_scroll = (cursor_row + 1).saturating_sub(prompt_height);
+
+        // The completion menu goes below the line buffer, in the otherwise unused space of the prompt panel
+        // Otherwise, the argument hint goes there, unless something else has taken the place of the line buffer
+        if let Some(menu) = &self.completion_menu {
+            let height = prompt_height.saturating_sub(prompt_text.lines.len());
+            prompt_text
+                .lines
+                .extend(menu.rows(prompt_width, height, &self.theme));
+        } else if let Some(hint) = &self.argument_hint {
+            if !self.link_hints && self.output_search.is_none() && self.output_selection.is_none() {
+                prompt_text.lines.push(hint.clone());
+            }
+        }
+
+        // Create a Paragraph widget for the prompt panel
+        // * The line is already wrapped, so the Paragraph must not wrap it again
+        let prompt_widget = Paragraph::new(prompt_text)
+            .block(prompt_borders)
+            .style(Style::default())
+            .alignment(Alignment::Left)
+            .scroll((prompt_scroll as u16, 0));
+
+        // Drop the oldest lines of output if there are more than the scrollback can hold
+        self.trim_output();
+
+        // If the debug panel or the job pane is open, it takes up part of the output window
+        let (output_area, job_area, debug_area) = self.split_output_window(output_window);
+        self.set_output_size(output_area);
+
+        // If autoscroll is enabled and the output panel is following the output, scroll to the bottom of the output buffer
+        // Otherwise, make sure the output has not been scrolled past the end (e.g. if it was cleared)
+        match autoscroll && self.following {
+            true => self.scroll_to_bottom(),
+            false => self.clamp_scroll(),
+        }
+
+        let window = OutputWindow {
+            revision: self.output_revision,
+            scroll: self.scroll,
+            width: self.output_width,
+            height: self.output_height,
+            selection: self
+                .output_selection
+                .as_ref()
+                .map(|selection| (selection.anchor, selection.cursor)),
+            search: self
+                .output_search
+                .as_ref()
+                .map(|search| (search.query.clone(), search.selected)),
+            focused: self.job_pane.is_some() && !self.job_pane_focused,
+            theme: self.theme.clone(),
+        };
+
+        // * Building the output panel means cloning and highlighting every visible line, so it is only done when something changed
+        let output_widget = match &self.output_cache {
+            Some((cached_window, widget)) if *cached_window == window => widget.clone(),
+            _ => {
+                let widget = self.build_output_widget();
+                self.output_cache = Some((window, widget.clone()));
+                widget
+            }
+        };
+
+        if let (Some(section), Some(debug_area)) = (self.debug_section, debug_area) {
+            // * The scroll is clamped here because the length of the section changes as it is updated
+            let max_debug_scroll = self.debug_buffer.lines.len().saturating_sub(1);
+            self.debug_scroll = self.debug_scroll.min(max_debug_scroll);
+
+            // Create a Paragraph widget for the debug panel, titled with the section and its position among the sections
+            let debug_title = format!(
+                "Debug: {} ({}/{})",
+                section.title(),
+                section.index() + 1,
+                DebugSection::ALL.len()
+            );
+            let debug_widget = Paragraph::new(self.debug_buffer.clone())
+                .block(self.panel_borders(debug_title))
+                .style(Style::default())
+                .alignment(Alignment::Left)
+                .wrap(Wrap { trim: false })
+                .scroll((self.debug_scroll as u16, 0));
+
+            // Render the debug panel widget
+            f.render_widget(debug_widget, debug_area)
+        }
+
+        if let (Some(pane), Some(job_area)) = (&mut self.job_pane, job_area) {
+            // * The -1 is for the top border, since the pane has no bottom border
+            pane.height = (job_area.height as usize).saturating_sub(1);
+            let job_widget = self.build_job_widget();
+            f.render_widget(job_widget, job_area);
+        }
+
+        // Render the default widgets
+        self.output_text_area = self.panel_borders(String::new()).inner(output_area);
+        f.render_widget(prompt_widget, prompt_area);
+        f.render_widget(output_widget, output_area);
+
+        // While the output panel is not following the output, a marker in its corner shows that more has been printed below
+        let text_area = self.output_text_area;
+        if self.has_unseen_output() && text_area.height > 0 {
+            let width = (NEW_OUTPUT_MARKER.width() as u16).min(text_area.width);
+            let marker_area =
+                Rect::new(text_area.right() - width, text_area.bottom() - 1, width, 1);
+            let marker_style = Style::default()
+                .fg(self.theme.title)
+                .add_modifier(Modifier::REVERSED | Modifier::BOLD);
+            let marker = Paragraph::new(Span::styled(NEW_OUTPUT_MARKER, marker_style));
+            f.render_widget(marker, marker_area);
+        }
+
+        // While link hints are shown, each link is labeled with the key that opens it
+        if self.link_hints {
+            let hint_style = Style::default()
+                .fg(self.theme.title)
+                .add_modifier(Modifier::REVERSED | Modifier::BOLD);
+            for (link, key) in self.visible_links.iter().zip(LINK_HINT_KEYS.chars()) {
+                let hint = Paragraph::new(Span::styled(key.to_string(), hint_style));
+                f.render_widget(hint, Rect::new(link.x, link.y, 1, 1));
+            }
+        }
+
+        // * This must come after everything else is rendered, since it changes the colors of what is already there
+        if self.color_support != ColorSupport::TrueColor {
+            f.render_widget(ColorConversion(self.color_support), f.size());
+        }
+
+        // Render the cursor at the edit position, as long as a line is being edited and the position fits in the prompt panel
+        // * If the cursor is not set, it is hidden
+        if self.editing
+            && cursor_column < prompt_width
+            && cursor_row - prompt_scroll < prompt_height
+        {
+            // * The +1 is to account for the left and top borders
+            let cursor_x = prompt_area.x as usize + 1 + cursor_column;
+            let cursor_y = prompt_area.y as usize + 1 + cursor_row - prompt_scroll;
+            f.set_cursor(cursor_x as u16, cursor_y as u16);
+        }
+    }
+
+    // Creates the borders of the output and debug panels, with the given title
+    fn panel_borders(&self, title: String) -> Block<'a> {
+        let title_style = Style::default()
+            .fg(self.theme.title)
+            .add_modifier(Modifier::BOLD);
+        Block::default()
+            .borders(Borders::ALL ^ Borders::BOTTOM)
+            .border_style(Style::default().fg(self.theme.border))
+            .border_type(self.theme.border_type)
+            .title(Span::styled(title, title_style))
+    }
+
+    // Builds the output panel for the current scroll position, highlighting the selection and the search matches
+    fn build_output_widget(&self) -> Paragraph<'a> {
+        // Create a Paragraph widget for the output panel
+        // If there is more output than fits in the panel, the title shows the last visible line and the total number of lines,
+        // along with the command that the line is output from
+        let total_lines = self.output_buffer.len();
+        let output_title = match self.bottom_scroll() == ScrollPosition::default() {
+            true => String::from("Output"),
+            false => {
+                let last_line = self.rows_after(self.scroll, self.page_height() - 1).line;
+                let title = format!("Output [{}/{}]", last_line + 1, total_lines);
+                match self.section_at(last_line) {
+                    Some(index) => format!("{} - {}", title, self.sections[index].command),
+                    None => title,
+                }
+            }
+        };
+
+        // * Only the lines that can be visible are given to the Paragraph, so that drawing does not slow down as the output grows
+        let mut visible_lines: Vec<Spans> = self
+            .output_buffer
+            .range(self.scroll.line..)
+            .take(self.output_height)
+            .cloned()
+            .collect();
+
+        // While selecting output, the selected lines are highlighted, and the line with the selection cursor stands out
+        if let Some(selection) = &self.output_selection {
+            let (first, last) = selection.range();
+            for (index, line) in visible_lines.iter_mut().enumerate() {
+                let index = self.scroll.line + index;
+                if index < first || index > last {
+                    continue;
+                }
+
+                let mut style = Style::default().add_modifier(Modifier::REVERSED);
+                if index == selection.cursor {
+                    style = style.fg(self.theme.title);
+                }
+
+                // * Empty lines are given a space so that the highlight can be seen
+                if line.width() == 0 {
+                    line.0.push(Span::raw(" "));
+                }
+
+                for span in &mut line.0 {
+                    span.style = span.style.patch(style);
+                }
+            }
+        }
+
+        // While searching the output, every occurrence of the query is highlighted, and the ones on the selected line stand out
+        if let Some(search) = self
+            .output_search
+            .as_ref()
+            .filter(|search| !search.query.is_empty())
+        {
+            let selected_line = search.selected.map(|selected| search.matches[selected]);
+            for (index, line) in visible_lines.iter_mut().enumerate() {
+                let style = match Some(self.scroll.line + index) == selected_line {
+                    true => Style::default()
+                        .fg(self.theme.title)
+                        .add_modifier(Modifier::REVERSED | Modifier::BOLD),
+                    false => Style::default().add_modifier(Modifier::REVERSED),
+                };
+
+                *line = highlight_matches(line, &search.query, style);
+            }
+        }
+
+        // The lines are wrapped here rather than by the Paragraph, so that the panel can start partway through a line
+        let rows: Vec<Spans> = visible_lines
+            .iter()
+            .flat_map(|line| wrap_line(line, self.output_width, 0).0)
+            .skip(self.scroll.row)
+            .take(self.output_height)
+            .collect();
+
+        // While the job pane is open, the border of whichever panel has focus is highlighted
+        let mut borders = self.panel_borders(output_title);
+        if self.job_pane.is_some() && !self.job_pane_focused {
+            borders = borders.border_style(Style::default().fg(self.theme.title));
+        }
+
+        Paragraph::new(rows)
+            .block(borders)
+            .style(Style::default())
+            .alignment(Alignment::Left)
+    }
+
+    // Adjusts to a new terminal size before the next frame is drawn
+    // If the output panel was scrolled to the bottom, it stays at the bottom, and otherwise it is kept in bounds
+    fn resize(&mut self, area: Rect) {
+        let at_bottom = self.scroll >= self.bottom_scroll();
+        let (output_window, _) = split_frame(area);
+        let (output_area, _, _) = self.split_output_window(output_window);
+        self.set_output_size(output_area);
+
+        match at_bottom {
+            true => self.scroll_to_bottom(),
+            false => self.clamp_scroll(),
+        }
+    }
+
+    // Splits the part of the frame above the prompt panel between the output panel, the job pane, and the debug panel
+    // * The debug panel goes beside the other two, and the job pane goes below the output panel
+    fn split_output_window(&self, area: Rect) -> (Rect, Option<Rect>, Option<Rect>) {
+        let (area, debug_area) = match self.debug_section {
+            Some(_) => {
+                let chunks = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+                    .split(area);
+                (chunks[0], Some(chunks[1]))
+            }
+            None => (area, None),
+        };
+
+        match self.job_pane {
+            Some(_) => {
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(area);
+                (chunks[0], Some(chunks[1]), debug_area)
+            }
+            None => (area, None, debug_area),
+        }
+    }
+
+    // Builds the job pane, showing the lines of the job's output that fit, from the scroll position
+    fn build_job_widget(&self) -> Paragraph<'a> {
+        let Some(pane) = &self.job_pane else {
+            return Paragraph::new("");
+        };
+
+        let lines = pane.log.lines();
+        let end = lines.len() - pane.scroll_back.min(lines.len());
+        let start = end.saturating_sub(pane.height);
+        let visible_lines: Vec<&str> = lines.range(start..end).map(String::as_str).collect();
+
+        // * Only the visible lines are parsed for color codes, since the job can print a lot of output
+        let text = match visible_lines.join("\n").into_text() {
+            Ok(text) => text,
+            Err(_) => Text::from(visible_lines.join("\n")),
+        };
+
+        let title = match pane.scroll_back {
+            0 => format!("Job {} - {}", pane.id, pane.command),
+            _ => format!(
+                "Job {} [{}/{}] - {}",
+                pane.id,
+                end,
+                lines.len(),
+                pane.command
+            ),
+        };
+
+        let mut borders = self.panel_borders(title);
+        if self.job_pane_focused {
+            borders = borders.border_style(Style::default().fg(self.theme.title));
+        }
+
+        Paragraph::new(text)
+            .block(borders)
+            .style(Style::default())
+            .alignment(Alignment::Left)
+    }
+
+    // Reads any new output from the job shown in the job pane, returning true if the pane needs to be redrawn
+    // * The log is only checked every so often, since this is called whenever the console waits
+    fn poll_job_pane(&mut self) -> bool {
+        let Some(pane) = &mut self.job_pane else {
+            return false;
+        };
+
+        if pane.last_poll.elapsed() < JOB_PANE_POLL_INTERVAL {
+            return false;
+        }
+
+        pane.last_poll = Instant::now();
+        let length = pane.log.lines().len();
+        if !pane.log.poll() {
+            return false;
+        }
+
+        // * If the pane has been scrolled up, it is scrolled further to keep showing the same lines
+        if pane.scroll_back > 0 {
+            pane.scroll_back += pane.log.lines().len().saturating_sub(length);
+        }
+
+        true
     }
 
-    // Updates the TUI frame
-    // ? Should the autoscroll parameter use a custom type for readability?
-    pub fn draw_frame(&mut self, autoscroll: bool) -> Result<()> {
-        self.terminal
-            .draw(|f| self.data.generate_frame(f, autoscroll))?;
-        Ok(())
+    // Scrolls the job pane up (positive) or down (negative) by the given number of lines
+    fn scroll_job_pane(&mut self, lines: isize) {
+        let Some(pane) = &mut self.job_pane else {
+            return;
+        };
+
+        let max_scroll_back = pane.log.lines().len().saturating_sub(pane.height);
+        pane.scroll_back = pane
+            .scroll_back
+            .saturating_add_signed(lines)
+            .min(max_scroll_back);
     }
 
-    // Clears the screen and the line buffer and reprompts the user
-    fn clear(&mut self, mode: ClearMode) -> Result<()> {
-        // Clear the output panel
-        if mode.contains(ClearMode::OUTPUT) {
-            self.data.output_buffer = Text::default();
+    // Scrolls the job pane up or down by a page
+    fn scroll_job_pane_page(&mut self, up: bool) {
+        let page = self.job_pane.as_ref().map_or(1, |pane| pane.height.max(1)) as isize;
+        match up {
+            true => self.scroll_job_pane(page),
+            false => self.scroll_job_pane(-page),
         }
+    }
 
-        if mode.contains(ClearMode::LINE) {
-            self.data.reset_line_buffer();
-            self.data.cursor_index = 0;
-        }
+    // Remembers the size of the output panel, which is needed to know how lines are wrapped when scrolling
+    // * The -2 is for the left and right borders
+    fn set_output_size(&mut self, area: Rect) {
+        self.output_width = (area.width as usize).saturating_sub(2);
+        self.output_height = area.height as usize;
+    }
 
-        Ok(())
+    // Starts selecting output, with the selection cursor on the last line that has any text
+    fn start_selection(&mut self) {
+        let Some(last_line) = self.output_buffer.iter().rposition(|line| line.width() > 0) else {
+            return;
+        };
+
+        self.output_selection = Some(OutputSelection {
+            anchor: last_line,
+            cursor: last_line,
+        });
+        self.scroll_to_line(last_line);
     }
 
-    // Clears the output panel
-    // * This is a public wrapper for the clear() method
-    pub fn clear_output(&mut self) -> Result<()> {
-        self.clear(ClearMode::OUTPUT)
+    // Moves the selection cursor up (negative) or down (positive) by the given number of lines, keeping it visible
+    fn move_selection_cursor(&mut self, lines: isize) {
+        let Some(selection) = &mut self.output_selection else {
+            return;
+        };
+
+        let last_line = self.output_buffer.len().saturating_sub(1);
+        selection.cursor = selection.cursor.saturating_add_signed(lines).min(last_line);
+        let cursor = selection.cursor;
+        self.scroll_to_line(cursor);
     }
 
-    // Prints a line of text to the console
-    // TODO: Probably make this a macro in the future, but for now just make it use &str or String
-    // TODO: Make lazy execution version of this, or a lazy execution mode
-    pub fn println(&mut self, text: &str) {
-        self.data.append_str_newline(text);
-        _ = self.draw_frame(true)
+    // Copies the selected lines to the clipboard and stops selecting, reporting the result in the output panel
+    fn copy_selection(&mut self) {
+        let Some(selection) = self.output_selection.take() else {
+            return;
+        };
+
+        let (first, last) = selection.range();
+        let text = self
+            .output_buffer
+            .range(first..=last)
+            .map(line_text)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        match clipboard::copy(&text) {
+            Ok(_) => self.append_str_newline(&format!(
+                "Copied {} to the clipboard",
+                line_count(last - first + 1)
+            )),
+            Err(e) => self.append_str_newline(&format!("Failed to copy to the clipboard: {}", e)),
+        }
+
+        self.enforce_spacing();
+        self.scroll_to_bottom();
     }
 
-    // Prints a line of text to the console without a newline
-    pub fn print(&mut self, text: &str) {
-        self.data.append_str(text);
-        _ = self.draw_frame(true)
+    // Gets the index of the section that the line at the given index in the output buffer belongs to, if any
+    fn section_at(&self, line: usize) -> Option<usize> {
+        let position = line + self.dropped_lines;
+        self.sections
+            .partition_point(|section| section.start <= position)
+            .checked_sub(1)
     }
-}
 
-impl Drop for Console<'_> {
-    fn drop(&mut self) {
-        restore_terminal();
+    // Gets the indices in the output buffer of the lines that are hidden when the section is collapsed
+    // * Blank lines at the end are left out, so that the spacing before the next command (or the next output) is kept
+    fn section_body(&self, index: usize) -> Range<usize> {
+        let header = self.sections[index].start - self.dropped_lines;
+        let mut end = match self.sections.get(index + 1) {
+            Some(next) => next.start - self.dropped_lines,
+            None => self.output_buffer.len(),
+        };
+
+        while end > header + 1 && self.output_buffer[end - 1].width() == 0 {
+            end -= 1;
+        }
+
+        header + 1..end
     }
-}
 
-impl<'a> ConsoleData<'a> {
-    fn new() -> Self {
-        Self {
-            prompt: Spans::default(),
-            prompt_tick: Span::styled(
-                "❯ ",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::LightGreen),
-            ),
-            success_tick_index: None,
-            line_buffer: String::new(),
-            output_buffer: Text::default(),
-            debug_buffer: Text::default(),
-            cursor_index: 0,
-            autocomplete_buffer: None,
-            history: None,
-            scroll: 0,
-            debug_mode: false,
+    // Collapses the section that the last visible line belongs to, or expands it if it is already collapsed
+    fn toggle_section(&mut self) {
+        let last_line = self.rows_after(self.scroll, self.page_height() - 1).line;
+        if let Some(index) = self.section_at(last_line) {
+            let collapsed = self.sections[index].folded.is_some();
+            self.fold_section(index, !collapsed);
         }
     }
 
-    // Recolors the command output tick based on the command's exit status
-    fn update_output_tick(&mut self, shell: &Shell) {
-        // Get the tick from the output buffer
-        // If the tick exists, it will be the first Span in the indexed Spans
-        let tick = {
-            if let Some(index) = self.success_tick_index {
-                if let Some(line) = self.output_buffer.lines.get_mut(index) {
-                    line.0.first_mut()
-                } else {
+    // Collapses every section, or expands them all if none of them can be collapsed any further
+    fn toggle_all_sections(&mut self) {
+        let collapse = (0..self.sections.len()).any(|index| {
+            self.sections[index].folded.is_none() && !self.section_body(index).is_empty()
+        });
+
+        for index in 0..self.sections.len() {
+            self.fold_section(index, collapse);
+        }
+    }
+
+    // Collapses or expands a section, keeping the same output in view
+    fn fold_section(&mut self, index: usize, collapse: bool) {
+        if collapse == self.sections[index].folded.is_some() {
+            return;
+        }
+
+        // * The line numbers of the selection and the search matches would no longer be right, so they are closed
+        self.output_selection = None;
+        self.output_search = None;
+
+        let at_bottom = self.scroll >= self.bottom_scroll();
+        let header = self.sections[index].start - self.dropped_lines;
+        let (removed, added) = match self.sections[index].folded.take() {
+            None => {
+                let body = self.section_body(index);
+                if body.is_empty() {
                     return;
                 }
-            } else {
-                return;
+
+                let lines: Vec<_> = self.output_mut().drain(body).collect();
+                self.output_buffer[header].0.push(Span::styled(
+                    format!(" [{} hidden]", line_count(lines.len())),
+                    Style::default().add_modifier(Modifier::DIM),
+                ));
+
+                let removed = lines.len();
+                self.sections[index].folded = Some(lines);
+                (removed, 0)
+            }
+            Some(lines) => {
+                let added = lines.len();
+                let buffer = self.output_mut();
+                buffer[header].0.pop();
+                let rest = buffer.split_off(header + 1);
+                buffer.extend(lines);
+                buffer.extend(rest);
+                (0, added)
             }
         };
 
-        let color = match shell.success() {
-            true => Color::LightGreen,
-            false => Color::LightRed,
-        };
+        let shift = |position: usize| position + added - removed;
+        for section in self.sections.range_mut(index + 1..) {
+            section.start = shift(section.start);
+        }
 
-        // * If the tick is None, this is an extraneous call made before a command has been executed, and should be ignored
-        if let Some(tick) = tick {
-            tick.style = tick.style.fg(color);
+        if let Some(tick_index) = self
+            .success_tick_index
+            .filter(|&i| i > self.sections[index].start)
+        {
+            self.success_tick_index = Some(shift(tick_index));
         }
-    }
 
-    // Updates the prompt panel header based on the current shell state (USER, CWD, etc)
-    // TODO: This will eventually need to not be hard-coded to allow for user customization
-    fn update_prompt(&mut self, shell: &Shell) {
-        let mut span_list = Vec::new();
+        // If the output panel was scrolled to the bottom, it stays at the bottom
+        if at_bottom {
+            self.scroll = self.bottom_scroll();
+        } else if self.scroll.line > header {
+            self.scroll = match self.scroll.line.checked_sub(removed) {
+                Some(line) if line > header => ScrollPosition {
+                    line: line + added,
+                    ..self.scroll
+                },
+                _ => ScrollPosition {
+                    line: header,
+                    row: 0,
+                },
+            };
+        }
 
-        let home = shell.env().HOME();
-        let truncation = shell.config().truncation_factor;
-        // $ RGB values do not work on some terminals
-        let user = Span::styled(
-            shell.env().USER().clone(),
-            Style::default()
-                .fg(Color::Rgb(0, 150, 255))
-                .add_modifier(Modifier::BOLD),
-        );
-        let cwd = Span::styled(
-            shell.env().CWD().collapse(home, truncation),
-            Style::default()
-                .fg(Color::Rgb(0, 255, 0))
-                .add_modifier(Modifier::BOLD),
-        );
+        self.clamp_scroll();
+    }
 
-        span_list.push(user);
-        span_list.push(Span::from(" on "));
-        span_list.push(cwd);
+    // Scrolls the output panel as little as possible so that the given line is visible
+    fn scroll_to_line(&mut self, line: usize) {
+        let start = ScrollPosition { line, row: 0 };
+        let end = ScrollPosition {
+            line,
+            row: self.rows_in(line) - 1,
+        };
 
-        self.prompt = Spans::from(span_list);
+        // * If the line is below the panel, it is scrolled to the bottom of the panel
+        let bottom_aligned = self.rows_before(end, self.page_height() - 1);
+        if start < self.scroll {
+            self.scroll = start;
+        } else if bottom_aligned > self.scroll {
+            self.scroll = bottom_aligned.min(self.bottom_scroll());
+        }
+    }
 
-        // Color the prompt tick based on the last shell command's exit status
-        match shell.success() {
-            true => self.prompt_tick.style = self.prompt_tick.style.fg(Color::LightGreen),
-            false => self.prompt_tick.style = self.prompt_tick.style.fg(Color::LightRed),
+    // Adds a character to the output search query, or removes the last one, and jumps to the newest matching line
+    fn edit_output_search(&mut self, c: Option<char>) {
+        let Some(search) = &mut self.output_search else {
+            return;
+        };
+
+        match c {
+            Some(c) => search.query.push(c),
+            None => {
+                search.query.pop();
+            }
         }
+
+        let query = search.query.to_ascii_lowercase();
+        search.matches = match query.is_empty() {
+            true => Vec::new(),
+            false => self
+                .output_buffer
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| line_text(line).to_ascii_lowercase().contains(&query))
+                .map(|(index, _)| index)
+                .collect(),
+        };
+
+        search.selected = search.matches.len().checked_sub(1);
+        self.scroll_to_search_match();
     }
 
-    // Updates the debug panel header based on the current shell state (USER, CWD, etc)
-    fn update_debug(&mut self, shell: &Shell) {
-        // Tracked items:
-        // Console.line_buffer
-        // Console.cursor_index
-        // Console.autocomplete_buffer
-        // Console.history_buffer
-        // Console.history_index
-        // Console.output_buffer.length
-        // Console.scroll
-
-        // Shell.config.truncation_factor
-        // Shell.config.history_limit
-        // Shell.config.show_errors
-
-        // Shell.environment.USER
-        // Shell.environment.HOME
-        // Shell.environment.CWD
+    // Jumps to the next newer or older line that matches the output search, wrapping around at either end
+    fn step_output_search(&mut self, newer: bool) {
+        let Some(search) = &mut self.output_search else {
+            return;
+        };
 
-        let key_style = Style::default().add_modifier(Modifier::BOLD);
-        let value_style = Style::default().fg(Color::LightGreen);
+        let count = search.matches.len();
+        search.selected = search.selected.map(|selected| match newer {
+            true => (selected + 1) % count,
+            false => (selected + count - 1) % count,
+        });
 
-        let get_spans = |key, value: &dyn Debug| {
-            Spans::from(vec![
-                Span::styled(key, key_style),
-                Span::styled(format!(" {:?}", value), value_style),
-            ])
+        self.scroll_to_search_match();
+    }
+
+    // Scrolls the output panel so that the selected match is visible, centering it if it was not already visible
+    fn scroll_to_search_match(&mut self) {
+        let Some(line) = self
+            .output_search
+            .as_ref()
+            .and_then(|search| Some(search.matches[search.selected?]))
+        else {
+            return;
         };
 
-        let line_buffer = get_spans("LINE BUFFER:", &self.line_buffer);
-        let cursor_index = get_spans("CURSOR INDEX:", &self.cursor_index);
-        let autocomplete_buffer = get_spans("AUTOCOMPLETE BUFFER:", &self.autocomplete_buffer);
-        let history = get_spans("HISTORY:", &self.history);
-        let output_buffer_length =
-            get_spans("OUTPUT BUFFER LENGTH:", &self.output_buffer.lines.len());
-        let scroll = get_spans("SCROLL:", &self.scroll);
-
-        let truncation = get_spans("PROMPT TRUNCATION:", &shell.config().truncation_factor);
-        let history_limit = get_spans("HISTORY LIMIT:", &shell.config().history_limit);
-        let show_errors = get_spans("SHOW ERRORS:", &shell.config().show_errors);
-
-        let user = get_spans("USER:", &shell.env().USER());
-        let home = get_spans("HOME:", &shell.env().HOME());
-        let cwd = get_spans("CWD:", &shell.env().CWD());
-
-        self.debug_buffer = Text::from(vec![
-            line_buffer,
-            cursor_index,
-            autocomplete_buffer,
-            history,
-            output_buffer_length,
-            scroll,
-            Spans::default(),
-            truncation,
-            history_limit,
-            show_errors,
-            Spans::default(),
-            user,
-            home,
-            cwd,
-        ])
+        let start = ScrollPosition { line, row: 0 };
+        let last_visible = self.rows_after(self.scroll, self.page_height() - 1);
+        if start < self.scroll || start > last_visible {
+            self.scroll = self
+                .rows_before(start, self.page_height() / 2)
+                .min(self.bottom_scroll());
+        }
     }
 
-    // Updates the autocomplete buffer based on the current line buffer and the command history
-    fn update_autocomplete(&mut self, shell: &Shell) {
-        // If the current line buffer matches any of the commands in the history, put the rest of the command in the autocomplete buffer
-        // Otherwise, clear the autocomplete buffer
-        if !self.line_buffer.is_empty() {
-            for command in &shell.command_history {
-                if command.starts_with(&self.line_buffer) && command != &self.line_buffer {
-                    let rest_of_command = command.strip_prefix(&self.line_buffer).unwrap();
-                    self.autocomplete_buffer = Some(rest_of_command.to_string());
-                    return;
-                }
+    // Performs one of the actions that scroll the output panel (or the job pane, if it has focus), returning false for any other action
+    // * The output panel only follows new output while it is scrolled to the bottom, so scrolling up stops it and scrolling back down starts it again
+    fn perform_scroll(&mut self, action: ConsoleAction) -> bool {
+        match action {
+            ConsoleAction::ScrollUp if self.job_pane_focused => self.scroll_job_pane(1),
+            ConsoleAction::ScrollDown if self.job_pane_focused => self.scroll_job_pane(-1),
+            ConsoleAction::ScrollPageUp if self.job_pane_focused => self.scroll_job_pane_page(true),
+            ConsoleAction::ScrollPageDown if self.job_pane_focused => {
+                self.scroll_job_pane_page(false)
             }
+            ConsoleAction::FollowOutput if self.job_pane_focused => {
+                self.scroll_job_pane(isize::MIN)
+            }
+            ConsoleAction::ScrollUp => self.scroll_up(),
+            ConsoleAction::ScrollDown => self.scroll_down(),
+            ConsoleAction::ScrollPageUp => self.scroll_page_up(),
+            ConsoleAction::ScrollPageDown => self.scroll_page_down(),
+            ConsoleAction::FollowOutput => self.scroll_to_bottom(),
+            _ => return false,
         }
 
-        self.autocomplete_buffer = None;
+        let following = self.scroll >= self.bottom_scroll();
+        if self.following && !following {
+            self.unfollowed_end = self.output_position_end();
+        }
+
+        self.following = following;
+        true
     }
 
-    // Generates a TUI frame based on the prompt/line buffer and output buffer
-    // ? Is there a way to make this a method to avoid passing in a ton of parameters?
-    fn generate_frame(&mut self, f: &mut Frame<CrosstermBackend<Stdout>>, autoscroll: bool) {
-        let prompt_borders = Block::default()
-            .borders(Borders::ALL)
-            .title(self.prompt.clone());
-        let output_borders = |title| {
-            Block::default()
-                .borders(Borders::ALL ^ Borders::BOTTOM)
-                .title(Span::styled(
-                    title,
-                    Style::default()
-                        .fg(Color::LightCyan)
-                        .add_modifier(Modifier::BOLD),
-                ))
-        };
-
-        let mut line = Spans::from(vec![
-            self.prompt_tick.clone(),
-            Span::from(self.line_buffer.clone()),
-        ]);
-        if let Some(autocomplete) = &self.autocomplete_buffer {
-            line.0.push(Span::styled(
-                autocomplete.clone(),
-                Style::default().add_modifier(Modifier::ITALIC | Modifier::DIM),
-            ));
-        }
+    // Checks whether output has been printed below the view since the output panel stopped following the output
+    fn has_unseen_output(&self) -> bool {
+        !self.following && self.output_position_end() > self.unfollowed_end
+    }
 
-        // Create a Paragraph widget for the prompt panel
-        let prompt_widget = Paragraph::new(line)
-            .block(prompt_borders)
-            .style(Style::default())
-            .alignment(Alignment::Left)
-            .wrap(Wrap { trim: false });
+    // Scrolls down the output panel by one row
+    fn scroll_down(&mut self) {
+        self.scroll = self.rows_after(self.scroll, 1).min(self.bottom_scroll());
+    }
 
-        // Create a Paragraph widget for the output panel
-        let output_widget = Paragraph::new(self.output_buffer.clone())
-            .block(output_borders("Output"))
-            .style(Style::default())
-            .alignment(Alignment::Left)
-            .wrap(Wrap { trim: false });
+    // Scrolls up the output panel by one row
+    fn scroll_up(&mut self) {
+        self.scroll = self.rows_before(self.scroll, 1);
+    }
 
-        // Split the terminal into two windows, one for the command output, and one for the prompt
-        // The output window takes up the top 85% of the terminal, and the prompt window takes up the bottom 15%
-        // If the debug panel is enabled, the output window will be split in 60/40 sections
-        let (mut output_area, prompt_area) = {
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([Constraint::Percentage(85), Constraint::Percentage(15)])
-                .split(f.size());
-            (chunks[0], chunks[1])
-        };
+    // Scrolls down the output panel by one page
+    fn scroll_page_down(&mut self) {
+        self.scroll = self
+            .rows_after(self.scroll, self.page_height())
+            .min(self.bottom_scroll());
+    }
+
+    // Scrolls up the output panel by one page
+    fn scroll_page_up(&mut self) {
+        self.scroll = self.rows_before(self.scroll, self.page_height());
+    }
+
+    // Automatically scrolls to the bottom of the output panel text, following the output from then on
+    fn scroll_to_bottom(&mut self) {
+        self.scroll = self.bottom_scroll();
+        self.following = true;
+    }
 
-        // If autoscroll is enabled, scroll to the bottom of the output buffer
-        if autoscroll {
-            self.scroll_to_bottom(output_area.height as usize)
+    // Makes sure that the output panel is not scrolled past the end of the output, or past the end of a line that got shorter
+    fn clamp_scroll(&mut self) {
+        if self.scroll.line < self.output_buffer.len() {
+            self.scroll.row = self.scroll.row.min(self.rows_in(self.scroll.line) - 1);
         }
 
-        // If the debug panel is enabled, subdivide the output window
-        if self.debug_mode {
-            let (new_output_area, debug_area) = {
-                let chunks = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
-                    .split(output_area);
-                (chunks[0], chunks[1])
-            };
+        self.scroll = self.scroll.min(self.bottom_scroll());
+    }
 
-            output_area = new_output_area;
+    // Gets the number of output rows that are shown in the output panel at once
+    // * The -3 for is a bottom margin
+    // TODO: Make the bottom margin configurable
+    fn page_height(&self) -> usize {
+        self.output_height.saturating_sub(3).max(1)
+    }
 
-            // Create a Paragraph widget for the debug panel
-            let debug_widget = Paragraph::new(self.debug_buffer.clone())
-                .block(output_borders("Debug"))
-                .style(Style::default())
-                .alignment(Alignment::Left)
-                .wrap(Wrap { trim: false });
+    // Gets the furthest the output panel can be scrolled, which puts the last row at the bottom of the panel
+    fn bottom_scroll(&self) -> ScrollPosition {
+        let Some(line) = self.output_buffer.len().checked_sub(1) else {
+            return ScrollPosition::default();
+        };
 
-            // Render the debug panel widget
-            if self.debug_mode {
-                f.render_widget(debug_widget, debug_area)
+        let end = ScrollPosition {
+            line,
+            row: self.rows_in(line) - 1,
+        };
+
+        self.rows_before(end, self.page_height() - 1)
+    }
+
+    // Gets the number of rows that a line of output is wrapped onto in the output panel
+    fn rows_in(&self, line: usize) -> usize {
+        wrap_line(&self.output_buffer[line], self.output_width, 0)
+            .0
+            .len()
+    }
+
+    // Gets the position that is the given number of rows above another one, stopping at the first row
+    fn rows_before(&self, position: ScrollPosition, rows: usize) -> ScrollPosition {
+        let ScrollPosition { mut line, mut row } = position;
+        let mut remaining = rows;
+        while remaining > row {
+            if line == 0 {
+                return ScrollPosition::default();
             }
-        }
 
-        // Render the default widgets
-        f.render_widget(prompt_widget, prompt_area);
-        f.render_widget(output_widget.scroll((self.scroll as u16, 0)), output_area);
+            remaining -= row + 1;
+            line -= 1;
+            row = self.rows_in(line) - 1;
+        }
 
-        // Render the cursor
-        let (cursor_x, cursor_y) = Self::cursor_coord(self.cursor_index, prompt_area);
-        f.set_cursor(cursor_x, cursor_y);
+        ScrollPosition {
+            line,
+            row: row - remaining,
+        }
     }
 
-    // Scrolls down the output panel by one line
-    fn scroll_down(&mut self) {
-        let max_scroll = self.output_buffer.lines.len();
-        if self.scroll < max_scroll {
-            self.scroll = self.scroll.saturating_add(1);
+    // Gets the position that is the given number of rows below another one, stopping at the last row
+    fn rows_after(&self, position: ScrollPosition, rows: usize) -> ScrollPosition {
+        let ScrollPosition { mut line, mut row } = position;
+        let mut remaining = rows;
+        while line < self.output_buffer.len() {
+            let line_rows = self.rows_in(line);
+            if row + remaining < line_rows || line + 1 == self.output_buffer.len() {
+                row = (row + remaining).min(line_rows - 1);
+                break;
+            }
+
+            remaining -= line_rows - row;
+            line += 1;
+            row = 0;
         }
+
+        ScrollPosition { line, row }
     }
 
-    // Scrolls up the output panel by one line
-    fn scroll_up(&mut self) {
-        self.scroll = self.scroll.saturating_sub(1);
+    // Gets the output position just past the last line, counting lines that have been dropped
+    fn output_position_end(&self) -> usize {
+        self.dropped_lines + self.output_buffer.len()
     }
 
-    // Automatically scrolls to the bottom of the output panel text
-    fn scroll_to_bottom(&mut self, output_panel_height: usize) {
-        // * The -3 for is a bottom margin
-        // TODO: Make the bottom margin configurable
-        let output_panel_height = output_panel_height.saturating_sub(3);
-        self.scroll = self
-            .output_buffer
-            .lines
-            .len()
-            .saturating_sub(output_panel_height);
+    // Gets the output buffer in order to change it, so that the output panel is rebuilt in the next frame
+    // * The output buffer should never be changed without going through this
+    fn output_mut(&mut self) -> &mut VecDeque<Spans<'a>> {
+        self.output_revision += 1;
+        &mut self.output_buffer
+    }
+
+    // Drops the oldest lines of output until the output buffer fits within the scrollback limit
+    // * The scroll position moves up with the remaining lines, so that the same output stays in view
+    fn trim_output(&mut self) {
+        let Some(limit) = self.scrollback_limit else {
+            return;
+        };
+
+        let excess = self.output_buffer.len().saturating_sub(limit);
+        if excess == 0 {
+            return;
+        }
+
+        self.output_mut().drain(..excess);
+        self.dropped_lines += excess;
+        // * Once the header of a section has been dropped, the section can no longer be collapsed
+        while self
+            .sections
+            .front()
+            .is_some_and(|section| section.start < self.dropped_lines)
+        {
+            self.sections.pop_front();
+        }
+
+        self.scroll = match self.scroll.line.checked_sub(excess) {
+            Some(line) => ScrollPosition {
+                line,
+                ..self.scroll
+            },
+            None => ScrollPosition::default(),
+        };
     }
 
     // Scrolls through the Shell's command history
@@ -645,21 +3213,17 @@ impl<'a> ConsoleData<'a> {
                 match direction {
                     Up => {
                         // Prevent the user from scrolling out of bounds
-                        let new_index = index.saturating_sub(1);
-                        *index = new_index;
-                        self.cursor_index = history_get(new_index).len();
+                        *index = index.saturating_sub(1);
                     }
                     Down => {
                         // If the user scrolls back past the start of the history, restore the original line buffer
                         // Otherwise, keep scrolling down as normal
                         if *index == history_last_index {
-                            self.line_buffer = std::mem::take(line_buffer);
-                            self.cursor_index = *cursor_index;
+                            self.editor.set_text(std::mem::take(line_buffer));
+                            self.editor.set_cursor(*cursor_index);
                             self.history = None;
                         } else {
-                            let new_index = *index + 1;
-                            *index = new_index;
-                            self.cursor_index = history_get(new_index).len();
+                            *index += 1;
                         }
                     }
                 }
@@ -673,10 +3237,9 @@ impl<'a> ConsoleData<'a> {
                         // * contains at least one element due to the .is_empty() check
                         self.history = Some(History {
                             index: history_last_index,
-                            line_buffer: self.line_buffer.clone(),
-                            cursor_index: self.cursor_index,
+                            line_buffer: self.editor.text().to_string(),
+                            cursor_index: self.editor.cursor(),
                         });
-                        self.cursor_index = history_get(history_last_index).len();
                     }
                     Down => return Ok(()),
                 }
@@ -684,101 +3247,128 @@ impl<'a> ConsoleData<'a> {
         }
 
         // TODO: Change this to an actual error
+        // * The cursor is put at the end of the command from the history
         if let Some(History { index, .. }) = self.history {
-            self.line_buffer = history_get(index).to_owned();
+            self.editor.set_text(history_get(index));
         }
 
         Ok(())
     }
 
-    // Inserts a character at the cursor position
-    fn insert_char(&mut self, c: char) {
-        self.line_buffer.insert(self.cursor_index, c);
-        self.move_cursor_right();
-    }
-
-    // Removes a character from the line buffer at the cursor position
-    fn remove_char(&mut self, mode: RemoveMode) {
-        use RemoveMode::*;
-        match mode {
-            Backspace => {
-                if self.cursor_index > 0 {
-                    self.line_buffer.remove(self.cursor_index - 1);
-                    self.move_cursor_left();
-                }
+    // Starts searching backwards through the history, or moves on to the next older match if a search is in progress
+    fn search_history_older(&mut self, shell: &Shell) {
+        match &self.history_search {
+            Some(search) => {
+                let before = search.match_index.unwrap_or(shell.history().len());
+                self.find_history_match(before, shell);
             }
-            Delete => {
-                if self.cursor_index < self.line_buffer.len() {
-                    self.line_buffer.remove(self.cursor_index);
-                }
+            None => {
+                self.history_search = Some(HistorySearch {
+                    query: String::new(),
+                    match_index: None,
+                    failing: false,
+                    original_line: self.editor.text().to_string(),
+                });
             }
         }
     }
 
-    // Moves the cursor left by one character, checking for bounds
-    fn move_cursor_left(&mut self) {
-        self.cursor_index = self.cursor_index.saturating_sub(1);
-    }
+    // Adds a character to the search query, or removes the last one, and searches again from the newest command
+    fn edit_history_search(&mut self, c: Option<char>, shell: &Shell) {
+        let Some(search) = &mut self.history_search else {
+            return;
+        };
 
-    // Moves the cursor right by one character, checking for bounds
-    fn move_cursor_right(&mut self) {
-        if self.cursor_index < self.line_buffer.len() {
-            self.cursor_index += 1;
+        match c {
+            Some(c) => search.query.push(c),
+            None => {
+                search.query.pop();
+            }
         }
+
+        self.find_history_match(shell.history().len(), shell);
     }
 
-    // Moves the cursor left by one word, checking for bounds
-    fn seek_cursor_left(&mut self) {
-        for i in (0..self.cursor_index).rev() {
-            if self.is_word_boundary(i) {
-                self.cursor_index = i;
-                return;
-            }
+    // Shows the newest command before the given history index that contains the search query
+    // * If nothing matches, the previous match stays in the line buffer and the search is marked as failing
+    fn find_history_match(&mut self, before: usize, shell: &Shell) {
+        let Some(search) = &mut self.history_search else {
+            return;
+        };
+
+        if search.query.is_empty() {
+            search.match_index = None;
+            search.failing = false;
+            self.editor.set_text(search.original_line.clone());
+            return;
         }
-    }
 
-    // Moves the cursor right by one word, checking for bounds
-    fn seek_cursor_right(&mut self) {
-        for i in (self.cursor_index..=self.line_buffer.len()).skip(1) {
-            if self.is_word_boundary(i) {
-                self.cursor_index = i;
-                return;
-            }
+        let history = shell.history();
+        let found = (0..before.min(history.len()))
+            .rev()
+            .find_map(|index| Some((index, history[index].find(&search.query)?)));
+
+        search.failing = found.is_none();
+        if let Some((index, position)) = found {
+            search.match_index = Some(index);
+            self.editor.set_text(history[index].clone());
+            self.editor.set_cursor(position);
         }
     }
 
-    // Checks if the given index in the line buffer is a word boundary
-    fn is_word_boundary(&self, index: usize) -> bool {
-        let buffer = &self.line_buffer;
-        if index == 0 || index == buffer.len() {
-            return true;
-        }
+    // Ends the history search, either keeping the match in the line buffer or restoring what was there before
+    fn close_history_search(&mut self, accept: bool) {
+        let Some(search) = self.history_search.take() else {
+            return;
+        };
 
-        if !buffer.is_char_boundary(index) {
-            return false;
+        match accept {
+            true => self.editor.move_to_end(),
+            false => self.editor.set_text(search.original_line),
         }
+    }
 
-        if buffer[index..]
-            .chars()
-            .next()
-            .map_or(false, |c| !c.is_whitespace())
-        {
-            return false;
-        }
+    // Highlights the next or previous candidate in the completion menu, previewing it in the line buffer
+    fn select_completion(&mut self, forward: bool) {
+        let Some(menu) = &mut self.completion_menu else {
+            return;
+        };
 
-        if let Some(c) = buffer[..index].chars().next_back() {
-            if c != ' ' {
-                return true;
-            }
-        }
+        let count = menu.completion.candidates.len();
+        let selected = match (menu.selected, forward) {
+            (None, true) => 0,
+            (None, false) => count - 1,
+            (Some(index), true) => (index + 1) % count,
+            (Some(index), false) => (index + count - 1) % count,
+        };
+        menu.selected = Some(selected);
 
-        false
+        let start = menu.completion.start;
+        let replacement = menu
+            .completion
+            .replacement(&menu.completion.candidates[selected].word, false);
+        self.editor.replace_before_cursor(start, &replacement);
     }
 
-    // Clears the line buffer and resets the cursor position
-    fn reset_line_buffer(&mut self) {
-        self.line_buffer.clear();
-        self.cursor_index = 0;
+    // Closes the completion menu, either finishing off the highlighted candidate or restoring what was typed before
+    fn close_completion_menu(&mut self, accept: bool) {
+        let Some(menu) = self.completion_menu.take() else {
+            return;
+        };
+
+        let replacement = match (accept, menu.selected) {
+            (true, Some(index)) => {
+                let candidate = &menu.completion.candidates[index];
+                let finished =
+                    !candidate.is_directory && self.editor.cursor() == self.editor.text().len();
+                menu.completion.replacement(&candidate.word, finished)
+            }
+            (true, None) => return,
+            (false, _) => menu.original,
+        };
+
+        self.editor
+            .replace_before_cursor(menu.completion.start, &replacement);
     }
 
     // Appends a string to the output buffer, splitting it into Spans by newline characters so it is rendered properly
@@ -800,14 +3390,14 @@ impl<'a> ConsoleData<'a> {
         // If the output buffer has any lines, we append the first line of the new text to the last line of the output buffer
         // Otherwise, we just push the first line of the new text to the output buffer in the form of a Spans,
         // so the first line of the new text isn't just skipped on an empty output buffer
-        if let Some(last_line) = self.output_buffer.lines.last_mut() {
+        if let Some(last_line) = self.output_mut().back_mut() {
             last_line.0.extend(first_spans);
         } else {
-            self.output_buffer.lines.push(Spans::from(first_spans));
+            self.output_mut().push_back(Spans::from(first_spans));
         }
 
         // The rest of the lines (Spans) can then be appended to the output buffer as normal
-        self.output_buffer.extend(spans)
+        self.output_mut().extend(spans)
     }
 
     // Appends a string to the next line of the output buffer
@@ -819,27 +3409,55 @@ impl<'a> ConsoleData<'a> {
     // Appends a Spans to the output buffer
     #[allow(dead_code)]
     fn append_spans(&mut self, spans: Spans<'a>) {
-        self.output_buffer.lines.extend([spans]);
+        self.output_mut().push_back(spans);
     }
 
     // Appends a Spans to the output buffer, adding a newline after it
     fn append_spans_newline(&mut self, spans: Spans<'a>) {
         // TODO: Come up with a better name for this or merge it with append_newline() somehow
-        self.output_buffer.lines.extend([spans, Spans::default()]);
+        self.output_mut().extend([spans, Spans::default()]);
+    }
+
+    // Appends a styled Span to the current line of the output buffer
+    fn append_styled(&mut self, span: Span<'a>) {
+        match self.output_mut().back_mut() {
+            Some(last_line) => last_line.0.push(span),
+            None => self.output_mut().push_back(Spans::from(span)),
+        }
+    }
+
+    // Appends a Spans to the current line of the output buffer, adding a newline after it
+    // * Unlike append_spans_newline(), this does not start a new line before the Spans
+    fn append_styled_newline(&mut self, spans: Spans<'a>) {
+        if let Some(last_line) = self.output_mut().back_mut() {
+            last_line.0.extend(spans.0);
+        } else {
+            self.output_mut().push_back(spans);
+        }
+
+        self.append_newline()
+    }
+
+    // Replaces the last line of the output buffer with a string
+    fn replace_last_line(&mut self, string: &str) {
+        match self.output_mut().back_mut() {
+            Some(last_line) => *last_line = Spans::from(string.to_owned()),
+            None => self.append_str(string),
+        }
     }
 
     // Appends a newline to the output buffer
     fn append_newline(&mut self) {
-        self.output_buffer.lines.push(Spans::default());
+        self.output_mut().push_back(Spans::default());
     }
 
     // Ensures that there is an empty line at the end of the output buffer
     // * This is used to make the prompt always appear one line below the last line of output, just for cosmetic purposes
     fn enforce_spacing(&mut self) {
-        if let Some(last_line) = self.output_buffer.lines.last_mut() {
+        if let Some(last_line) = self.output_mut().back_mut() {
             // TODO: Find a less ugly way to do this
             if !last_line.0.is_empty() && last_line.0.last() != Some(&Span::raw("")) {
-                self.output_buffer.lines.push(Spans::default());
+                self.output_mut().push_back(Spans::default());
             }
         }
     }
@@ -847,26 +3465,29 @@ impl<'a> ConsoleData<'a> {
     // Autocompletes the line buffer
     fn autocomplete_line(&mut self) {
         if let Some(autocompletion) = &self.autocomplete_buffer {
-            self.line_buffer.push_str(autocompletion);
-            self.cursor_index = self.line_buffer.len();
+            self.editor.move_to_end();
+            self.editor.insert_str(autocompletion);
             self.autocomplete_buffer = None;
         }
     }
+}
 
-    // Given the cursor index and the Rect of the prompt panel, returns the terminal cursor position
-    // $ This only works on the first line due to soft-wrapping
-    fn cursor_coord(cursor_index: usize, prompt_area: Rect) -> (u16, u16) {
-        // Get the prompt panel width to determine the starting y-position of the cursor
-        // * The -2 is to account for the left and right borders
-        let prompt_width = prompt_area.width as usize - 2;
-        // * The +1 is to account for the top border
-        let prompt_y_coord = (prompt_area.y + 1) as usize;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        // Find the x and y offsets based on the cursor index
-        let y_offset = cursor_index / prompt_width + prompt_y_coord;
-        // * The +2 is to account for the prompt tick, and the space after the tick
-        let x = (cursor_index + 3) % prompt_width;
+    #[test]
+    fn escape_every_character_that_the_parser_reads() {
+        //given
+        let target = "https://a.b/x'\" && touch $HOME/$((1))";
 
-        (x as u16, y_offset as u16)
+        //when
+        let escaped = escape_argument(target);
+
+        //then
+        assert_eq!(
+            escaped,
+            r#"https://a.b/x\'\"\ \&\&\ touch\ \$HOME/\$\(\(1\)\)"#
+        );
     }
 }
@@ -7,6 +7,7 @@ use std::path::PathBuf;
 use anyhow::Result;
 
 use crate::errors::ShellError;
+use crate::executables::ExecutableCache;
 use crate::path::Path;
 
 // Identifier enum for safely accessing environment variables
@@ -36,13 +37,38 @@ impl Display for EnvVar {
 }
 
 impl EnvVar {
-    // Does the same thing as .to_string(), but uses legacy environment variable names
-    fn to_legacy_string(self) -> String {
+    // Gets the special variable with the given name, if there is one
+    // * Special variable names are case-insensitive, and the legacy name for the working directory is accepted
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_uppercase().as_str() {
+            "USER" => Some(Self::User),
+            "HOME" => Some(Self::Home),
+            "CWD" | "PWD" => Some(Self::Cwd),
+            "PATH" => Some(Self::Path),
+            _ => None,
+        }
+    }
+
+    // Gets the legacy name of the variable, which is the name that child processes see it under
+    #[cfg(unix)]
+    fn legacy_name(self) -> &'static str {
+        match self {
+            Self::User => "USER",
+            Self::Home => "HOME",
+            Self::Cwd => "PWD",
+            Self::Path => "PATH",
+        }
+    }
+
+    // Gets the Windows name of the variable, which is the name that child processes see it under
+    // * Windows has no equivalent of PWD, so the working directory is read from the process instead
+    #[cfg(windows)]
+    fn legacy_name(self) -> &'static str {
         match self {
-            Self::User => "USER".to_string(),
-            Self::Home => "HOME".to_string(),
-            Self::Cwd => "PWD".to_string(),
-            Self::Path => "PATH".to_string(),
+            Self::User => "USERNAME",
+            Self::Home => "USERPROFILE",
+            Self::Cwd => "CD",
+            Self::Path => "Path",
         }
     }
 }
@@ -67,50 +93,63 @@ impl EnvVarBundle {
 }
 
 // Represents the shell environment by encapsulating the environment variables
-// * Environment variables are represented in all caps by convention,
-// * any fields that are not actual environment variables are represented in the usual snake_case
+// * The special variables that the shell itself relies on are also kept in parsed form, with fields in all caps by convention,
+// * and any fields that are not actual environment variables are represented in the usual snake_case
 #[allow(non_snake_case)]
 pub struct Environment {
-    USER: String,
+    // Every variable by name, including the ones inherited from the parent process
+    // * The special variables are stored under their legacy names, except for the working directory, which is only kept in CWD
+    variables: HashMap<String, String>,
+    // The names of the variables that child processes inherit
+    // * Any other variable only exists inside the shell, for use in scripts and prompts, until it is exported
+    exported: HashSet<String>,
     HOME: PathBuf,
     CWD: Path,
     backward_directories: VecDeque<Path>,
     forward_directories: VecDeque<Path>,
+    // The directories saved by push-directory, most recent first
+    // * This is separate from the back/forward history, and is only changed explicitly by the user
+    directory_stack: VecDeque<Path>,
     // * PATH is not to be confused with the WORKING_DIRECTORY. PATH is a list of directories which
     // * the shell will search for executables in. WORKING_DIRECTORY is the current directory the user is in.
     PATH: VecDeque<Path>,
-    #[allow(dead_code)]
-    custom_variables: HashMap<String, String>,
+    // An index of the executables in the PATH, so they do not have to be searched for on every command
+    executable_cache: ExecutableCache,
 }
 
 #[allow(non_snake_case)]
 impl Environment {
     pub fn new() -> Result<Self> {
-        let USER = get_parent_env_var(EnvVar::User)?;
+        // * The special variables are checked for up front, since the shell cannot work without them
+        get_parent_env_var(EnvVar::User)?;
         let HOME = PathBuf::from(get_parent_env_var(EnvVar::Home)?);
         let CWD = Path::from_str(get_parent_env_var(EnvVar::Cwd)?.as_str(), &HOME)?;
         let PATH = convert_path(get_parent_env_var(EnvVar::Path)?.as_str(), &HOME);
 
+        // Inherit every variable from the parent process, other than the working directory
+        let variables: HashMap<String, String> = env::vars()
+            .filter(|(name, _)| name != EnvVar::Cwd.legacy_name())
+            .collect();
+        let exported = variables.keys().cloned().collect();
+
         Ok(Self {
-            USER,
+            variables,
+            exported,
             HOME,
             CWD,
             backward_directories: VecDeque::new(),
             forward_directories: VecDeque::new(),
+            directory_stack: VecDeque::new(),
             PATH,
-            custom_variables: HashMap::new(),
+            executable_cache: ExecutableCache::new(),
         })
     }
 
-    // Updates the shell process's environment variables to match the internal representation
-    fn update_process_env_vars(&self, vars: EnvVarBundle) -> Result<()> {
-        // TODO: How to detect errors here?
-        if vars.contains(EnvVar::User) {
-            env::set_var("USER", &self.USER);
-        }
-
+    // Updates the stored values of the special variables to match their parsed forms, and exports them to the shell process
+    fn update_special_vars(&mut self, vars: EnvVarBundle) -> Result<()> {
         if vars.contains(EnvVar::Home) {
-            env::set_var("HOME", &self.HOME);
+            let home = self.HOME.to_string_lossy().to_string();
+            self.export_var(EnvVar::Home.legacy_name(), home);
         }
 
         if vars.contains(EnvVar::Cwd) {
@@ -118,11 +157,122 @@ impl Environment {
                 .map_err(|_| ShellError::FailedToUpdateEnvironmentVariable(EnvVar::Cwd))?;
         }
 
+        // * The PATH is exported so that child processes search the same directories as the shell
+        if vars.contains(EnvVar::Path) {
+            let path = env::join_paths(self.PATH.iter().map(|p| p.path()))
+                .map_err(|_| ShellError::FailedToUpdateEnvironmentVariable(EnvVar::Path))?;
+            self.export_var(
+                EnvVar::Path.legacy_name(),
+                path.to_string_lossy().to_string(),
+            );
+        }
+
         Ok(())
     }
 
-    pub fn USER(&self) -> &String {
-        &self.USER
+    // Stores a variable and sets it in the shell process, so that child processes inherit it
+    // TODO: How to detect errors here?
+    fn export_var(&mut self, name: &str, value: String) {
+        env::set_var(name, &value);
+        self.variables.insert(name.to_string(), value);
+        self.exported.insert(name.to_string());
+    }
+
+    // Gets the value of any variable, special or otherwise, as a string
+    pub fn get_var(&self, name: &str) -> Option<String> {
+        match EnvVar::from_name(name) {
+            Some(EnvVar::Cwd) => Some(self.CWD.to_string()),
+            Some(var) => self.variables.get(var.legacy_name()).cloned(),
+            None => self.variables.get(name).cloned(),
+        }
+    }
+
+    // Gets every variable, including the special ones, sorted by name
+    // * The working directory is listed under its legacy name, since that is what child processes see
+    pub fn all_vars(&self) -> Vec<(String, String)> {
+        let cwd = (EnvVar::Cwd.legacy_name().to_string(), self.CWD.to_string());
+        let mut vars: Vec<(String, String)> =
+            self.variables.clone().into_iter().chain([cwd]).collect();
+
+        vars.sort();
+        vars
+    }
+
+    // Creates or modifies a variable, which only exists inside the shell unless it has been exported
+    // * As in POSIX shells, changing an exported variable exports the new value too
+    // * The special variables are parsed first, so an invalid value leaves them unchanged
+    pub fn set_var(&mut self, name: &str, value: &str, history_limit: Option<usize>) -> Result<()> {
+        match EnvVar::from_name(name) {
+            Some(EnvVar::User) => {
+                self.export_var(EnvVar::User.legacy_name(), value.to_string());
+                Ok(())
+            }
+            Some(EnvVar::Home) => {
+                let home = Path::from_str(value, &self.HOME)?;
+                self.HOME = home.path().clone();
+                self.update_special_vars([EnvVar::Home].into())
+            }
+            Some(EnvVar::Cwd) => self.set_CWD(value, history_limit),
+            Some(EnvVar::Path) => {
+                self.PATH = convert_path(value, &self.HOME);
+                self.executable_cache.invalidate();
+                self.update_special_vars([EnvVar::Path].into())
+            }
+            None => {
+                check_var_name(name)?;
+                match self.exported.contains(name) {
+                    true => self.export_var(name, value.to_string()),
+                    false => _ = self.variables.insert(name.to_string(), value.to_string()),
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    // Marks a variable to be inherited by child processes, exporting its current value to the shell process if it has one
+    // * A variable that has not been set yet is exported as soon as it is set
+    pub fn export(&mut self, name: &str) -> Result<()> {
+        // * The special variables are always exported, since child processes rely on them
+        if EnvVar::from_name(name).is_some() {
+            return Ok(());
+        }
+
+        check_var_name(name)?;
+        match self.variables.get(name) {
+            Some(value) => self.export_var(name, value.clone()),
+            None => _ = self.exported.insert(name.to_string()),
+        }
+
+        Ok(())
+    }
+
+    // Checks whether a variable is inherited by child processes, rather than only existing inside the shell
+    pub fn is_exported(&self, name: &str) -> bool {
+        EnvVar::from_name(name).is_some() || self.exported.contains(name)
+    }
+
+    // Deletes a variable from the shell and the shell process
+    // * The special variables are required by the shell, so they cannot be deleted
+    pub fn unset_var(&mut self, name: &str) -> Result<()> {
+        if let Some(var) = EnvVar::from_name(name) {
+            return Err(ShellError::CannotUnsetSpecialVariable(var).into());
+        }
+
+        // * A variable that was exported before it was set has no value, but unsetting it still stops it being exported
+        let was_exported = self.exported.remove(name);
+        if self.variables.remove(name).is_none() && !was_exported {
+            return Err(ShellError::UnknownVariable(name.to_string()).into());
+        }
+
+        env::remove_var(name);
+        Ok(())
+    }
+
+    pub fn USER(&self) -> &str {
+        self.variables
+            .get(EnvVar::User.legacy_name())
+            .map_or("", String::as_str)
     }
 
     pub fn HOME(&self) -> &PathBuf {
@@ -141,71 +291,202 @@ impl Environment {
         &self.PATH
     }
 
+    // * Any modification to the PATH may change which executables are available, so the cache is invalidated
     pub fn PATH_mut(&mut self) -> &mut VecDeque<Path> {
+        self.executable_cache.invalidate();
         &mut self.PATH
     }
 
+    // Exports the PATH to the shell process after it has been modified through PATH_mut()
+    pub fn export_PATH(&mut self) -> Result<()> {
+        self.update_special_vars([EnvVar::Path].into())
+    }
+
+    // Resolves the name of an executable in the PATH using the executable cache
+    pub fn resolve_executable(&mut self, name: &str) -> Result<Path> {
+        self.executable_cache.resolve(name, &self.PATH)
+    }
+
+    // Marks the executable cache as out of date, e.g. because a cached executable could not be found when it was run
+    pub fn invalidate_executables(&mut self) {
+        self.executable_cache.invalidate();
+    }
+
+    // Gets the names of all executables in the PATH
+    pub fn executable_names(&mut self) -> Vec<&String> {
+        self.executable_cache.names(&self.PATH)
+    }
+
+    // Rescans the PATH directories for executables, returning the number that were found
+    pub fn rehash(&mut self) -> usize {
+        self.executable_cache.rebuild(&self.PATH);
+        self.executable_cache.len()
+    }
+
     // Sets the current working directory and stores the previous working directory
     pub fn set_CWD(&mut self, new_directory: &str, history_limit: Option<usize>) -> Result<()> {
-        let starting_directory = self.CWD.clone();
         let new_directory = Path::from_str(new_directory, &self.HOME)?;
+        self.move_to(new_directory, history_limit)
+    }
+
+    // Changes to an already-resolved directory and stores the previous working directory
+    fn move_to(&mut self, new_directory: Path, history_limit: Option<usize>) -> Result<()> {
+        let starting_directory = self.CWD.clone();
 
         // Add the old directory to the history, avoiding duplicates
         if new_directory != starting_directory {
             self.CWD = new_directory;
             self.backward_directories.push_back(starting_directory);
             self.forward_directories.clear();
+            self.trim_history(history_limit);
+            self.update_special_vars([EnvVar::Cwd].into())?;
+        }
 
-            if let Some(limit) = history_limit {
-                while self.backward_directories.len() > limit {
-                    self.backward_directories.pop_front();
-                }
-            }
+        Ok(())
+    }
+
+    // Gets the directory stack, most recently pushed first
+    pub fn directory_stack(&self) -> &VecDeque<Path> {
+        &self.directory_stack
+    }
+
+    // Saves the current working directory on the directory stack and changes to a new directory
+    // If no directory is given, the current working directory is swapped with the top of the stack
+    pub fn push_directory(
+        &mut self,
+        new_directory: Option<&str>,
+        history_limit: Option<usize>,
+    ) -> Result<()> {
+        let new_directory = match new_directory {
+            Some(directory) => Path::from_str(directory, &self.HOME)?,
+            None => self
+                .directory_stack
+                .pop_front()
+                .ok_or(ShellError::DirectoryStackEmpty)?,
+        };
+
+        self.directory_stack.push_front(self.CWD.clone());
+        self.move_to(new_directory, history_limit)
+    }
+
+    // Removes the top directory from the directory stack and changes to it
+    pub fn pop_directory(&mut self, history_limit: Option<usize>) -> Result<()> {
+        let directory = self
+            .directory_stack
+            .pop_front()
+            .ok_or(ShellError::DirectoryStackEmpty)?;
+        self.move_to(directory, history_limit)
+    }
 
-            self.update_process_env_vars([EnvVar::Cwd].into())?;
+    // Rotates the directory stack (with the current working directory at index 0)
+    // so that the entry at the given index becomes the current working directory
+    pub fn rotate_directories(&mut self, index: usize, history_limit: Option<usize>) -> Result<()> {
+        if index > self.directory_stack.len() {
+            return Err(ShellError::InvalidDirectoryStackIndex(index).into());
         }
 
-        Ok(())
+        let mut directories = self.directory_stack.clone();
+        directories.push_front(self.CWD.clone());
+        directories.rotate_left(index);
+
+        // The stack cannot be empty here, because it contains at least the current working directory
+        let new_directory = directories.pop_front().unwrap();
+        self.directory_stack = directories;
+        self.move_to(new_directory, history_limit)
+    }
+
+    // Gets the directories that can be gone back to, oldest first
+    pub fn backward_directories(&self) -> &VecDeque<Path> {
+        &self.backward_directories
+    }
+
+    // Gets the directories that can be gone forward to, nearest first
+    pub fn forward_directories(&self) -> &VecDeque<Path> {
+        &self.forward_directories
     }
 
     // Sets the current working directory to the previous working directory
-    pub fn go_back(&mut self) -> Result<()> {
-        let starting_directory = self.CWD.clone();
-        if let Some(previous_path) = self.backward_directories.pop_back() {
-            self.CWD = previous_path;
-            self.forward_directories.push_front(starting_directory);
-            self.update_process_env_vars([EnvVar::Cwd].into())
-        } else {
-            Err(ShellError::NoPreviousDirectory.into())
-        }
+    pub fn go_back(&mut self, history_limit: Option<usize>) -> Result<()> {
+        self.move_in_history(-1, history_limit)
     }
 
     // Sets the current working directory to the next working directory
-    pub fn go_forward(&mut self) -> Result<()> {
-        let starting_directory = self.CWD.clone();
-        if let Some(next_path) = self.forward_directories.pop_front() {
-            self.CWD = next_path;
-            self.backward_directories.push_back(starting_directory);
-            self.update_process_env_vars([EnvVar::Cwd].into())
-        } else {
-            Err(ShellError::NoNextDirectory.into())
+    pub fn go_forward(&mut self, history_limit: Option<usize>) -> Result<()> {
+        self.move_in_history(1, history_limit)
+    }
+
+    // Moves through the back/forward history by the given number of steps, going back if it is negative and forward if it is positive
+    // * Every directory that is passed over stays in the history, so that it can still be returned to
+    pub fn move_in_history(&mut self, steps: isize, history_limit: Option<usize>) -> Result<()> {
+        if steps < 0 && steps.unsigned_abs() > self.backward_directories.len() {
+            return Err(ShellError::NoPreviousDirectory.into());
+        }
+
+        if steps > 0 && steps.unsigned_abs() > self.forward_directories.len() {
+            return Err(ShellError::NoNextDirectory.into());
+        }
+
+        for _ in 0..steps.unsigned_abs() {
+            // The entries were checked above, so the stack being moved from cannot run out
+            if steps < 0 {
+                let previous = self.backward_directories.pop_back().unwrap();
+                let current = std::mem::replace(&mut self.CWD, previous);
+                self.forward_directories.push_front(current);
+            } else {
+                let next = self.forward_directories.pop_front().unwrap();
+                let current = std::mem::replace(&mut self.CWD, next);
+                self.backward_directories.push_back(current);
+            }
+        }
+
+        self.trim_history(history_limit);
+        match steps {
+            0 => Ok(()),
+            _ => self.update_special_vars([EnvVar::Cwd].into()),
         }
     }
+
+    // Drops the directories furthest from the current one so that neither side of the back/forward history is longer than the limit
+    // * This is done on every move, so that lowering the limit takes effect the next time the directory changes
+    fn trim_history(&mut self, history_limit: Option<usize>) {
+        let Some(limit) = history_limit else {
+            return;
+        };
+
+        let excess = self.backward_directories.len().saturating_sub(limit);
+        self.backward_directories.drain(..excess);
+        self.forward_directories.truncate(limit);
+    }
+}
+
+// Checks that a name can be used for a variable in the environment of a process
+fn check_var_name(name: &str) -> Result<()> {
+    match name.is_empty() || name.contains(['=', '\0']) {
+        true => Err(ShellError::InvalidVariableName(name.to_string()).into()),
+        false => Ok(()),
+    }
 }
 
 // Gets the name of the user who invoked the shell (to be used when the shell is first initialized)
 fn get_parent_env_var(variable: EnvVar) -> Result<String> {
-    std::env::var(variable.to_legacy_string())
+    #[cfg(windows)]
+    if variable == EnvVar::Cwd {
+        return env::current_dir()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .map_err(|_| ShellError::MissingExternalEnvironmentVariable(variable).into());
+    }
+
+    std::env::var(variable.legacy_name())
         .map_err(|_| ShellError::MissingExternalEnvironmentVariable(variable).into())
 }
 
 // Converts the PATH environment variable from a string to a vector of Paths
-fn convert_path(path: &str, home: &PathBuf) -> VecDeque<Path> {
+fn convert_path(path: &str, home: &std::path::Path) -> VecDeque<Path> {
     let mut paths = VecDeque::new();
 
-    let path_strings = path.split(':').collect::<Vec<&str>>();
-    for path_string in path_strings {
-        let path = Path::from_str(path_string, home);
+    // * The PATH separator is platform-specific (':' on Unix, ';' on Windows)
+    for path_buf in env::split_paths(path) {
+        let path = Path::from_str(&path_buf.to_string_lossy(), home);
         // TODO: Handle errors
         if let Ok(path) = path {
             paths.push_back(path);
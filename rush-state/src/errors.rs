@@ -12,19 +12,74 @@ pub enum ShellError {
     MissingInternalEnvironmentVariable(EnvVar),
     #[error("Failed to update shell environment variable: {0}")]
     FailedToUpdateEnvironmentVariable(EnvVar),
+    #[error("Invalid variable name: {0}")]
+    InvalidVariableName(String),
+    #[error("Variable does not exist: {0}")]
+    UnknownVariable(String),
+    #[error("Special variable cannot be unset: {0}")]
+    CannotUnsetSpecialVariable(EnvVar),
     #[error("Previous directory does not exist")]
     NoPreviousDirectory,
     #[error("Next directory does not exist")]
     NoNextDirectory,
+    #[error("Directory stack is empty")]
+    DirectoryStackEmpty,
+    #[error("Directory stack index is out of range: {0}")]
+    InvalidDirectoryStackIndex(usize),
     #[error("Failed to open configuration file: {0}")]
     // ? Should these be Strings or Path/PathBuf?
     FailedToOpenConfigFile(String),
     #[error("Failed to read configuration file: {0}")]
     FailedToReadConfigFile(String),
+    #[error("Failed to write configuration file: {0}")]
+    FailedToWriteConfigFile(String),
+    #[error("Invalid configuration file: {0}:{1}: {2}")]
+    InvalidConfigFile(String, usize, String),
+    #[error("Unknown configuration key: {0}")]
+    UnknownConfigKey(String),
+    #[error("Invalid value for configuration key '{0}': {1}")]
+    InvalidConfigValue(String, String),
+    #[error("No configuration file to write to")]
+    MissingConfigFilePath,
+    #[error("Invalid alias definition (expected 'name=command'): {0}")]
+    InvalidAliasDefinition(String),
+    #[error("Invalid key binding (expected 'key=action'): {0}")]
+    InvalidKeyBinding(String),
+    #[error("Invalid interpreter association (expected 'extension=interpreter'): {0}")]
+    InvalidInterpreterAssociation(String),
     #[error("Unknown error")]
     Uncategorized,
 }
 
+impl ShellError {
+    // The name of the variant, which identifies the kind of error without its details
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::MissingExternalEnvironmentVariable(..) => "MissingExternalEnvironmentVariable",
+            Self::MissingInternalEnvironmentVariable(..) => "MissingInternalEnvironmentVariable",
+            Self::FailedToUpdateEnvironmentVariable(..) => "FailedToUpdateEnvironmentVariable",
+            Self::InvalidVariableName(..) => "InvalidVariableName",
+            Self::UnknownVariable(..) => "UnknownVariable",
+            Self::CannotUnsetSpecialVariable(..) => "CannotUnsetSpecialVariable",
+            Self::NoPreviousDirectory => "NoPreviousDirectory",
+            Self::NoNextDirectory => "NoNextDirectory",
+            Self::DirectoryStackEmpty => "DirectoryStackEmpty",
+            Self::InvalidDirectoryStackIndex(..) => "InvalidDirectoryStackIndex",
+            Self::FailedToOpenConfigFile(..) => "FailedToOpenConfigFile",
+            Self::FailedToReadConfigFile(..) => "FailedToReadConfigFile",
+            Self::FailedToWriteConfigFile(..) => "FailedToWriteConfigFile",
+            Self::InvalidConfigFile(..) => "InvalidConfigFile",
+            Self::UnknownConfigKey(..) => "UnknownConfigKey",
+            Self::InvalidConfigValue(..) => "InvalidConfigValue",
+            Self::MissingConfigFilePath => "MissingConfigFilePath",
+            Self::InvalidAliasDefinition(..) => "InvalidAliasDefinition",
+            Self::InvalidKeyBinding(..) => "InvalidKeyBinding",
+            Self::InvalidInterpreterAssociation(..) => "InvalidInterpreterAssociation",
+            Self::Uncategorized => "Uncategorized",
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum PathError {
     #[error("Failed to convert PathBuf to String: {0}")]
@@ -36,3 +91,14 @@ pub enum PathError {
     #[error("Directory does not exist: {0}")]
     UnknownDirectory(PathBuf),
 }
+
+impl PathError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::FailedToConvertPathBufToString(..) => "FailedToConvertPathBufToString",
+            Self::FailedToCanonicalize(..) => "FailedToCanonicalize",
+            Self::FailedToAccess(..) => "FailedToAccess",
+            Self::UnknownDirectory(..) => "UnknownDirectory",
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::Result;
+
+use crate::path::Path;
+
+// Represents an in-memory index of the executables found in the PATH directories
+// Avoids searching every PATH directory on the filesystem each time a command is dispatched
+pub struct ExecutableCache {
+    executables: HashMap<String, Path>,
+    // Whether the cache needs to be rebuilt before it is used again (e.g. because the PATH has changed)
+    stale: bool,
+}
+
+impl Default for ExecutableCache {
+    // * The cache starts out stale so that it is built lazily on the first lookup
+    fn default() -> Self {
+        Self {
+            executables: HashMap::new(),
+            stale: true,
+        }
+    }
+}
+
+impl ExecutableCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Marks the cache as out of date, so it will be rebuilt on the next lookup
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    // Rebuilds the cache by scanning every directory in the PATH
+    // * Directories earlier in the PATH take priority, matching the behavior of Path::from_path_var()
+    pub fn rebuild(&mut self, path: &VecDeque<Path>) {
+        self.executables.clear();
+
+        for dir in path {
+            // * Unreadable PATH entries are skipped rather than treated as errors, since they are common and harmless
+            let Ok(entries) = fs_err::read_dir(dir.path()) else {
+                continue;
+            };
+
+            for entry in entries.flatten() {
+                let Ok(name) = entry.file_name().into_string() else {
+                    continue;
+                };
+
+                // * Anything else with the same name (e.g. a directory) would hide a real executable later in the PATH
+                if !is_executable_file(&entry.path()) {
+                    continue;
+                }
+
+                for key in cache_keys(&name) {
+                    self.executables
+                        .entry(key)
+                        .or_insert_with(|| Path::from_existing(entry.path()));
+                }
+            }
+        }
+
+        self.stale = false;
+    }
+
+    // Resolves an executable name to its Path, rebuilding the cache first if it is stale
+    // If the name is missing from the cache, the PATH is searched directly in case the executable was added recently
+    // * A cached executable may have been deleted since the cache was built, in which case the cache is rebuilt once
+    pub fn resolve(&mut self, name: &str, path: &VecDeque<Path>) -> Result<Path> {
+        if self.stale {
+            self.rebuild(path);
+        }
+
+        let key = normalize_name(name);
+        if let Some(executable) = self.executables.get(&key) {
+            if is_executable_file(executable.path()) {
+                return Ok(executable.clone());
+            }
+
+            self.rebuild(path);
+            if let Some(executable) = self.executables.get(&key) {
+                return Ok(executable.clone());
+            }
+        }
+
+        // * Files without permission to execute are still found this way (e.g. scripts run through an interpreter), but are not cached
+        let executable = Path::from_path_var(name, path)?;
+        if is_executable_file(executable.path()) {
+            self.executables.insert(key, executable.clone());
+        }
+
+        Ok(executable)
+    }
+
+    // Gets the names of all cached executables, rebuilding the cache first if it is stale
+    pub fn names(&mut self, path: &VecDeque<Path>) -> Vec<&String> {
+        if self.stale {
+            self.rebuild(path);
+        }
+
+        self.executables.keys().collect()
+    }
+
+    // Gets the number of cached executables
+    pub fn len(&self) -> usize {
+        self.executables.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.executables.is_empty()
+    }
+}
+
+// Checks whether a path is a file (or a symbolic link to one) that can be executed
+#[cfg(unix)]
+fn is_executable_file(path: &std::path::Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs_err::metadata(path)
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+// * Windows has no permission to execute, so any file is counted, and its extension decides how it is run
+#[cfg(windows)]
+fn is_executable_file(path: &std::path::Path) -> bool {
+    path.is_file()
+}
+
+// Gets the names under which an executable file should be indexed
+#[cfg(unix)]
+fn cache_keys(file_name: &str) -> Vec<String> {
+    vec![file_name.to_string()]
+}
+
+// * On Windows, executables can be invoked with or without their PATHEXT extension, and names are case-insensitive
+#[cfg(windows)]
+fn cache_keys(file_name: &str) -> Vec<String> {
+    let file_name = file_name.to_lowercase();
+    let mut keys = vec![file_name.clone()];
+
+    for extension in crate::path::executable_extensions() {
+        if let Some(stem) = file_name.strip_suffix(&extension) {
+            keys.push(stem.to_string());
+        }
+    }
+
+    keys
+}
+
+// Normalizes an executable name before it is looked up in the cache
+fn normalize_name(name: &str) -> String {
+    if cfg!(windows) {
+        name.to_lowercase()
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn skip_entries_that_cannot_be_executed_and_forget_deleted_ones() {
+        //given
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join(format!("rush-executables-{}", std::process::id()));
+        let (first, second) = (root.join("first"), root.join("second"));
+        std::fs::create_dir_all(first.join("tool")).unwrap();
+        std::fs::create_dir_all(&second).unwrap();
+        std::fs::write(first.join("script"), "").unwrap();
+        std::fs::write(second.join("tool"), "").unwrap();
+        std::fs::set_permissions(second.join("tool"), std::fs::Permissions::from_mode(0o755))
+            .unwrap();
+        let path = VecDeque::from([
+            Path::from_existing(first.clone()),
+            Path::from_existing(second.clone()),
+        ]);
+        let mut cache = ExecutableCache::new();
+
+        //when
+        let tool = cache.resolve("tool", &path).unwrap();
+        let names: Vec<String> = cache.names(&path).into_iter().cloned().collect();
+        std::fs::remove_file(second.join("tool")).unwrap();
+        let deleted = cache.resolve("tool", &path);
+        _ = std::fs::remove_dir_all(&root);
+
+        //then
+        assert_eq!(tool.path(), &second.join("tool"));
+        assert_eq!(names, ["tool"]);
+        assert!(deleted.is_err());
+    }
+}
@@ -0,0 +1,20 @@
+use anyhow::Result;
+use regex::{Regex, RegexBuilder};
+
+// Converts a glob-style pattern into an anchored regular expression
+// * '*' matches any run of characters and '?' matches any one character; everything else is matched literally
+pub fn glob_to_regex(pattern: &str, ignore_case: bool) -> Result<Regex> {
+    let mut expression = String::from("^");
+    for c in pattern.chars() {
+        match c {
+            '*' => expression.push_str(".*"),
+            '?' => expression.push('.'),
+            _ => expression.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    expression.push('$');
+
+    Ok(RegexBuilder::new(&expression)
+        .case_insensitive(ignore_case)
+        .build()?)
+}
@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+use std::process::{Child, ExitStatus};
+
+// Represents a command that is running in the background
+pub struct Job {
+    pub id: usize,
+    // The command line that started the job, for display purposes
+    pub command: String,
+    // The file that the job's stdout and stderr are written to
+    pub log_path: PathBuf,
+    process: Child,
+}
+
+impl Job {
+    pub fn pid(&self) -> u32 {
+        self.process.id()
+    }
+
+    // Sends a hangup to the job, the same way a terminal would when it is closed
+    #[cfg(unix)]
+    fn hangup(&mut self) {
+        // * Background jobs are started in their own session, so the whole process group is signalled
+        // SAFETY: kill() has no memory safety requirements
+        unsafe {
+            libc::kill(-(self.pid() as libc::pid_t), libc::SIGHUP);
+        }
+    }
+
+    #[cfg(windows)]
+    fn hangup(&mut self) {
+        _ = self.process.kill();
+    }
+}
+
+// Represents the table of background jobs owned by the shell
+// * Jobs in the table are hung up when the shell exits, so removing a job from the table allows it to outlive the shell
+#[derive(Default)]
+pub struct JobTable {
+    jobs: Vec<Job>,
+    next_id: usize,
+}
+
+impl JobTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Adds a running process to the table, returning its job ID
+    pub fn add(&mut self, command: String, process: Child, log_path: PathBuf) -> usize {
+        self.next_id += 1;
+        self.jobs.push(Job {
+            id: self.next_id,
+            command,
+            log_path,
+            process,
+        });
+
+        self.next_id
+    }
+
+    // Gets the ID that will be assigned to the next job added to the table
+    pub fn next_id(&self) -> usize {
+        self.next_id + 1
+    }
+
+    // Removes a job from the table without stopping it
+    pub fn remove(&mut self, id: usize) -> Option<Job> {
+        let index = self.jobs.iter().position(|job| job.id == id)?;
+        Some(self.jobs.remove(index))
+    }
+
+    pub fn jobs(&self) -> &Vec<Job> {
+        &self.jobs
+    }
+
+    // Removes any jobs that have finished from the table, returning them along with their exit statuses
+    pub fn reap(&mut self) -> Vec<(Job, ExitStatus)> {
+        let mut finished = Vec::new();
+        let mut index = 0;
+
+        while index < self.jobs.len() {
+            // * If the status cannot be read, the job is assumed to still be running
+            if let Ok(Some(status)) = self.jobs[index].process.try_wait() {
+                finished.push((self.jobs.remove(index), status));
+            } else {
+                index += 1;
+            }
+        }
+
+        finished
+    }
+
+    // Hangs up every job in the table
+    pub fn hangup_all(&mut self) {
+        for job in &mut self.jobs {
+            job.hangup();
+        }
+    }
+}
@@ -0,0 +1,542 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::errors::ShellError;
+
+// The prefix that marks a binding as running a command line, e.g. 'ctrl+g=run:git status'
+pub const COMMAND_PREFIX: &str = "run:";
+
+// Represents an action that the Console can perform in response to a keypress
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ConsoleAction {
+    DeleteBackward,
+    DeleteForward,
+    DeleteForwardOrExit,
+    MoveLeft,
+    MoveRight,
+    MoveWordLeft,
+    MoveWordRight,
+    MoveToStart,
+    MoveToEnd,
+    SelectLeft,
+    SelectRight,
+    Submit,
+    ScrollUp,
+    ScrollDown,
+    ScrollPageUp,
+    ScrollPageDown,
+    FollowOutput,
+    SearchOutput,
+    SelectOutput,
+    LinkHints,
+    ToggleSection,
+    ToggleAllSections,
+    SplitOutput,
+    SwitchPane,
+    ClosePane,
+    HistoryUp,
+    HistoryDown,
+    HistorySearch,
+    Autocomplete,
+    Exit,
+    Interrupt,
+    ClearOutput,
+    ClearLine,
+    DeleteWordBackward,
+    KillToStart,
+    KillToEnd,
+    Yank,
+    YankPop,
+    YankLastArgument,
+    EditInEditor,
+    PasteClipboard,
+    ToggleDebug,
+    ScrollDebugUp,
+    ScrollDebugDown,
+}
+
+impl ConsoleAction {
+    // Gets every action that can be bound to a key
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::DeleteBackward,
+            Self::DeleteForward,
+            Self::DeleteForwardOrExit,
+            Self::MoveLeft,
+            Self::MoveRight,
+            Self::MoveWordLeft,
+            Self::MoveWordRight,
+            Self::MoveToStart,
+            Self::MoveToEnd,
+            Self::SelectLeft,
+            Self::SelectRight,
+            Self::Submit,
+            Self::ScrollUp,
+            Self::ScrollDown,
+            Self::ScrollPageUp,
+            Self::ScrollPageDown,
+            Self::FollowOutput,
+            Self::SearchOutput,
+            Self::SelectOutput,
+            Self::LinkHints,
+            Self::ToggleSection,
+            Self::ToggleAllSections,
+            Self::SplitOutput,
+            Self::SwitchPane,
+            Self::ClosePane,
+            Self::HistoryUp,
+            Self::HistoryDown,
+            Self::HistorySearch,
+            Self::Autocomplete,
+            Self::Exit,
+            Self::Interrupt,
+            Self::ClearOutput,
+            Self::ClearLine,
+            Self::DeleteWordBackward,
+            Self::KillToStart,
+            Self::KillToEnd,
+            Self::Yank,
+            Self::YankPop,
+            Self::YankLastArgument,
+            Self::EditInEditor,
+            Self::PasteClipboard,
+            Self::ToggleDebug,
+            Self::ScrollDebugUp,
+            Self::ScrollDebugDown,
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::DeleteBackward => "delete-backward",
+            Self::DeleteForward => "delete-forward",
+            Self::DeleteForwardOrExit => "delete-forward-or-exit",
+            Self::MoveLeft => "move-left",
+            Self::MoveRight => "move-right",
+            Self::MoveWordLeft => "move-word-left",
+            Self::MoveWordRight => "move-word-right",
+            Self::MoveToStart => "move-to-start",
+            Self::MoveToEnd => "move-to-end",
+            Self::SelectLeft => "select-left",
+            Self::SelectRight => "select-right",
+            Self::Submit => "submit",
+            Self::ScrollUp => "scroll-up",
+            Self::ScrollDown => "scroll-down",
+            Self::ScrollPageUp => "scroll-page-up",
+            Self::ScrollPageDown => "scroll-page-down",
+            Self::FollowOutput => "follow-output",
+            Self::SearchOutput => "search-output",
+            Self::SelectOutput => "select-output",
+            Self::LinkHints => "link-hints",
+            Self::ToggleSection => "toggle-section",
+            Self::ToggleAllSections => "toggle-all-sections",
+            Self::SplitOutput => "split-output",
+            Self::SwitchPane => "switch-pane",
+            Self::ClosePane => "close-pane",
+            Self::HistoryUp => "history-up",
+            Self::HistoryDown => "history-down",
+            Self::HistorySearch => "history-search",
+            Self::Autocomplete => "autocomplete",
+            Self::Exit => "exit",
+            Self::Interrupt => "interrupt",
+            Self::ClearOutput => "clear-output",
+            Self::ClearLine => "clear-line",
+            Self::DeleteWordBackward => "delete-word-backward",
+            Self::KillToStart => "kill-to-start",
+            Self::KillToEnd => "kill-to-end",
+            Self::Yank => "yank",
+            Self::YankPop => "yank-pop",
+            Self::YankLastArgument => "yank-last-argument",
+            Self::EditInEditor => "edit-in-editor",
+            Self::PasteClipboard => "paste-clipboard",
+            Self::ToggleDebug => "toggle-debug",
+            Self::ScrollDebugUp => "scroll-debug-up",
+            Self::ScrollDebugDown => "scroll-debug-down",
+        }
+    }
+}
+
+impl Display for ConsoleAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for ConsoleAction {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::all()
+            .iter()
+            .find(|action| action.name() == name)
+            .copied()
+            .ok_or(())
+    }
+}
+
+// Represents what pressing a bound key does
+// * Each kind of action other than the Console's own is written with a prefix,
+// * so that new kinds (such as actions added by plugins) can be added without clashing with action names
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum KeyAction {
+    // One of the Console's own actions
+    Console(ConsoleAction),
+    // A command line that is run as if it had been typed and submitted
+    Command(String),
+}
+
+impl Display for KeyAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Console(action) => write!(f, "{}", action),
+            Self::Command(command) => write!(f, "{}{}", COMMAND_PREFIX, command),
+        }
+    }
+}
+
+impl FromStr for KeyAction {
+    type Err = ();
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.strip_prefix(COMMAND_PREFIX) {
+            Some(command) if !command.trim().is_empty() => {
+                Ok(Self::Command(command.trim().to_string()))
+            }
+            Some(_) => Err(()),
+            None => Ok(Self::Console(text.parse()?)),
+        }
+    }
+}
+
+impl From<ConsoleAction> for KeyAction {
+    fn from(action: ConsoleAction) -> Self {
+        Self::Console(action)
+    }
+}
+
+// Represents a key combination, such as Ctrl+K or Alt+Left, or a sequence of two of them, such as Ctrl+X Ctrl+E
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    // The key combination that must be pressed first, if this is a sequence
+    pub prefix: Option<(KeyModifiers, KeyCode)>,
+    pub modifiers: KeyModifiers,
+    pub code: KeyCode,
+}
+
+impl KeyBinding {
+    pub fn new(modifiers: KeyModifiers, code: KeyCode) -> Self {
+        Self {
+            prefix: None,
+            modifiers,
+            code,
+        }
+    }
+
+    // Creates a sequence where this key combination is pressed after the given one
+    pub fn after(self, prefix: KeyBinding) -> Self {
+        Self {
+            prefix: Some((prefix.modifiers, prefix.code)),
+            ..self
+        }
+    }
+}
+
+impl Display for KeyBinding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some((modifiers, code)) = self.prefix {
+            write!(f, "{} ", KeyBinding::new(modifiers, code))?;
+        }
+
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl+")?;
+        }
+
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt+")?;
+        }
+
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "shift+")?;
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            code => write!(f, "{}", key_name(code).unwrap_or("unknown")),
+        }
+    }
+}
+
+// Parses a key combination in the form 'ctrl+alt+k', where the modifiers are optional,
+// or a sequence of two key combinations separated by a space, such as 'ctrl+x ctrl+e'
+impl FromStr for KeyBinding {
+    type Err = ();
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if let Some((prefix, key)) = text.trim().split_once(' ') {
+            let prefix: Self = prefix.parse()?;
+            let key: Self = key.trim().parse()?;
+            return match (prefix.prefix, key.prefix) {
+                (None, None) => Ok(key.after(prefix)),
+                _ => Err(()),
+            };
+        }
+
+        let text = text.to_lowercase();
+        let mut parts: Vec<&str> = text.split('+').collect();
+        let key = parts.pop().ok_or(())?;
+
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier in parts {
+            modifiers |= match modifier {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(()),
+            };
+        }
+
+        let mut characters = key.chars();
+        let code = match (characters.next(), characters.next()) {
+            // * Terminals report letters typed with Shift as capital letters
+            (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            (Some(c), None) => KeyCode::Char(c),
+            _ if key == "space" => KeyCode::Char(' '),
+            _ if key.starts_with('f') => KeyCode::F(key[1..].parse().map_err(|_| ())?),
+            _ => [
+                KeyCode::Backspace,
+                KeyCode::Delete,
+                KeyCode::Enter,
+                KeyCode::Esc,
+                KeyCode::Tab,
+                KeyCode::Left,
+                KeyCode::Right,
+                KeyCode::Up,
+                KeyCode::Down,
+                KeyCode::Home,
+                KeyCode::End,
+                KeyCode::PageUp,
+                KeyCode::PageDown,
+                KeyCode::Insert,
+            ]
+            .into_iter()
+            .find(|code| key_name(*code) == Some(key))
+            .ok_or(())?,
+        };
+
+        Ok(Self::new(modifiers, code))
+    }
+}
+
+// Gets the name used for a non-character key in the keymap
+fn key_name(code: KeyCode) -> Option<&'static str> {
+    Some(match code {
+        KeyCode::Backspace => "backspace",
+        KeyCode::Delete => "delete",
+        KeyCode::Enter => "enter",
+        KeyCode::Esc => "esc",
+        KeyCode::Tab => "tab",
+        KeyCode::Left => "left",
+        KeyCode::Right => "right",
+        KeyCode::Up => "up",
+        KeyCode::Down => "down",
+        KeyCode::Home => "home",
+        KeyCode::End => "end",
+        KeyCode::PageUp => "pageup",
+        KeyCode::PageDown => "pagedown",
+        KeyCode::Insert => "insert",
+        _ => return None,
+    })
+}
+
+// Maps key combinations to the Console actions they trigger
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: HashMap<KeyBinding, KeyAction>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use ConsoleAction::*;
+
+        let bindings = [
+            (KeyModifiers::NONE, KeyCode::Backspace, DeleteBackward),
+            (KeyModifiers::NONE, KeyCode::Delete, DeleteForward),
+            (
+                KeyModifiers::CONTROL,
+                KeyCode::Char('d'),
+                DeleteForwardOrExit,
+            ),
+            (KeyModifiers::NONE, KeyCode::Left, MoveLeft),
+            (KeyModifiers::NONE, KeyCode::Right, MoveRight),
+            (KeyModifiers::ALT, KeyCode::Left, MoveWordLeft),
+            (KeyModifiers::ALT, KeyCode::Right, MoveWordRight),
+            (KeyModifiers::CONTROL, KeyCode::Left, MoveWordLeft),
+            (KeyModifiers::CONTROL, KeyCode::Right, MoveWordRight),
+            (KeyModifiers::ALT, KeyCode::Char('b'), MoveWordLeft),
+            (KeyModifiers::ALT, KeyCode::Char('f'), MoveWordRight),
+            (KeyModifiers::NONE, KeyCode::Home, MoveToStart),
+            (KeyModifiers::NONE, KeyCode::End, MoveToEnd),
+            (KeyModifiers::CONTROL, KeyCode::Char('a'), MoveToStart),
+            (KeyModifiers::CONTROL, KeyCode::Char('e'), MoveToEnd),
+            (KeyModifiers::SHIFT, KeyCode::Left, SelectLeft),
+            (KeyModifiers::SHIFT, KeyCode::Right, SelectRight),
+            (KeyModifiers::NONE, KeyCode::Enter, Submit),
+            (KeyModifiers::SHIFT, KeyCode::Up, ScrollUp),
+            (KeyModifiers::SHIFT, KeyCode::Down, ScrollDown),
+            (KeyModifiers::NONE, KeyCode::PageUp, ScrollPageUp),
+            (KeyModifiers::NONE, KeyCode::PageDown, ScrollPageDown),
+            (KeyModifiers::CONTROL, KeyCode::End, FollowOutput),
+            (KeyModifiers::CONTROL, KeyCode::Char('f'), SearchOutput),
+            (KeyModifiers::ALT, KeyCode::Char('v'), SelectOutput),
+            (KeyModifiers::ALT, KeyCode::Char('o'), LinkHints),
+            (KeyModifiers::ALT, KeyCode::Char('z'), ToggleSection),
+            (
+                KeyModifiers::ALT | KeyModifiers::SHIFT,
+                KeyCode::Char('Z'),
+                ToggleAllSections,
+            ),
+            (KeyModifiers::NONE, KeyCode::Up, HistoryUp),
+            (KeyModifiers::NONE, KeyCode::Down, HistoryDown),
+            (KeyModifiers::CONTROL, KeyCode::Char('r'), HistorySearch),
+            (KeyModifiers::NONE, KeyCode::Tab, Autocomplete),
+            (KeyModifiers::CONTROL, KeyCode::Char('c'), Interrupt),
+            (KeyModifiers::CONTROL, KeyCode::Char('l'), ClearOutput),
+            (
+                KeyModifiers::CONTROL,
+                KeyCode::Char('w'),
+                DeleteWordBackward,
+            ),
+            (KeyModifiers::CONTROL, KeyCode::Char('u'), KillToStart),
+            (KeyModifiers::CONTROL, KeyCode::Char('k'), KillToEnd),
+            (KeyModifiers::CONTROL, KeyCode::Char('y'), Yank),
+            (KeyModifiers::ALT, KeyCode::Char('y'), YankPop),
+            (KeyModifiers::ALT, KeyCode::Char('.'), YankLastArgument),
+            (KeyModifiers::CONTROL, KeyCode::Char('v'), PasteClipboard),
+            // * Most terminals paste on their own when Ctrl+Shift+V is pressed, but some pass it through instead
+            (
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+                KeyCode::Char('V'),
+                PasteClipboard,
+            ),
+            (KeyModifiers::NONE, KeyCode::F(12), ToggleDebug),
+            (KeyModifiers::ALT, KeyCode::PageUp, ScrollDebugUp),
+            (KeyModifiers::ALT, KeyCode::PageDown, ScrollDebugDown),
+        ];
+
+        let mut bindings: HashMap<KeyBinding, KeyAction> = bindings
+            .into_iter()
+            .map(|(modifiers, code, action)| {
+                (KeyBinding::new(modifiers, code), KeyAction::Console(action))
+            })
+            .collect();
+
+        // * Sequences are listed separately, since they are made of two key combinations
+        let ctrl_x = KeyBinding::new(KeyModifiers::CONTROL, KeyCode::Char('x'));
+        let sequences = [
+            (
+                ctrl_x,
+                KeyModifiers::CONTROL,
+                KeyCode::Char('e'),
+                EditInEditor,
+            ),
+            (ctrl_x, KeyModifiers::NONE, KeyCode::Char('2'), SplitOutput),
+            (ctrl_x, KeyModifiers::NONE, KeyCode::Char('o'), SwitchPane),
+            (ctrl_x, KeyModifiers::NONE, KeyCode::Char('0'), ClosePane),
+        ];
+        for (prefix, modifiers, code, action) in sequences {
+            let binding = KeyBinding::new(modifiers, code).after(prefix);
+            bindings.insert(binding, KeyAction::Console(action));
+        }
+
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    // Gets the action bound to a key combination, if there is one
+    pub fn action_for(&self, binding: &KeyBinding) -> Option<&KeyAction> {
+        self.bindings.get(binding)
+    }
+
+    // Checks if a key combination starts any sequence, in which case the next key completes the sequence
+    pub fn is_prefix(&self, binding: &KeyBinding) -> bool {
+        binding.prefix.is_none()
+            && self
+                .bindings
+                .keys()
+                .any(|other| other.prefix == Some((binding.modifiers, binding.code)))
+    }
+
+    // Gets the Console action bound to a key combination, if it is bound to one
+    pub fn console_action_for(&self, binding: &KeyBinding) -> Option<ConsoleAction> {
+        match self.action_for(binding) {
+            Some(KeyAction::Console(action)) => Some(*action),
+            _ => None,
+        }
+    }
+
+    // Binds a key combination to an action, replacing any existing binding for that key
+    pub fn bind(&mut self, binding: KeyBinding, action: KeyAction) {
+        self.bindings.insert(binding, action);
+    }
+
+    // Removes the binding for a key combination, returning the action it was bound to
+    pub fn unbind(&mut self, binding: &KeyBinding) -> Option<KeyAction> {
+        self.bindings.remove(binding)
+    }
+
+    // Adds, replaces, or removes a binding from a string in the form 'key=action'
+    // Binding a key to 'none' removes its binding
+    pub fn apply(&mut self, definition: &str) -> anyhow::Result<()> {
+        let invalid = || ShellError::InvalidKeyBinding(definition.to_string());
+        let (key, action) = definition.split_once('=').ok_or_else(invalid)?;
+        let binding = key.trim().parse::<KeyBinding>().map_err(|_| invalid())?;
+
+        match action.trim() {
+            "none" => {
+                self.unbind(&binding);
+            }
+            action => self.bind(binding, action.parse().map_err(|_| invalid())?),
+        }
+
+        Ok(())
+    }
+
+    // Gets every binding, sorted by action so related keys are listed together
+    pub fn bindings(&self) -> Vec<(KeyBinding, KeyAction)> {
+        let mut bindings: Vec<_> = self
+            .bindings
+            .iter()
+            .map(|(binding, action)| (*binding, action.clone()))
+            .collect();
+        bindings.sort_by_key(|(binding, action)| (action.clone(), binding.to_string()));
+        bindings
+    }
+
+    // Gets the bindings that differ from the defaults, as key and action pairs for the config file
+    pub fn overrides(&self) -> Vec<(String, String)> {
+        let defaults = Self::default();
+        let mut overrides: Vec<(String, String)> = self
+            .bindings
+            .iter()
+            .filter(|(binding, action)| defaults.action_for(binding) != Some(*action))
+            .map(|(binding, action)| (binding.to_string(), action.to_string()))
+            .chain(
+                defaults
+                    .bindings
+                    .keys()
+                    .filter(|binding| !self.bindings.contains_key(binding))
+                    .map(|binding| (binding.to_string(), String::from("none"))),
+            )
+            .collect();
+        overrides.sort();
+        overrides
+    }
+}
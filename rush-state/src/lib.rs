@@ -1,6 +1,24 @@
-mod config;
+pub mod backend;
+mod clipboard;
+pub mod color_support;
+pub mod completion;
+pub mod config;
+mod config_file;
+mod config_watcher;
 pub mod console;
 mod environment;
 pub mod errors;
+pub mod executables;
+pub mod glob;
+pub mod jobs;
+pub mod keymap;
+pub mod line_editor;
+mod links;
+mod log_follower;
+mod notification;
 pub mod path;
+pub mod prompt;
 pub mod shell;
+pub mod signature;
+pub mod temp_file;
+pub mod theme;
@@ -0,0 +1,442 @@
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::completion::word_start;
+
+// The number of removed pieces of text that are kept so they can be pasted back
+const KILL_RING_SIZE: usize = 16;
+
+// Represents a line of text that is being edited, along with the cursor, the selection, and the text removed from it
+// * Positions are byte indices into the text, and always fall between graphemes,
+// * so that an emoji with modifiers or a letter with an accent is treated as a single character
+#[derive(Debug, Default)]
+pub struct LineEditor {
+    text: String,
+    // The index of the cursor in the text
+    cursor: usize,
+    // Where the selection started, if there is one
+    // * The selection covers the text between the anchor and the cursor
+    anchor: Option<usize>,
+    // Text removed by the kill methods, from oldest to newest
+    kill_ring: Vec<String>,
+    // The most recently pasted kill, if nothing has happened since then, so that it can be swapped for an older one
+    last_yank: Option<Yank>,
+    // The most recently pasted last argument, if nothing has happened since then, so that it can be swapped for an older one
+    last_argument: Option<LastArgument>,
+}
+
+// Represents a kill that was pasted back into the text, ending at the cursor
+#[derive(Debug)]
+struct Yank {
+    // The index in the text where the pasted kill starts
+    start: usize,
+    // The index in the kill ring of the pasted kill
+    ring_index: usize,
+}
+
+// Represents the last argument of a previous command that was pasted into the text, ending at the cursor
+#[derive(Debug)]
+struct LastArgument {
+    // The index in the text where the pasted argument starts
+    start: usize,
+    // How many commands before the most recent one the argument was taken from
+    commands_back: usize,
+}
+
+impl LineEditor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn kill_ring(&self) -> &[String] {
+        &self.kill_ring
+    }
+
+    // Replaces the text, putting the cursor at the end of it
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.cursor = self.text.len();
+        self.anchor = None;
+    }
+
+    // Moves the cursor to the given index, as long as it is inside the text
+    pub fn set_cursor(&mut self, index: usize) {
+        self.cursor = index.min(self.text.len());
+        self.anchor = None;
+    }
+
+    // Removes and returns the text, leaving the editor empty
+    // * The kill ring is kept, so that text from a previous line can be pasted into the next one
+    pub fn take(&mut self) -> String {
+        self.cursor = 0;
+        self.anchor = None;
+        std::mem::take(&mut self.text)
+    }
+
+    // Inserts a character at the cursor, replacing the selection if there is one
+    pub fn insert_char(&mut self, c: char) {
+        self.delete_selection();
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    // Inserts a string at the cursor, replacing the selection if there is one
+    pub fn insert_str(&mut self, string: &str) {
+        self.delete_selection();
+        self.text.insert_str(self.cursor, string);
+        self.cursor += string.len();
+    }
+
+    // Removes the selection, or the character before the cursor if nothing is selected
+    pub fn delete_backward(&mut self) {
+        if !self.delete_selection() {
+            let start = self.previous_boundary(self.cursor);
+            self.text.drain(start..self.cursor);
+            self.cursor = start;
+        }
+    }
+
+    // Removes the selection, or the character after the cursor if nothing is selected
+    pub fn delete_forward(&mut self) {
+        if !self.delete_selection() {
+            let end = self.next_boundary(self.cursor);
+            self.text.drain(self.cursor..end);
+        }
+    }
+
+    // Replaces the text between the given index and the cursor, moving the cursor past the replacement
+    pub fn replace_before_cursor(&mut self, start: usize, replacement: &str) {
+        self.text.replace_range(start..self.cursor, replacement);
+        self.cursor = start + replacement.len();
+        self.anchor = None;
+    }
+
+    // Moves the cursor left by one character
+    pub fn move_left(&mut self) {
+        self.set_cursor(self.previous_boundary(self.cursor));
+    }
+
+    // Moves the cursor right by one character
+    pub fn move_right(&mut self) {
+        self.set_cursor(self.next_boundary(self.cursor));
+    }
+
+    // Moves the cursor left to the start of the current or previous word
+    pub fn move_word_left(&mut self) {
+        self.set_cursor(self.previous_word_start());
+    }
+
+    // Moves the cursor right to the end of the current or next word
+    pub fn move_word_right(&mut self) {
+        self.set_cursor(self.next_word_end());
+    }
+
+    pub fn move_to_start(&mut self) {
+        self.set_cursor(0);
+    }
+
+    pub fn move_to_end(&mut self) {
+        self.set_cursor(self.text.len());
+    }
+
+    // Extends the selection left by one character, starting a selection at the cursor if there is none
+    pub fn select_left(&mut self) {
+        let anchor = self.anchor.unwrap_or(self.cursor);
+        self.cursor = self.previous_boundary(self.cursor);
+        self.anchor = Some(anchor);
+    }
+
+    // Extends the selection right by one character, starting a selection at the cursor if there is none
+    pub fn select_right(&mut self) {
+        let anchor = self.anchor.unwrap_or(self.cursor);
+        self.cursor = self.next_boundary(self.cursor);
+        self.anchor = Some(anchor);
+    }
+
+    // Gets the range of the text that is selected, if anything is
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor?;
+        let range = anchor.min(self.cursor)..anchor.max(self.cursor);
+        (!range.is_empty()).then_some(range)
+    }
+
+    pub fn selected_text(&self) -> Option<&str> {
+        self.selection().map(|range| &self.text[range])
+    }
+
+    // Removes the selected text, returning true if anything was selected
+    fn delete_selection(&mut self) -> bool {
+        let selection = self.selection();
+        self.anchor = None;
+        match selection {
+            Some(range) => {
+                self.text.drain(range.clone());
+                self.cursor = range.start;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Removes the text between the start of the current or previous word and the cursor
+    pub fn delete_word_backward(&mut self) {
+        self.kill_range(self.previous_word_start()..self.cursor);
+    }
+
+    // Removes the text between the start of the line and the cursor
+    pub fn kill_to_start(&mut self) {
+        self.kill_range(0..self.cursor);
+    }
+
+    // Removes the text between the cursor and the end of the line
+    pub fn kill_to_end(&mut self) {
+        self.kill_range(self.cursor..self.text.len());
+    }
+
+    // Removes all of the text
+    pub fn kill_line(&mut self) {
+        self.kill_range(0..self.text.len());
+    }
+
+    // Removes a range of text, leaving the cursor where the text was
+    // The removed text is added to the kill ring so that it can be pasted back later
+    fn kill_range(&mut self, range: Range<usize>) {
+        self.cursor = range.start;
+        self.anchor = None;
+        let killed: String = self.text.drain(range).collect();
+
+        if !killed.is_empty() {
+            if self.kill_ring.len() == KILL_RING_SIZE {
+                self.kill_ring.remove(0);
+            }
+
+            self.kill_ring.push(killed);
+        }
+    }
+
+    // Pastes the most recent kill at the cursor
+    pub fn yank(&mut self) {
+        if let Some(ring_index) = self.kill_ring.len().checked_sub(1) {
+            self.paste_kill(ring_index);
+        }
+    }
+
+    // Replaces the text that was just pasted with the kill before it, wrapping around to the newest kill
+    pub fn yank_pop(&mut self) {
+        let Some(yank) = self.last_yank.take() else {
+            return;
+        };
+
+        self.text.drain(yank.start..self.cursor);
+        self.cursor = yank.start;
+
+        let ring_index = match yank.ring_index {
+            0 => self.kill_ring.len() - 1,
+            index => index - 1,
+        };
+
+        self.paste_kill(ring_index);
+    }
+
+    // Pastes the last argument of the most recent command at the cursor,
+    // or replaces the argument that was just pasted with the last argument of the command before it
+    // * Once the oldest command is reached, its argument is kept
+    pub fn yank_last_argument(&mut self, history: &[String]) {
+        self.last_yank = None;
+        let commands_back = match self.last_argument.take() {
+            Some(previous) => {
+                self.text.drain(previous.start..self.cursor);
+                self.cursor = previous.start;
+                (previous.commands_back + 1).min(history.len().saturating_sub(1))
+            }
+            None => 0,
+        };
+
+        let Some(command) = history.iter().rev().nth(commands_back) else {
+            return;
+        };
+
+        let command = command.trim_end();
+        let start = self.cursor;
+        self.insert_str(&command[word_start(command)..]);
+        self.last_argument = Some(LastArgument {
+            start,
+            commands_back,
+        });
+    }
+
+    // Forgets the most recently pasted kill or argument, so that it can no longer be swapped for an older one
+    // * This should be called whenever anything other than a yank is done
+    pub fn forget_yank(&mut self) {
+        self.last_yank = None;
+        self.last_argument = None;
+    }
+
+    // Inserts a kill from the kill ring at the cursor, remembering it so that it can be swapped for another
+    fn paste_kill(&mut self, ring_index: usize) {
+        self.last_argument = None;
+        let start = self.cursor;
+        self.insert_str(&self.kill_ring[ring_index].clone());
+        self.last_yank = Some(Yank { start, ring_index });
+    }
+
+    // Gets the index where the character before the given index starts
+    fn previous_boundary(&self, index: usize) -> usize {
+        index - self.grapheme_before(index).map_or(0, str::len)
+    }
+
+    // Gets the index where the character after the given index ends
+    fn next_boundary(&self, index: usize) -> usize {
+        index + self.grapheme_after(index).map_or(0, str::len)
+    }
+
+    // Gets the grapheme that ends at the given index, if there is one
+    fn grapheme_before(&self, index: usize) -> Option<&str> {
+        self.text[..index].graphemes(true).next_back()
+    }
+
+    // Gets the grapheme that starts at the given index, if there is one
+    fn grapheme_after(&self, index: usize) -> Option<&str> {
+        self.text[index..].graphemes(true).next()
+    }
+
+    // Gets the index of the start of the current or previous word
+    fn previous_word_start(&self) -> usize {
+        self.text[..self.cursor]
+            .grapheme_indices(true)
+            .rev()
+            .map(|(index, _)| index)
+            .find(|&index| self.is_word_start(index))
+            .unwrap_or(0)
+    }
+
+    // Gets the index of the end of the current or next word
+    fn next_word_end(&self) -> usize {
+        self.text[self.cursor..]
+            .grapheme_indices(true)
+            .map(|(index, grapheme)| self.cursor + index + grapheme.len())
+            .find(|&index| self.is_word_end(index))
+            .unwrap_or(self.text.len())
+    }
+
+    // Checks if a word starts at the given index
+    fn is_word_start(&self, index: usize) -> bool {
+        self.grapheme_after(index).is_some_and(is_word_grapheme)
+            && !self.grapheme_before(index).is_some_and(is_word_grapheme)
+    }
+
+    // Checks if a word ends at the given index
+    fn is_word_end(&self, index: usize) -> bool {
+        self.grapheme_before(index).is_some_and(is_word_grapheme)
+            && !self.grapheme_after(index).is_some_and(is_word_grapheme)
+    }
+}
+
+// Checks if a grapheme is part of a word for the purpose of moving the cursor by words
+// * Whitespace, path separators, and most punctuation separate words, so that each directory in a path is its own word
+// * Dashes, underscores, and dots are kept inside words so that flags and file names are not split apart
+fn is_word_grapheme(grapheme: &str) -> bool {
+    grapheme
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor_with(text: &str) -> LineEditor {
+        let mut editor = LineEditor::new();
+        editor.set_text(text);
+        editor
+    }
+
+    #[test]
+    fn delete_whole_grapheme_when_deleting_backward() {
+        //given
+        let mut editor = editor_with("cafe\u{301}");
+
+        //when
+        editor.delete_backward();
+
+        //then
+        assert_eq!(editor.text(), "caf");
+        assert_eq!(editor.cursor(), 3);
+    }
+
+    #[test]
+    fn stop_at_path_separators_when_moving_by_words() {
+        //given
+        let mut editor = editor_with("cd ~/my-dir/file.txt");
+
+        //when
+        editor.move_word_left();
+        let first_stop = editor.cursor();
+        editor.move_word_left();
+        let second_stop = editor.cursor();
+
+        //then
+        assert_eq!(first_stop, 12);
+        assert_eq!(second_stop, 5);
+    }
+
+    #[test]
+    fn replace_selection_when_typing() {
+        //given
+        let mut editor = editor_with("echo hello");
+        editor.select_left();
+        editor.select_left();
+
+        //when
+        editor.insert_char('p');
+
+        //then
+        assert_eq!(editor.text(), "echo help");
+        assert_eq!(editor.selection(), None);
+    }
+
+    #[test]
+    fn cycle_through_older_kills_when_yank_popping() {
+        //given
+        let mut editor = editor_with("one two");
+        editor.delete_word_backward();
+        editor.delete_word_backward();
+
+        //when
+        editor.yank();
+        editor.yank_pop();
+
+        //then
+        assert_eq!(editor.text(), "two");
+    }
+
+    #[test]
+    fn cycle_through_older_arguments_when_yanking_last_argument() {
+        //given
+        let history = vec![String::from("cd '~/my dir'"), String::from("ls -l src")];
+        let mut editor = editor_with("cat ");
+
+        //when
+        editor.yank_last_argument(&history);
+        let first_paste = editor.text().to_string();
+        editor.yank_last_argument(&history);
+
+        //then
+        assert_eq!(first_paste, "cat src");
+        assert_eq!(editor.text(), "cat '~/my dir'");
+    }
+}
@@ -0,0 +1,241 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+
+// The schemes that are recognized as the start of a link in the output
+const URL_SCHEMES: [&str; 3] = ["https://", "http://", "file://"];
+
+// Represents a link that a child process printed with an OSC 8 sequence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExplicitLink {
+    // The text that the link was attached to
+    pub text: String,
+    pub uri: String,
+}
+
+// Represents a link that is currently shown on the screen
+#[derive(Debug, Clone)]
+pub struct VisibleLink {
+    // What the link leads to, which is either a URL or the absolute path of a file
+    pub target: String,
+    // The target in the form that terminals expect in an OSC 8 sequence
+    pub uri: String,
+    // The position of the first cell of the link text, and how many cells it covers
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+}
+
+// Removes the OSC 8 hyperlink sequences from a line of output, returning the line without them and the links that they described
+// * Both of the terminators that OSC sequences can end with (BEL and ST) are accepted
+pub fn extract_hyperlinks(line: &str) -> (String, Vec<ExplicitLink>) {
+    let mut text = String::new();
+    let mut links = Vec::new();
+    // The URI of the link that is currently open, and where its text starts
+    let mut open_link: Option<(String, usize)> = None;
+    let mut rest = line;
+
+    while let Some(start) = rest.find("\x1b]8;") {
+        text.push_str(&rest[..start]);
+        let sequence = &rest[start + 4..];
+        let Some((body, remainder)) = split_osc_terminator(sequence) else {
+            // * An unterminated sequence is left for the SGR parser to discard
+            text.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+
+        // * The parameters (such as an id) come before the URI, and are not needed
+        let uri = body.split_once(';').map(|(_, uri)| uri).unwrap_or_default();
+        if let Some((uri, link_start)) = open_link.take() {
+            links.push(ExplicitLink {
+                text: strip_escape_sequences(&text[link_start..]),
+                uri,
+            });
+        }
+
+        if !uri.is_empty() {
+            open_link = Some((uri.to_string(), text.len()));
+        }
+
+        rest = remainder;
+    }
+
+    text.push_str(rest);
+    (text, links)
+}
+
+// Splits an OSC sequence at its terminator, returning its body and whatever comes after it
+fn split_osc_terminator(sequence: &str) -> Option<(&str, &str)> {
+    let bel = sequence.find('\x07').map(|index| (index, 1));
+    let st = sequence.find("\x1b\\").map(|index| (index, 2));
+    let (index, length) = match (bel, st) {
+        (Some(bel), Some(st)) => bel.min(st),
+        (bel, st) => bel.or(st)?,
+    };
+
+    Some((&sequence[..index], &sequence[index + length..]))
+}
+
+// Removes the CSI sequences (such as SGR sequences) from a string
+fn strip_escape_sequences(text: &str) -> String {
+    let mut stripped = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.peek() == Some(&'[') {
+            // * A CSI sequence ends with a byte in the range '@' to '~'
+            for c in chars.by_ref().skip(1) {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+
+    stripped
+}
+
+// Finds the links in a line of text, returning where each one is, what it leads to, and its URI
+// * URLs are recognized by their scheme, and file paths are only recognized if the file exists,
+// * so that things like fractions and dates are not mistaken for paths
+fn find_links(text: &str, explicit_links: &[ExplicitLink]) -> Vec<(Range<usize>, String, String)> {
+    let mut links: Vec<(Range<usize>, String, String)> = Vec::new();
+    for link in explicit_links.iter().filter(|link| !link.text.is_empty()) {
+        for (start, _) in text.match_indices(&link.text) {
+            let range = start..start + link.text.len();
+            links.push((range, link.uri.clone(), link.uri.clone()));
+        }
+    }
+
+    for token in text.split_whitespace() {
+        let token_start = token.as_ptr() as usize - text.as_ptr() as usize;
+        let trimmed = token.trim_start_matches(['(', '[', '<', '"', '\'', '`']);
+        let start = token_start + (token.len() - trimmed.len());
+        let trimmed = trimmed.trim_end_matches([')', ']', '>', '"', '\'', '`', ',', '.', ';', ':']);
+
+        let link = match URL_SCHEMES.iter().any(|scheme| trimmed.starts_with(scheme)) {
+            true => Some((trimmed, trimmed.to_string(), trimmed.to_string())),
+            // * Compiler messages put a line and column after the path, e.g. 'src/main.rs:10:5'
+            false => [Some(trimmed), trimmed.split(':').next()]
+                .into_iter()
+                .flatten()
+                .filter(|candidate| candidate.contains('/'))
+                .find_map(|candidate| {
+                    let path = existing_path(candidate)?;
+                    let uri = file_uri(&path);
+                    Some((candidate, path.to_string_lossy().to_string(), uri))
+                }),
+        };
+
+        let Some((link_text, target, uri)) = link else {
+            continue;
+        };
+
+        let range = start..start + link_text.len();
+        let overlaps = links
+            .iter()
+            .any(|(other, _, _)| range.start < other.end && other.start < range.end);
+        if !overlaps {
+            links.push((range, target, uri));
+        }
+    }
+
+    links
+}
+
+// Gets the absolute path of a file, if it exists
+fn existing_path(path: &str) -> Option<PathBuf> {
+    let path = match path.strip_prefix("~/") {
+        Some(relative) => PathBuf::from(std::env::var("HOME").ok()?).join(relative),
+        None => PathBuf::from(path),
+    };
+
+    path.canonicalize().ok()
+}
+
+// Converts the absolute path of a file into a file:// URL, which includes the name of the machine
+#[cfg(unix)]
+fn file_uri(path: &Path) -> String {
+    format!("file://{}{}", crate::prompt::host_name(), path.display())
+}
+
+// * Windows paths start with a drive letter and use backslashes, so they are converted to the form that file:// URLs use
+#[cfg(windows)]
+fn file_uri(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    let path = path.trim_start_matches(r"\\?\").replace('\\', "/");
+    format!("file:///{}", path)
+}
+
+// Percent-encodes the control characters in a URI, so that it cannot end the OSC 8 sequence it is written in
+// * A URI printed by a child process could otherwise smuggle its own escape sequences to the terminal
+fn encode_control_characters(uri: &str) -> String {
+    let mut encoded = String::with_capacity(uri.len());
+    for c in uri.chars() {
+        match c.is_control() {
+            true => {
+                let mut bytes = [0; 4];
+                for byte in c.encode_utf8(&mut bytes).bytes() {
+                    encoded.push_str(&format!("%{:02X}", byte));
+                }
+            }
+            false => encoded.push(c),
+        }
+    }
+
+    encoded
+}
+
+// Finds the links that are shown in the given area of a rendered frame
+// * Each row is searched separately, so a link that was wrapped onto the next row is not found
+pub fn find_visible_links(
+    buffer: &Buffer,
+    area: Rect,
+    explicit_links: &[ExplicitLink],
+) -> Vec<VisibleLink> {
+    let mut visible_links = Vec::new();
+    for y in area.top()..area.bottom() {
+        // Rebuild the text of the row, remembering where each cell starts in it
+        let mut row = String::new();
+        let mut cell_starts = Vec::new();
+        for x in area.left()..area.right() {
+            cell_starts.push(row.len());
+            row.push_str(&buffer.get(x, y).symbol);
+        }
+
+        for (range, target, uri) in find_links(&row, explicit_links) {
+            let first_cell = cell_starts.partition_point(|&start| start <= range.start) - 1;
+            let last_cell = cell_starts.partition_point(|&start| start < range.end) - 1;
+            visible_links.push(VisibleLink {
+                target,
+                uri: encode_control_characters(&uri),
+                x: area.left() + first_cell as u16,
+                y,
+                width: (last_cell - first_cell + 1) as u16,
+            });
+        }
+    }
+
+    visible_links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_control_characters_in_uri() {
+        //given
+        let uri = "https://example.com/\x1b]0;title\x07";
+
+        //when
+        let encoded = encode_control_characters(uri);
+
+        //then
+        assert_eq!(encoded, "https://example.com/%1B]0;title%07");
+    }
+}
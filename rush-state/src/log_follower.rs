@@ -0,0 +1,136 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+
+// The most lines of a log that are kept, with the oldest lines being dropped
+const LINE_LIMIT: usize = 1000;
+// The most bytes that are read at once, so that a huge log does not stall the console
+// * If more than this has been written since the log was last read, only the end of it is read
+const READ_LIMIT: u64 = 256 * 1024;
+
+// Follows a log file as it is written to, like 'tail -f', keeping its most recent lines
+pub struct LogFollower {
+    path: PathBuf,
+    // The number of bytes of the file that have been read
+    offset: u64,
+    // The most recent lines, oldest first, including the last line even if it has not been finished yet
+    lines: VecDeque<String>,
+    // The bytes of the last line that have been read so far, since it may end partway through a character
+    unfinished: Vec<u8>,
+}
+
+impl LogFollower {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            offset: 0,
+            lines: VecDeque::new(),
+            unfinished: Vec::new(),
+        }
+    }
+
+    pub fn lines(&self) -> &VecDeque<String> {
+        &self.lines
+    }
+
+    // Reads anything that has been written to the log since it was last read, returning true if there was anything new
+    // * If the log cannot be read (e.g. because it was deleted), it is treated as unchanged
+    pub fn poll(&mut self) -> bool {
+        let Ok(mut file) = File::open(&self.path) else {
+            return false;
+        };
+
+        let Ok(length) = file.metadata().map(|metadata| metadata.len()) else {
+            return false;
+        };
+
+        // * If the log got shorter, it was truncated or replaced, so it is read again from the start
+        if length < self.offset {
+            self.offset = 0;
+            self.lines.clear();
+            self.unfinished.clear();
+        }
+
+        if length == self.offset {
+            return false;
+        }
+
+        // * If only the end of the new output is read, the line that it starts partway through is left out
+        let start = self.offset.max(length.saturating_sub(READ_LIMIT));
+        let skip_first_line = start > self.offset;
+        let mut bytes = Vec::new();
+        if file.seek(SeekFrom::Start(start)).is_err()
+            || file.take(length - start).read_to_end(&mut bytes).is_err()
+        {
+            return false;
+        }
+
+        self.offset = start + bytes.len() as u64;
+        if skip_first_line {
+            self.unfinished.clear();
+            match bytes.iter().position(|&byte| byte == b'\n') {
+                Some(newline) => _ = bytes.drain(..=newline),
+                None => bytes.clear(),
+            }
+        }
+
+        // The unfinished line is replaced, since more of it may have been read
+        if !self.unfinished.is_empty() {
+            self.lines.pop_back();
+        }
+
+        self.unfinished.extend(bytes);
+        let text = std::mem::take(&mut self.unfinished);
+        let mut chunks = text.split(|&byte| byte == b'\n');
+        // * The last chunk is the line that has not been finished yet, which is empty if the output ended with a newline
+        let unfinished = chunks.next_back().unwrap_or_default();
+        for chunk in chunks {
+            self.lines.push_back(visible_text(chunk));
+        }
+
+        if !unfinished.is_empty() {
+            self.lines.push_back(visible_text(unfinished));
+        }
+
+        self.unfinished = unfinished.to_vec();
+        let excess = self.lines.len().saturating_sub(LINE_LIMIT);
+        self.lines.drain(..excess);
+        true
+    }
+}
+
+// Gets the text of a line as a terminal would show it
+// * Progress bars redraw a line by returning to the start of it, so only the text after the last carriage return is kept
+fn visible_text(line: &[u8]) -> String {
+    let line = String::from_utf8_lossy(line);
+    let line = line.trim_end_matches('\r');
+    line.rsplit('\r').next().unwrap_or_default().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Write;
+
+    #[test]
+    fn follow_lines_as_they_are_written() {
+        //given
+        let path =
+            std::env::temp_dir().join(format!("rush-log-follower-{}.log", std::process::id()));
+        let mut file = File::create(&path).unwrap();
+        let mut follower = LogFollower::new(path.clone());
+        file.write_all(b"first\nsec").unwrap();
+        follower.poll();
+
+        //when
+        file.write_all(b"ond\nloading 50%\rloading 100%\n").unwrap();
+        let changed = follower.poll();
+        _ = std::fs::remove_file(&path);
+
+        //then
+        assert!(changed);
+        assert_eq!(follower.lines(), &["first", "second", "loading 100%"]);
+    }
+}
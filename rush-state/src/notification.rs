@@ -0,0 +1,16 @@
+use std::thread;
+
+use notify_rust::Notification;
+
+// Shows a desktop notification with the given summary and body
+// * Talking to the notification server can take a moment, so it is done on another thread to avoid delaying the prompt
+// $ Failures are ignored, since there may be no notification server (e.g. over SSH)
+pub fn send(summary: String, body: String) {
+    thread::spawn(move || {
+        _ = Notification::new()
+            .appname("rush")
+            .summary(&summary)
+            .body(&body)
+            .show();
+    });
+}
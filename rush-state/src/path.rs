@@ -1,7 +1,7 @@
 use fs_err::canonicalize;
 use std::collections::VecDeque;
 use std::fmt::{Display, Formatter};
-use std::path::PathBuf;
+use std::path::{Component, PathBuf, MAIN_SEPARATOR, MAIN_SEPARATOR_STR};
 
 use anyhow::Result;
 
@@ -10,7 +10,7 @@ use crate::errors::PathError;
 // Wrapper class for a directory path string
 // Adds convenience methods for displaying the path in a user-friendly way,
 // and adds guarantees about path validity that are not provided by PathBuf
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Path {
     absolute_path: PathBuf,
 }
@@ -23,12 +23,12 @@ impl Display for Path {
 
 impl Path {
     // Attempts to construct a new Path from a given path string by resolving it to an absolute path
-    pub fn from_str(path: &str, home_directory: &PathBuf) -> Result<Self> {
+    pub fn from_str(path: &str, home_directory: &std::path::Path) -> Result<Self> {
         Self::from_pathbuf(&PathBuf::from(path), home_directory)
     }
 
     // Attempts to construct a new Path from a given PathBuf by first resolving it to an absolute path
-    fn from_pathbuf(path: &PathBuf, home_directory: &PathBuf) -> Result<Self> {
+    fn from_pathbuf(path: &std::path::Path, home_directory: &std::path::Path) -> Result<Self> {
         // The home directory shorthand must be expanded before resolving the path,
         // because PathBuf is not user-aware and only uses absolute and relative paths
         let expanded_path = expand_home(path, home_directory)?;
@@ -44,23 +44,90 @@ impl Path {
         }
     }
 
+    // Resolves a path string to an absolute path, even if it does not exist yet
+    // * Since a Path must always exist, this returns a PathBuf instead
+    // * '.' and '..' are resolved lexically, but the part of the path that already exists is canonicalized
+    pub fn normalize(
+        path: &str,
+        working_directory: &std::path::Path,
+        home_directory: &std::path::Path,
+    ) -> Result<PathBuf> {
+        let expanded_path = expand_home(&PathBuf::from(path), home_directory)?;
+        // * Joining an absolute path replaces the working directory entirely
+        let joined_path = working_directory.join(expanded_path);
+
+        let mut normalized_path = PathBuf::new();
+        for component in joined_path.components() {
+            match component {
+                Component::CurDir => (),
+                Component::ParentDir => {
+                    normalized_path.pop();
+                }
+                component => normalized_path.push(component),
+            }
+        }
+
+        match normalized_path
+            .ancestors()
+            .find(|ancestor| ancestor.exists())
+        {
+            Some(existing_ancestor) => {
+                let existing_path = canonicalize(existing_ancestor)?;
+                match normalized_path.strip_prefix(existing_ancestor)? {
+                    missing_part if missing_part.as_os_str().is_empty() => Ok(existing_path),
+                    missing_part => Ok(existing_path.join(missing_part)),
+                }
+            }
+            None => Ok(normalized_path),
+        }
+    }
+
+    // Constructs a Path from an absolute path that is already known to exist, without touching the filesystem
+    pub(crate) fn from_existing(absolute_path: PathBuf) -> Self {
+        Self { absolute_path }
+    }
+
     // Attempts to construct a new Path from the PATH environment variable,
     // given the name of an executable that is in the PATH
     pub fn from_path_var(name: &str, path: &VecDeque<Path>) -> Result<Self> {
         if !name.is_empty() {
             for dir in path {
-                let mut path = dir.path().clone();
-                path.push(name);
+                for candidate in executable_candidates(name) {
+                    let mut path = dir.path().clone();
+                    path.push(candidate);
+
+                    if path.is_file() {
+                        return Ok(Self {
+                            absolute_path: path,
+                        });
+                    }
+                }
+            }
+        }
 
-                if path.exists() {
-                    return Ok(Self {
+        Err(PathError::FailedToCanonicalize(PathBuf::from(name)).into())
+    }
+
+    // Finds every executable in the PATH with the given name, in the order they are searched
+    // * Only the first of these is actually run, but the rest are useful for finding out why
+    pub fn all_from_path_var(name: &str, path: &VecDeque<Path>) -> Vec<Self> {
+        let mut executables = Vec::new();
+        if name.is_empty() {
+            return executables;
+        }
+
+        for dir in path {
+            for candidate in executable_candidates(name) {
+                let path = dir.path().join(candidate);
+                if path.is_file() {
+                    executables.push(Self {
                         absolute_path: path,
                     });
                 }
             }
         }
 
-        Err(PathError::FailedToCanonicalize(PathBuf::from(name)).into())
+        executables
     }
 
     // Gets the absolute path, with all directory names included
@@ -71,7 +138,11 @@ impl Path {
     // Gets the shortened version of the path
     // If a truncation factor is provided, the path will be truncated
     // The shortened path will always have the home directory collapsed
-    pub fn collapse(&self, home_directory: &PathBuf, truncation_factor: Option<usize>) -> String {
+    pub fn collapse(
+        &self,
+        home_directory: &std::path::Path,
+        truncation_factor: Option<usize>,
+    ) -> String {
         // ? Is there a less redundant way to write this?
         let path = match self.absolute_path.strip_prefix(home_directory) {
             Ok(path) => {
@@ -79,7 +150,7 @@ impl Path {
                 // ? Is this really necessary? Wouldn't it be fine to just have '~/'?
                 path_string = match path_string.len() {
                     0 => String::from("~"),
-                    _ => format!("~/{}", path_string),
+                    _ => format!("~{}{}", MAIN_SEPARATOR, path_string),
                 };
 
                 path_string
@@ -88,7 +159,7 @@ impl Path {
         };
 
         // $ This might cause a bug with non-unicode characters (paths use OsString which is not guaranteed to be valid unicode)
-        let directories: Vec<String> = path.split('/').map(|d| d.to_string()).collect();
+        let directories: Vec<String> = path.split(MAIN_SEPARATOR).map(|d| d.to_string()).collect();
         let mut truncated_directories = Vec::new();
 
         if let Some(factor) = truncation_factor {
@@ -104,22 +175,56 @@ impl Path {
             truncated_directories = directories;
         }
 
-        truncated_directories.join("/")
+        truncated_directories.join(MAIN_SEPARATOR_STR)
+    }
+}
+
+// Gets the file names that an executable name could refer to
+// * On Windows, executables are usually invoked without their extension, so each extension in PATHEXT is tried as well
+#[cfg(unix)]
+fn executable_candidates(name: &str) -> Vec<String> {
+    vec![name.to_string()]
+}
+
+#[cfg(windows)]
+fn executable_candidates(name: &str) -> Vec<String> {
+    let mut candidates = vec![name.to_string()];
+    if std::path::Path::new(name).extension().is_none() {
+        candidates.extend(
+            executable_extensions()
+                .iter()
+                .map(|extension| format!("{}{}", name, extension)),
+        );
     }
+
+    candidates
+}
+
+// Gets the list of executable file extensions from the PATHEXT environment variable, in lowercase
+#[cfg(windows)]
+pub(crate) fn executable_extensions() -> Vec<String> {
+    std::env::var("PATHEXT")
+        .unwrap_or_else(|_| String::from(".COM;.EXE;.BAT;.CMD"))
+        .split(';')
+        .filter(|extension| !extension.is_empty())
+        .map(|extension| extension.to_lowercase())
+        .collect()
 }
 
 // Expands the home directory shorthand in a path string
-fn expand_home(path: &PathBuf, home_directory: &PathBuf) -> Result<String> {
+fn expand_home(path: &std::path::Path, home_directory: &std::path::Path) -> Result<String> {
     let path = path
         .to_str()
-        .ok_or(PathError::FailedToConvertPathBufToString(path.clone()))?;
+        .ok_or(PathError::FailedToConvertPathBufToString(
+            path.to_path_buf(),
+        ))?;
     if path.starts_with('~') {
         Ok(path.replace(
             '~',
             home_directory
                 .to_str()
                 .ok_or(PathError::FailedToConvertPathBufToString(
-                    home_directory.clone(),
+                    home_directory.to_path_buf(),
                 ))?,
         ))
     } else {
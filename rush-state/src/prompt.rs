@@ -0,0 +1,332 @@
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+// The prompt that is used if none is configured, which matches the prompt from before templates existed
+const DEFAULT_TEMPLATE: &str = "{user} on {cwd}";
+
+// Represents one piece of the prompt panel header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptSegment {
+    // Text that is shown as it was written
+    Text(String),
+    // The name of the current user
+    User,
+    // The name of the machine
+    Host,
+    // The working directory, with the home directory collapsed and the truncation factor applied
+    Cwd,
+    // The current Git branch and its status, or nothing if the working directory is not in a repository
+    Git,
+    // Whether the last command succeeded or failed
+    Status,
+}
+
+// Represents a user-defined layout for the prompt panel header, e.g. '{user}@{host} {cwd} {git}'
+// Placeholders are written in braces, and literal braces are written as '{{' and '}}'
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTemplate {
+    // The template as it was written, so that it can be saved back to the configuration file
+    source: String,
+    segments: Vec<PromptSegment>,
+}
+
+impl Default for PromptTemplate {
+    fn default() -> Self {
+        DEFAULT_TEMPLATE.parse().unwrap()
+    }
+}
+
+impl Display for PromptTemplate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl FromStr for PromptTemplate {
+    type Err = ();
+
+    // * Unknown placeholders and unmatched braces are rejected, so that typos do not silently show up in the prompt
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(()),
+                        }
+                    }
+
+                    let segment = match name.as_str() {
+                        "user" => PromptSegment::User,
+                        "host" => PromptSegment::Host,
+                        "cwd" => PromptSegment::Cwd,
+                        "git" => PromptSegment::Git,
+                        "status" => PromptSegment::Status,
+                        _ => return Err(()),
+                    };
+
+                    if !text.is_empty() {
+                        segments.push(PromptSegment::Text(std::mem::take(&mut text)));
+                    }
+
+                    segments.push(segment);
+                }
+                '}' => return Err(()),
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            segments.push(PromptSegment::Text(text));
+        }
+
+        Ok(Self {
+            source: template.to_string(),
+            segments,
+        })
+    }
+}
+
+impl PromptTemplate {
+    pub fn segments(&self) -> &[PromptSegment] {
+        &self.segments
+    }
+
+    // Checks if the template shows the given kind of segment
+    pub fn contains(&self, segment: &PromptSegment) -> bool {
+        self.segments.contains(segment)
+    }
+}
+
+// Represents the state of the Git repository that the working directory is in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitStatus {
+    // The name of the branch, or the abbreviated commit hash if HEAD is detached
+    pub branch: String,
+    // Whether there are any uncommitted or untracked changes
+    pub dirty: bool,
+    // How many commits the branch is ahead of and behind its upstream branch
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+impl Display for GitStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.branch)?;
+        if self.dirty {
+            write!(f, "*")?;
+        }
+
+        if self.ahead > 0 {
+            write!(f, " ↑{}", self.ahead)?;
+        }
+
+        if self.behind > 0 {
+            write!(f, " ↓{}", self.behind)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl GitStatus {
+    // Gets the full status of the repository that the given directory is in by running 'git status'
+    // * This can take a while in large repositories, so it should not be called on the thread that handles input
+    fn from_git(directory: &Path) -> Option<Self> {
+        let output = Command::new("git")
+            .args(["status", "--porcelain=v2", "--branch"])
+            .current_dir(directory)
+            // * Optional locks are skipped so that the prompt never gets in the way of a Git command the user runs
+            .env("GIT_OPTIONAL_LOCKS", "0")
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        let mut status = Self {
+            branch: String::new(),
+            dirty: false,
+            ahead: 0,
+            behind: 0,
+        };
+        let mut commit = String::new();
+
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            match line.strip_prefix("# ") {
+                Some(header) => match header.split_once(' ') {
+                    Some(("branch.head", head)) => status.branch = head.to_string(),
+                    Some(("branch.oid", oid)) => commit = oid.chars().take(7).collect(),
+                    Some(("branch.ab", counts)) => {
+                        for count in counts.split(' ') {
+                            match count.split_at(1) {
+                                ("+", ahead) => status.ahead = ahead.parse().unwrap_or(0),
+                                ("-", behind) => status.behind = behind.parse().unwrap_or(0),
+                                _ => (),
+                            }
+                        }
+                    }
+                    _ => (),
+                },
+                // * Every line that is not a header describes a changed or untracked file
+                None => status.dirty = true,
+            }
+        }
+
+        if status.branch == "(detached)" {
+            status.branch = commit;
+        }
+
+        Some(status)
+    }
+}
+
+// Keeps track of the Git status shown in the prompt, which is refreshed in the background after every command
+#[derive(Default)]
+pub struct GitStatusCache {
+    // The directory that the status belongs to
+    directory: Option<PathBuf>,
+    status: Option<GitStatus>,
+    // Receives the result of the refresh that is in progress, if there is one
+    pending: Option<Receiver<Option<GitStatus>>>,
+}
+
+impl GitStatusCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Starts computing the Git status for the given directory in the background
+    // * If the directory changed, the branch is shown right away, since reading it is fast,
+    // * and otherwise the old status is shown until the new one is ready, so that the prompt does not flicker
+    pub fn refresh(&mut self, directory: &Path) {
+        if self.directory.as_deref() != Some(directory) {
+            self.directory = Some(directory.to_path_buf());
+            self.status = git_branch(directory).map(|branch| GitStatus {
+                branch,
+                dirty: false,
+                ahead: 0,
+                behind: 0,
+            });
+        }
+
+        // * Outside of a repository, there is nothing to compute
+        if self.status.is_none() {
+            self.pending = None;
+            return;
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        let directory = directory.to_path_buf();
+        thread::spawn(move || sender.send(GitStatus::from_git(&directory)));
+        self.pending = Some(receiver);
+    }
+
+    // Checks if a refresh has finished, returning true if the status changed
+    // * If 'git' could not be run, the branch that was read directly is kept
+    pub fn poll(&mut self) -> bool {
+        let Some(receiver) = &self.pending else {
+            return false;
+        };
+
+        match receiver.try_recv() {
+            Ok(status) => {
+                self.pending = None;
+                match status {
+                    Some(status) if self.status.as_ref() != Some(&status) => {
+                        self.status = Some(status);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            Err(TryRecvError::Empty) => false,
+            Err(TryRecvError::Disconnected) => {
+                self.pending = None;
+                false
+            }
+        }
+    }
+
+    // Checks if a refresh is still in progress
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    pub fn status(&self) -> Option<&GitStatus> {
+        self.status.as_ref()
+    }
+}
+
+// Gets the name of the machine that the shell is running on
+#[cfg(unix)]
+pub fn host_name() -> String {
+    let mut buffer = [0u8; 256];
+    // * gethostname() is always safe to call with a valid buffer, and the result is null-terminated if it fits
+    let result =
+        unsafe { libc::gethostname(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len()) };
+    if result != 0 {
+        return String::new();
+    }
+
+    let length = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+    String::from_utf8_lossy(&buffer[..length]).to_string()
+}
+
+#[cfg(windows)]
+pub fn host_name() -> String {
+    std::env::var("COMPUTERNAME").unwrap_or_default()
+}
+
+// Gets the Git branch that the given directory is on, by reading the HEAD file of the repository it is in
+// * A detached HEAD is shown as the first 7 characters of the commit hash, like Git itself does
+pub fn git_branch(directory: &Path) -> Option<String> {
+    let git_path = directory
+        .ancestors()
+        .map(|ancestor| ancestor.join(".git"))
+        .find(|git_path| git_path.exists())?;
+
+    // * In worktrees and submodules, .git is a file that points to the real Git directory
+    let git_directory = match git_path.is_file() {
+        true => {
+            let contents = fs_err::read_to_string(&git_path).ok()?;
+            let target = contents.trim().strip_prefix("gitdir: ")?;
+            git_path.parent()?.join(target)
+        }
+        false => git_path,
+    };
+
+    let head = fs_err::read_to_string(git_directory.join("HEAD")).ok()?;
+    let head = head.trim();
+    match head.strip_prefix("ref: ") {
+        Some(reference) => Some(
+            reference
+                .strip_prefix("refs/heads/")
+                .unwrap_or(reference)
+                .to_string(),
+        ),
+        None => Some(head.chars().take(7).collect()),
+    }
+}
@@ -1,7 +1,20 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::time::Duration;
+
 use anyhow::Result;
 
-use crate::config::Configuration;
+use crate::config::{self, Configuration, HistoryDedup, SettingSource};
+use crate::config_watcher::ConfigWatcher;
 use crate::environment::Environment;
+use crate::errors::ShellError;
+use crate::glob::glob_to_regex;
+use crate::jobs::JobTable;
+use crate::signature::CommandSignature;
+
+// The location of the configuration file
+const CONFIG_FILE_PATH: &str = "config/config.toml";
 
 // Represents the shell, its state, and provides methods for interacting with it
 // ? Should this be called ShellState or something like that?
@@ -10,19 +23,96 @@ pub struct Shell {
     pub(crate) environment: Environment,
     pub(crate) config: Configuration,
     pub(crate) command_success: bool,
+    // The exit code of the last command, which is 1 for a failed builtin
+    pub(crate) exit_code: i32,
+    // The exit code of the last executable that was run by the current command, if it failed
+    pub(crate) executable_exit_code: Option<i32>,
     pub(crate) command_history: Vec<String>,
+    pub(crate) last_resource_usage: Option<ResourceUsage>,
+    // How long the last command took to run, including any builtins
+    pub(crate) last_duration: Option<Duration>,
+    pub(crate) last_error: Option<LastError>,
+    pub(crate) jobs: JobTable,
+    // The names and aliases of every builtin, used for completing command names
+    pub(crate) builtin_names: Vec<String>,
+    // The arguments of every builtin that parses them with clap, keyed by name and alias, used for the argument hint
+    pub(crate) builtin_signatures: HashMap<String, CommandSignature>,
+    // Watches the configuration file so that changes to it are applied without restarting, if watching it is supported
+    pub(crate) config_watcher: Option<ConfigWatcher>,
+    // The canonical paths of the scripts that are being sourced, outermost first, so that a script cannot source itself
+    pub(crate) sourcing: Vec<PathBuf>,
+}
+
+// Represents the most recent error, so that it can be investigated after the fact
+// * This is kept even when errors are not shown, so that show-errors can be turned off without losing information
+#[derive(Debug)]
+pub struct LastError {
+    // The line of input that caused the error
+    pub command: String,
+    // The name of the error's variant, e.g. 'PathNotFound', or 'Other' if it did not come from the shell
+    pub kind: &'static str,
+    pub error: anyhow::Error,
+}
+
+// Represents the resources consumed by an executable over its lifetime
+#[derive(Debug, Clone, Copy)]
+pub struct ResourceUsage {
+    // The real time elapsed between spawning the executable and its exit
+    pub wall_time: Duration,
+    // The CPU time spent in user mode
+    pub user_time: Duration,
+    // The CPU time spent in kernel mode
+    pub system_time: Duration,
+    // The peak resident set size, in kilobytes
+    pub max_rss: u64,
+}
+
+impl Display for ResourceUsage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "real {:.2}s, user {:.2}s, sys {:.2}s, max rss {:.1} MB",
+            self.wall_time.as_secs_f64(),
+            self.user_time.as_secs_f64(),
+            self.system_time.as_secs_f64(),
+            self.max_rss as f64 / 1024.0
+        )
+    }
 }
 
 impl Shell {
     pub fn new() -> Result<Self> {
-        let config =
-            Configuration::from_file("config/config.rush").unwrap_or(Configuration::default());
+        // * A missing configuration file just means the defaults are used, but any other problem is reported once the console is ready
+        let config_path = absolute_config_path();
+        let mut config = Configuration::from_file(&config_path).unwrap_or_else(|e| {
+            let mut config = Configuration::default();
+            if !matches!(
+                e.downcast_ref(),
+                Some(ShellError::FailedToOpenConfigFile(_))
+            ) {
+                config.warnings.push(e.to_string());
+            }
+
+            config
+        });
+        // * Even if the configuration could not be loaded, persistent changes should still go to the usual file
+        config.file_path.get_or_insert_with(|| config_path.clone());
 
         Ok(Self {
             environment: Environment::new()?,
             config,
             command_success: true,
+            exit_code: 0,
+            executable_exit_code: None,
             command_history: Vec::new(),
+            last_resource_usage: None,
+            last_duration: None,
+            last_error: None,
+            jobs: JobTable::new(),
+            builtin_names: Vec::new(),
+            builtin_signatures: HashMap::new(),
+            config_watcher: ConfigWatcher::new(&config_path).ok(),
+            sourcing: Vec::new(),
         })
     }
 
@@ -42,33 +132,214 @@ impl Shell {
         &mut self.config
     }
 
+    // Re-reads the configuration file, replacing the current configuration entirely
+    // * If the file cannot be loaded, the current configuration is kept as it was
+    pub fn reload_config(&mut self) -> Result<()> {
+        let file_path = self
+            .config
+            .file_path
+            .clone()
+            .unwrap_or_else(|| CONFIG_FILE_PATH.to_string());
+        self.config = Configuration::from_file(&file_path)?;
+        Ok(())
+    }
+
+    // Records that a script is being sourced, returning false if it is already being sourced further up
+    pub fn start_sourcing(&mut self, path: PathBuf) -> bool {
+        if self.sourcing.contains(&path) {
+            return false;
+        }
+
+        self.sourcing.push(path);
+        true
+    }
+
+    // Records that the script that was sourced most recently has finished
+    pub fn finish_sourcing(&mut self) {
+        self.sourcing.pop();
+    }
+
+    pub fn watches_config(&self) -> bool {
+        self.config_watcher.is_some()
+    }
+
+    // Re-reads the configuration file if it has changed since this was last called, returning a description of each change
+    // * If the file cannot be loaded, the current configuration is kept and the error is returned instead
+    pub fn reload_changed_config(&mut self) -> Option<Result<Vec<String>>> {
+        if !self.config_watcher.as_ref()?.poll() {
+            return None;
+        }
+
+        let file_path = self
+            .config
+            .file_path
+            .clone()
+            .unwrap_or_else(|| CONFIG_FILE_PATH.to_string());
+        Some(Configuration::from_file(&file_path).map(|mut config| {
+            // * Settings changed with configure but not saved are kept, since saving an alias or key binding also writes the file
+            for key in config::setting_keys() {
+                if self.config.source(&key) == SettingSource::Runtime {
+                    if let Some(value) = self.config.get(&key) {
+                        _ = config.set(&key, &value, SettingSource::Runtime);
+                    }
+                }
+            }
+
+            let changes = config.changes_from(&self.config);
+            self.config = config;
+            changes
+        }))
+    }
+
+    pub fn set_builtin_names(&mut self, names: Vec<String>) {
+        self.builtin_names = names;
+    }
+
+    pub fn set_builtin_signatures(&mut self, signatures: HashMap<String, CommandSignature>) {
+        self.builtin_signatures = signatures;
+    }
+
     pub fn success(&self) -> bool {
         self.command_success
     }
 
+    // Records whether the last command succeeded, which also determines its exit code
+    // * This should be called after every command, so that the exit code of a failed executable does not carry over to the next one
     pub fn set_success(&mut self, success: bool) {
+        let executable_exit_code = self.executable_exit_code.take();
         self.command_success = success;
+        self.exit_code = match success {
+            true => 0,
+            false => executable_exit_code.unwrap_or(1),
+        };
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        self.exit_code
+    }
+
+    // Records the exit code of an executable that failed, so that it becomes the exit code of the command
+    pub fn set_executable_exit_code(&mut self, code: i32) {
+        self.executable_exit_code = Some(code);
+    }
+
+    pub fn last_resource_usage(&self) -> Option<&ResourceUsage> {
+        self.last_resource_usage.as_ref()
+    }
+
+    pub fn last_duration(&self) -> Option<Duration> {
+        self.last_duration
+    }
+
+    pub fn set_last_duration(&mut self, duration: Duration) {
+        self.last_duration = Some(duration);
+    }
+
+    pub fn set_last_resource_usage(&mut self, usage: ResourceUsage) {
+        self.last_resource_usage = Some(usage);
+    }
+
+    pub fn last_error(&self) -> Option<&LastError> {
+        self.last_error.as_ref()
+    }
+
+    pub fn set_last_error(&mut self, command: String, kind: &'static str, error: anyhow::Error) {
+        self.last_error = Some(LastError {
+            command,
+            kind,
+            error,
+        });
+    }
+
+    pub fn jobs(&self) -> &JobTable {
+        &self.jobs
+    }
+
+    pub fn jobs_mut(&mut self) -> &mut JobTable {
+        &mut self.jobs
     }
 
     pub fn history(&self) -> &Vec<String> {
         &self.command_history
     }
 
-    // Adds a line of input to the command history
-    // If it already exists in the history, brings the previous occurrence to the front
+    // Removes every command from the command history
+    pub fn history_clear(&mut self) {
+        self.command_history.clear();
+    }
+
+    // Removes the most recent commands from the command history, returning how many were removed
+    pub fn history_remove_last(&mut self, count: usize) -> usize {
+        let remaining = self.command_history.len().saturating_sub(count);
+        let removed = self.command_history.len() - remaining;
+        self.command_history.truncate(remaining);
+        removed
+    }
+
+    // Adds a line of input to the command history, unless the configuration says to leave it out
     pub fn history_add(&mut self, command: String) {
-        match self.command_history.contains(&command) {
-            true => {
-                let index = self
-                    .command_history
-                    .iter()
-                    .position(|c| c == &command)
-                    .unwrap();
-                self.command_history.remove(index);
+        add_to_history(&mut self.command_history, command, &self.config);
+    }
+}
+
+// Adds a command to a history, following the history settings of the configuration
+// Depending on the configuration, a repeated command is either kept, left out, or brought to the front
+fn add_to_history(history: &mut Vec<String>, command: String, config: &Configuration) {
+    // * A leading space is a common way to keep a one-off command (e.g. one with a password in it) out of the history
+    if config.history_ignore_space && command.starts_with(' ') {
+        return;
+    }
+
+    if config.history_ignore.iter().any(|pattern| {
+        glob_to_regex(pattern, false).is_ok_and(|regex| regex.is_match(command.trim()))
+    }) {
+        return;
+    }
+
+    match config.history_dedup {
+        HistoryDedup::Off => (),
+        HistoryDedup::Consecutive if history.last() == Some(&command) => return,
+        HistoryDedup::Consecutive => (),
+        HistoryDedup::All => {
+            if let Some(index) = history.iter().position(|c| c == &command) {
+                history.remove(index);
             }
-            false => (),
+        }
+    }
+
+    history.push(command)
+}
+
+// Gets the absolute path of the configuration file, relative to the directory the shell was started in
+// * The path is resolved once at startup and kept in the configuration, so that changing directories
+// * does not change which file is saved to, reloaded, or watched
+fn absolute_config_path() -> String {
+    std::env::current_dir()
+        .map(|directory| directory.join(CONFIG_FILE_PATH))
+        .unwrap_or_else(|_| PathBuf::from(CONFIG_FILE_PATH))
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leave_ignored_and_repeated_commands_out_of_history() {
+        //given
+        let mut config = Configuration::default();
+        config.history_dedup = HistoryDedup::Consecutive;
+        config.history_ignore_space = true;
+        config.history_ignore = vec![String::from("*TOKEN=*")];
+        let mut history = Vec::new();
+
+        //when
+        for command in ["ls", "ls", " secret", "export TOKEN=abc", "pwd", "ls"] {
+            add_to_history(&mut history, command.to_string(), &config);
         }
 
-        self.command_history.push(command)
+        //then
+        assert_eq!(history, ["ls", "pwd", "ls"]);
     }
 }
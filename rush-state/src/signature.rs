@@ -0,0 +1,231 @@
+// Represents the arguments that a command accepts, which are shown as a hint below the prompt while the command is typed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandSignature {
+    pub name: String,
+    pub options: Vec<OptionSignature>,
+    pub positionals: Vec<PositionalSignature>,
+    // The subcommands that the first positional argument can name, if the command has any
+    pub subcommands: Vec<CommandSignature>,
+}
+
+// Represents an option such as '-s <SORT>' or '--all'
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OptionSignature {
+    pub short: Option<char>,
+    pub long: Option<String>,
+    // The name of the value that comes after the option, if it takes one
+    pub value_name: Option<String>,
+    // The values that the option accepts, if only certain ones are allowed
+    pub possible_values: Vec<String>,
+}
+
+// Represents a positional argument such as '<PATH>' or '[TEXT]...'
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PositionalSignature {
+    pub name: String,
+    pub required: bool,
+    // Whether the argument takes every remaining value, rather than just one
+    pub variadic: bool,
+    pub possible_values: Vec<String>,
+}
+
+// Represents what a command expects to be typed next
+#[derive(Debug, PartialEq, Eq)]
+pub enum ExpectedArgument<'a> {
+    // The value of an option that was just typed
+    OptionValue(&'a CommandSignature, &'a OptionSignature),
+    // Any of the options of the command, because a word starting with a dash is being typed
+    Options(&'a CommandSignature),
+    // The positional argument at the given index
+    Positional(&'a CommandSignature, usize),
+    // Nothing, because every argument has already been given
+    Nothing(&'a CommandSignature),
+}
+
+impl OptionSignature {
+    // Formats the option as it would be typed, preferring the long form, e.g. '--sort <SORT>'
+    pub fn usage(&self) -> String {
+        let flag = match (&self.long, self.short) {
+            (Some(long), _) => format!("--{}", long),
+            (None, Some(short)) => format!("-{}", short),
+            (None, None) => String::new(),
+        };
+
+        match &self.value_name {
+            Some(value_name) => format!("{} <{}>", flag, value_name),
+            None => flag,
+        }
+    }
+}
+
+impl PositionalSignature {
+    // Formats the argument like clap does, e.g. '<PATH>' if it is required and '[TEXT]...' if it is optional and variadic
+    pub fn usage(&self) -> String {
+        let name = match self.required {
+            true => format!("<{}>", self.name),
+            false => format!("[{}]", self.name),
+        };
+
+        match self.variadic {
+            true => format!("{}...", name),
+            false => name,
+        }
+    }
+}
+
+impl CommandSignature {
+    // Finds the option that a word typed as an option refers to, e.g. '--sort' or '-s'
+    fn find_option(&self, flag: &str) -> Option<&OptionSignature> {
+        match flag.strip_prefix("--") {
+            Some(long) => self
+                .options
+                .iter()
+                .find(|option| option.long.as_deref() == Some(long)),
+            None => {
+                let short = flag.strip_prefix('-')?.chars().next()?;
+                self.options
+                    .iter()
+                    .find(|option| option.short == Some(short))
+            }
+        }
+    }
+
+    // Works out what should be typed next, given the words typed after the command name
+    // * The last word is the one being typed, which is empty if the line ends with a space
+    // * Words are split on whitespace, so quoted arguments containing spaces are counted as several words
+    pub fn expected_argument<'a>(&'a self, words: &[&str]) -> ExpectedArgument<'a> {
+        let Some((current, finished)) = words.split_last() else {
+            return self.next_positional(0);
+        };
+
+        let mut positional_index = 0;
+        let mut only_positionals = false;
+        let mut pending_option: Option<&OptionSignature> = None;
+
+        for (index, word) in finished.iter().enumerate() {
+            if pending_option.take().is_some() {
+                continue;
+            }
+
+            if !only_positionals && *word == "--" {
+                only_positionals = true;
+            } else if !only_positionals && word.starts_with("--") {
+                // * A value can be given in the same word, e.g. '--sort=size'
+                if !word.contains('=') {
+                    pending_option = self.find_option(word).filter(|o| o.value_name.is_some());
+                }
+            } else if !only_positionals && word.starts_with('-') && word.len() > 1 {
+                // * Short options can be grouped, e.g. '-la', and only the last one can take the next word as its value
+                let last_flag = format!("-{}", word.chars().last().unwrap());
+                pending_option = self
+                    .find_option(&last_flag)
+                    .filter(|o| o.value_name.is_some() && word.len() == 2);
+            } else {
+                let subcommand = match positional_index {
+                    0 => self.subcommands.iter().find(|s| s.name == *word),
+                    _ => None,
+                };
+
+                if let Some(subcommand) = subcommand {
+                    return subcommand.expected_argument(&words[index + 1..]);
+                }
+
+                let variadic = self
+                    .positionals
+                    .get(positional_index)
+                    .is_some_and(|positional| positional.variadic);
+                if !variadic {
+                    positional_index += 1;
+                }
+            }
+        }
+
+        match pending_option {
+            Some(option) => ExpectedArgument::OptionValue(self, option),
+            None if !only_positionals && current.starts_with('-') => {
+                ExpectedArgument::Options(self)
+            }
+            None => self.next_positional(positional_index),
+        }
+    }
+
+    fn next_positional(&self, index: usize) -> ExpectedArgument<'_> {
+        match index < self.positional_count() {
+            true => ExpectedArgument::Positional(self, index),
+            false => ExpectedArgument::Nothing(self),
+        }
+    }
+
+    // Gets the number of positional arguments, counting the subcommand as one
+    pub fn positional_count(&self) -> usize {
+        self.positionals.len() + usize::from(!self.subcommands.is_empty())
+    }
+
+    // Gets the usage of the positional argument at the given index, where the subcommand comes first if there is one
+    pub fn positional_usage(&self, index: usize) -> String {
+        match (self.subcommands.is_empty(), index) {
+            (false, 0) => {
+                let names: Vec<_> = self.subcommands.iter().map(|s| s.name.as_str()).collect();
+                format!("<{}>", names.join("|"))
+            }
+            (false, index) => self.positionals[index - 1].usage(),
+            (true, index) => self.positionals[index].usage(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list_signature() -> CommandSignature {
+        CommandSignature {
+            name: String::from("list"),
+            options: vec![
+                OptionSignature {
+                    short: Some('a'),
+                    long: Some(String::from("all")),
+                    ..Default::default()
+                },
+                OptionSignature {
+                    short: Some('s'),
+                    long: Some(String::from("sort")),
+                    value_name: Some(String::from("SORT")),
+                    possible_values: vec![String::from("name"), String::from("size")],
+                },
+            ],
+            positionals: vec![PositionalSignature {
+                name: String::from("PATH"),
+                ..Default::default()
+            }],
+            subcommands: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn expect_option_value_after_option_that_takes_one() {
+        //given
+        let signature = list_signature();
+
+        //when
+        let expected = signature.expected_argument(&["-a", "--sort", ""]);
+
+        //then
+        assert_eq!(
+            expected,
+            ExpectedArgument::OptionValue(&signature, &signature.options[1])
+        );
+    }
+
+    #[test]
+    fn expect_nothing_after_every_positional_is_given() {
+        //given
+        let signature = list_signature();
+
+        //when
+        let expected = signature.expected_argument(&["-s", "size", "src", ""]);
+
+        //then
+        assert_eq!(expected, ExpectedArgument::Nothing(&signature));
+    }
+}
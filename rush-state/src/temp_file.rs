@@ -0,0 +1,49 @@
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+// The number of names that are tried before giving up on creating a temporary file
+const MAX_ATTEMPTS: usize = 100;
+
+// Creates a new file in the temporary directory that only the current user can read or write, returning its path and the file
+// * The file must not already exist, so a file or symbolic link that someone else planted under the same name is never written to,
+// * and if the name is taken, a number is added to it until a free name is found
+// * The file is opened for appending, so that anything writing to it (e.g. a child process) always writes at the end
+pub fn create(name: &str, extension: &str) -> io::Result<(PathBuf, fs_err::File)> {
+    let directory = std::env::temp_dir();
+    let mut attempt = 0;
+    loop {
+        let file_name = match attempt {
+            0 => format!("{}.{}", name, extension),
+            _ => format!("{}-{}.{}", name, attempt, extension),
+        };
+
+        let path = directory.join(file_name);
+        match open_new(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists && attempt < MAX_ATTEMPTS => {
+                attempt += 1
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn open_new(path: &Path) -> io::Result<fs_err::File> {
+    use fs_err::os::unix::fs::OpenOptionsExt;
+
+    fs_err::OpenOptions::new()
+        .append(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+}
+
+// * The temporary directory on Windows is already private to each user
+#[cfg(windows)]
+fn open_new(path: &Path) -> io::Result<fs_err::File> {
+    fs_err::OpenOptions::new()
+        .append(true)
+        .create_new(true)
+        .open(path)
+}
@@ -0,0 +1,313 @@
+use ratatui::style::{Color, Modifier};
+use ratatui::widgets::BorderType;
+
+// The names of the colors in a theme, as they are written in the configuration file (after 'color-')
+pub const COLOR_NAMES: [&str; 13] = [
+    "user",
+    "cwd",
+    "git",
+    "success",
+    "failure",
+    "pending",
+    "command",
+    "border",
+    "title",
+    "debug-value",
+    "directory",
+    "symlink",
+    "executable",
+];
+
+// Represents a color scheme for the TUI console
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub name: String,
+    // The color of the username in the prompt panel header
+    pub user: Color,
+    // The color of the working directory in the prompt panel header
+    pub cwd: Color,
+    // The color of the Git branch in the prompt panel header
+    pub git: Color,
+    // The colors of the prompt/output ticks after a command succeeds or fails
+    pub success: Color,
+    pub failure: Color,
+    // The color of the output tick while a command is still running
+    pub pending: Color,
+    // The color of commands echoed into the output panel
+    pub command: Color,
+    // The color of the panel borders
+    pub border: Color,
+    // The line style of the panel borders
+    pub border_type: BorderType,
+    // The color of the panel titles
+    pub title: Color,
+    // The color of the values shown in the debug panel
+    pub debug_value: Color,
+    // The colors of directories, symbolic links, and executables in directory listings
+    pub directory: Color,
+    pub symlink: Color,
+    pub executable: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            name: String::from("default"),
+            // $ RGB values do not work on some terminals
+            user: Color::Rgb(0, 150, 255),
+            cwd: Color::Rgb(0, 255, 0),
+            git: Color::LightMagenta,
+            success: Color::LightGreen,
+            failure: Color::LightRed,
+            pending: Color::DarkGray,
+            command: Color::LightYellow,
+            border: Color::Reset,
+            border_type: BorderType::Plain,
+            title: Color::LightCyan,
+            debug_value: Color::LightGreen,
+            directory: Color::LightBlue,
+            symlink: Color::LightCyan,
+            executable: Color::LightGreen,
+        }
+    }
+}
+
+impl Theme {
+    // Gets every theme that is included with the shell
+    pub fn builtin_themes() -> Vec<Self> {
+        vec![
+            Self::default(),
+            Self {
+                name: String::from("ocean"),
+                user: Color::LightBlue,
+                cwd: Color::Cyan,
+                command: Color::White,
+                border: Color::Blue,
+                border_type: BorderType::Rounded,
+                title: Color::LightBlue,
+                debug_value: Color::Cyan,
+                directory: Color::Blue,
+                ..Self::default()
+            },
+            Self {
+                name: String::from("ember"),
+                user: Color::LightRed,
+                cwd: Color::Yellow,
+                success: Color::Yellow,
+                failure: Color::Red,
+                command: Color::LightYellow,
+                border: Color::Red,
+                border_type: BorderType::Thick,
+                title: Color::LightRed,
+                debug_value: Color::Yellow,
+                directory: Color::LightRed,
+                executable: Color::Yellow,
+                ..Self::default()
+            },
+            Self {
+                name: String::from("forest"),
+                user: Color::Green,
+                cwd: Color::LightGreen,
+                command: Color::LightGreen,
+                border: Color::Green,
+                border_type: BorderType::Rounded,
+                title: Color::LightGreen,
+                directory: Color::Green,
+                executable: Color::LightYellow,
+                ..Self::default()
+            },
+            // * This only uses the terminal's own foreground color, for terminals with poor color support
+            Self {
+                name: String::from("monochrome"),
+                user: Color::Reset,
+                cwd: Color::Reset,
+                git: Color::Reset,
+                success: Color::Reset,
+                failure: Color::Reset,
+                pending: Color::Reset,
+                command: Color::Reset,
+                border: Color::Reset,
+                border_type: BorderType::Plain,
+                title: Color::Reset,
+                debug_value: Color::Reset,
+                directory: Color::Reset,
+                symlink: Color::Reset,
+                executable: Color::Reset,
+            },
+        ]
+    }
+
+    // Gets an included theme by name, if it exists
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::builtin_themes()
+            .into_iter()
+            .find(|theme| theme.name == name)
+    }
+
+    // Gets one of the colors in the theme by its name in the configuration file
+    pub fn color(&self, name: &str) -> Option<Color> {
+        Some(match name {
+            "user" => self.user,
+            "cwd" => self.cwd,
+            "git" => self.git,
+            "success" => self.success,
+            "failure" => self.failure,
+            "pending" => self.pending,
+            "command" => self.command,
+            "border" => self.border,
+            "title" => self.title,
+            "debug-value" => self.debug_value,
+            "directory" => self.directory,
+            "symlink" => self.symlink,
+            "executable" => self.executable,
+            _ => return None,
+        })
+    }
+
+    // Replaces one of the colors in the theme by its name in the configuration file
+    pub fn set_color(&mut self, name: &str, color: Color) {
+        let field = match name {
+            "user" => &mut self.user,
+            "cwd" => &mut self.cwd,
+            "git" => &mut self.git,
+            "success" => &mut self.success,
+            "failure" => &mut self.failure,
+            "pending" => &mut self.pending,
+            "command" => &mut self.command,
+            "border" => &mut self.border,
+            "title" => &mut self.title,
+            "debug-value" => &mut self.debug_value,
+            "directory" => &mut self.directory,
+            "symlink" => &mut self.symlink,
+            "executable" => &mut self.executable,
+            _ => return,
+        };
+
+        *field = color;
+    }
+}
+
+// Parses a color from its configuration file representation,
+// which is either a color name (e.g. 'light-blue'), a hex code (e.g. '#0096ff'), or a 256-color index (e.g. '208')
+pub fn parse_color(value: &str) -> Option<Color> {
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+
+        let component = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+        return Some(Color::Rgb(component(0)?, component(2)?, component(4)?));
+    }
+
+    if let Ok(index) = value.parse() {
+        return Some(Color::Indexed(index));
+    }
+
+    Some(match value.to_lowercase().replace('_', "-").as_str() {
+        "reset" | "default" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "dark-gray" | "dark-grey" => Color::DarkGray,
+        "light-red" => Color::LightRed,
+        "light-green" => Color::LightGreen,
+        "light-yellow" => Color::LightYellow,
+        "light-blue" => Color::LightBlue,
+        "light-magenta" => Color::LightMagenta,
+        "light-cyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    })
+}
+
+// Formats a color the same way it is written in the configuration file
+pub fn format_color(color: Color) -> String {
+    match color {
+        Color::Reset => String::from("reset"),
+        Color::Black => String::from("black"),
+        Color::Red => String::from("red"),
+        Color::Green => String::from("green"),
+        Color::Yellow => String::from("yellow"),
+        Color::Blue => String::from("blue"),
+        Color::Magenta => String::from("magenta"),
+        Color::Cyan => String::from("cyan"),
+        Color::Gray => String::from("gray"),
+        Color::DarkGray => String::from("dark-gray"),
+        Color::LightRed => String::from("light-red"),
+        Color::LightGreen => String::from("light-green"),
+        Color::LightYellow => String::from("light-yellow"),
+        Color::LightBlue => String::from("light-blue"),
+        Color::LightMagenta => String::from("light-magenta"),
+        Color::LightCyan => String::from("light-cyan"),
+        Color::White => String::from("white"),
+        Color::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        Color::Indexed(index) => index.to_string(),
+    }
+}
+
+// Parses a border line style from its configuration file representation
+pub fn parse_border_type(value: &str) -> Option<BorderType> {
+    match value {
+        "plain" => Some(BorderType::Plain),
+        "rounded" => Some(BorderType::Rounded),
+        "double" => Some(BorderType::Double),
+        "thick" => Some(BorderType::Thick),
+        _ => None,
+    }
+}
+
+// Formats a border line style the same way it is written in the configuration file
+pub fn format_border_type(border_type: BorderType) -> String {
+    String::from(match border_type {
+        BorderType::Plain => "plain",
+        BorderType::Rounded => "rounded",
+        BorderType::Double => "double",
+        BorderType::Thick => "thick",
+    })
+}
+
+// The names of the text modifiers that can be used in styles, in the order they are listed
+const MODIFIER_NAMES: [(&str, Modifier); 7] = [
+    ("bold", Modifier::BOLD),
+    ("dim", Modifier::DIM),
+    ("italic", Modifier::ITALIC),
+    ("underlined", Modifier::UNDERLINED),
+    ("blink", Modifier::SLOW_BLINK),
+    ("rapid-blink", Modifier::RAPID_BLINK),
+    ("reversed", Modifier::REVERSED),
+];
+
+// Parses a set of text modifiers from its configuration file representation, e.g. 'bold,blink' or 'none'
+pub fn parse_modifiers(value: &str) -> Option<Modifier> {
+    if value == "none" {
+        return Some(Modifier::empty());
+    }
+
+    value
+        .split(',')
+        .try_fold(Modifier::empty(), |modifiers, name| {
+            let (_, modifier) = MODIFIER_NAMES
+                .iter()
+                .find(|(modifier_name, _)| *modifier_name == name.trim())?;
+            Some(modifiers | *modifier)
+        })
+}
+
+// Formats a set of text modifiers the same way it is written in the configuration file
+pub fn format_modifiers(modifiers: Modifier) -> String {
+    let names: Vec<&str> = MODIFIER_NAMES
+        .iter()
+        .filter(|(_, modifier)| modifiers.contains(*modifier))
+        .map(|(name, _)| *name)
+        .collect();
+
+    match names.is_empty() {
+        true => String::from("none"),
+        false => names.join(","),
+    }
+}
@@ -1,11 +1,16 @@
+use std::time::Instant;
+
 use anyhow::Result;
 
 use rush_eval::dispatcher::Dispatcher;
-use rush_eval::errors::DispatchError;
-use rush_state::console::{Console, restore_terminal};
+use rush_eval::errors::{error_kind, DispatchError};
+use rush_state::console::{restore_terminal, Console};
 use rush_state::shell::Shell;
 use rush_state::showln;
 
+// The name of the script in the home directory that is run when the shell starts
+const STARTUP_SCRIPT_NAME: &str = ".rushrc";
+
 fn main() -> Result<()> {
     // The Shell type stores all of the state for the shell, including its configuration,
     // its environment, and other miscellaneous data like command history
@@ -21,20 +26,65 @@ fn main() -> Result<()> {
     // The Dispatcher type is responsible for resolving command names to actual function calls,
     // or executables if needed, and then invoking them with the given arguments
     let dispatcher = Dispatcher::default();
+    // * The Console cannot see the Dispatcher, so the builtin names and signatures are stored on the Shell for completion and hints
+    shell.set_builtin_names(dispatcher.command_names());
+    shell.set_builtin_signatures(dispatcher.command_signatures());
 
     console.enter()?;
+    report_config_warnings(&mut shell, &mut console);
+    run_startup_script(&dispatcher, &mut shell, &mut console);
 
     loop {
-        let line = console.read_line(&shell)?;
+        let line = console.read_line(&mut shell)?;
+        let started = Instant::now();
         let status = dispatcher.eval(&mut shell, &mut console, &line);
-        handle_error(status, &mut shell, &mut console);
+        let elapsed = started.elapsed();
+        shell.set_last_duration(elapsed);
+        handle_error(status, &line, &mut shell, &mut console);
+        console.notify_finished(&shell, &line, elapsed)?;
+        report_finished_jobs(&mut shell, &mut console);
 
         shell.history_add(line);
     }
 }
 
+// Prints any problems that were found in the configuration file when the shell started
+fn report_config_warnings(shell: &mut Shell, console: &mut Console) {
+    for warning in shell.config_mut().take_warnings() {
+        showln!(console, "Configuration warning: {}", warning);
+    }
+}
+
+// Runs the user's startup script, if they have one, so that it can set up aliases, the PATH, and the configuration
+// * A line that fails is reported in the output panel, but the shell still starts, so that the script can be fixed from inside it
+fn run_startup_script(dispatcher: &Dispatcher, shell: &mut Shell, console: &mut Console) {
+    let path = shell.env().HOME().join(STARTUP_SCRIPT_NAME);
+    if !path.is_file() {
+        return;
+    }
+
+    // * Each failing line has already been reported by the time this returns, so the error itself is not shown again
+    _ = dispatcher.eval_file(shell, console, &path.to_string_lossy());
+}
+
+// Prints a notice for each background job that has finished since the last command
+fn report_finished_jobs(shell: &mut Shell, console: &mut Console) {
+    for (job, status) in shell.jobs_mut().reap() {
+        match status.code() {
+            Some(code) => showln!(
+                console,
+                "[{}] Done (exit code {}): {}",
+                job.id,
+                code,
+                job.command
+            ),
+            None => showln!(console, "[{}] Terminated: {}", job.id, job.command),
+        }
+    }
+}
+
 // Prints an appropriate error message for the given error, if applicable
-fn handle_error(error: Result<()>, shell: &mut Shell, console: &mut Console) {
+fn handle_error(error: Result<()>, line: &str, shell: &mut Shell, console: &mut Console) {
     match error {
         Ok(_) => shell.set_success(true),
         Err(e) => {
@@ -51,6 +101,7 @@ fn handle_error(error: Result<()>, shell: &mut Shell, console: &mut Console) {
             }
 
             shell.set_success(false);
+            shell.set_last_error(line.to_string(), error_kind(&e), e);
         }
     }
 }